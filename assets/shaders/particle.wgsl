@@ -0,0 +1,41 @@
+#import bevy_pbr::mesh_view_bindings
+
+struct Vertex {
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+    @location(2) uv: vec2<f32>,
+
+    @location(3) i_position_size: vec4<f32>,
+    @location(4) i_color: vec4<f32>,
+    @location(5) i_uv_rect: vec4<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+    @location(1) uv: vec2<f32>,
+};
+
+@group(2) @binding(0)
+var particle_texture: texture_2d<f32>;
+@group(2) @binding(1)
+var particle_sampler: sampler;
+
+@vertex
+fn vertex(vertex: Vertex) -> VertexOutput {
+    // Particles face the camera, so the quad is spanned by the view's right and up axes.
+    let right = view.view[0].xyz;
+    let up = view.view[1].xyz;
+    let offset = (right * vertex.position.x + up * vertex.position.y) * vertex.i_position_size.w;
+
+    var out: VertexOutput;
+    out.clip_position = view.view_proj * vec4<f32>(vertex.i_position_size.xyz + offset, 1.0);
+    out.color = vertex.i_color;
+    out.uv = mix(vertex.i_uv_rect.xy, vertex.i_uv_rect.zw, vertex.uv);
+    return out;
+}
+
+@fragment
+fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    return in.color * textureSample(particle_texture, particle_sampler, in.uv);
+}
@@ -0,0 +1,338 @@
+use bevy::{prelude::*, reflect::TypeUuid};
+use bevy_asset_loader::prelude::*;
+use bevy_rapier3d::prelude::*;
+use leafwing_input_manager::prelude::*;
+use rand::{seq::SliceRandom, Rng};
+use serde::Deserialize;
+
+use crate::{
+    behavior::{Node, Status},
+    boss::BossBehavior,
+    character::CharacterState,
+    devices::PlayerSlot,
+    game_time::GameTime,
+    hit::LIVE_BALL_SPEED,
+    opening::StartGate,
+    ron_loader::{RonAsset, RonLoader},
+    rules::{match_active, Ruleset},
+    score::Score,
+    team::{Team, TeamTint},
+    tick_timer::TickTimer,
+    Action, Ball, HeldBy, HoldingBall, Player, PlayerName,
+};
+
+/// Where bots stand when there is nothing else to do, as a distance from the center line.
+const HOME_DISTANCE: f32 = 4.0;
+const ARRIVE_DISTANCE: f32 = 0.2;
+/// Skill lost or gained per point the bots are ahead or behind.
+const SKILL_PER_POINT: f32 = 0.1;
+const MIN_SKILL: f32 = 0.1;
+/// How fast skill drifts towards its rubber-banded target, per second.
+const SKILL_DRIFT: f32 = 0.05;
+/// How strongly bot sprites can take on their team's color, so no two bots look quite alike.
+const BOT_TINT_RANGE: (f32, f32) = (0.2, 0.6);
+
+pub struct AiPlugin;
+
+impl Plugin for AiPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<BotBehavior>()
+            .init_asset_loader::<RonLoader<BotBehavior>>()
+            .add_asset::<BossBehavior>()
+            .init_asset_loader::<RonLoader<BossBehavior>>()
+            .add_asset::<NameList>()
+            .init_asset_loader::<RonLoader<NameList>>()
+            .init_resource::<AiSettings>()
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(match_active)
+                    .with_system(dress_bots)
+                    .with_system(adjust_difficulty)
+                    .with_system(drive_bots.after(adjust_difficulty)),
+            );
+    }
+}
+
+pub struct AiSettings {
+    /// Nudges bot skill towards keeping the score close.
+    pub rubber_banding: bool,
+}
+
+impl Default for AiSettings {
+    fn default() -> Self {
+        Self {
+            rubber_banding: true,
+        }
+    }
+}
+
+/// A computer controlled player, driving its [`ActionState`] the way input would.
+#[derive(Component)]
+pub struct Bot {
+    /// The skill the bot was set up with, from zero to one.
+    pub base_skill: f32,
+    /// The skill the bot plays at right now, after difficulty adjustment.
+    pub skill: f32,
+    reacting: Option<TickTimer>,
+    holding: Option<TickTimer>,
+}
+
+impl Bot {
+    pub fn new(skill: f32) -> Self {
+        Self {
+            base_skill: skill,
+            skill,
+            reacting: None,
+            holding: None,
+        }
+    }
+
+    /// How long the bot takes to react to what is going on.
+    fn reaction_seconds(&self) -> f32 {
+        0.6 - 0.45 * self.skill
+    }
+
+    /// How long the bot holds on to a ball before throwing it.
+    fn hold_seconds(&self) -> f32 {
+        1.5 - self.skill
+    }
+
+    /// How far away the bot notices balls flying at it.
+    fn catch_distance(&self) -> f32 {
+        1.0 + 2.0 * self.skill
+    }
+}
+
+/// Rubber-bands bot skill against the score gap, unless the match is competitive.
+fn adjust_difficulty(
+    time: Res<GameTime>,
+    settings: Res<AiSettings>,
+    ruleset: Res<Ruleset>,
+    score: Res<Score>,
+    humans: Query<&Team, (With<Player>, Without<Bot>)>,
+    mut bots: Query<(&mut Bot, &Team)>,
+) {
+    let enabled = settings.rubber_banding && !ruleset.competitive;
+    let best_human = humans.iter().map(|team| score.get(*team)).max();
+    for (mut bot, team) in &mut bots {
+        let target = match best_human {
+            Some(human) if enabled => {
+                let lead = score.get(*team) as f32 - human as f32;
+                (bot.base_skill - lead * SKILL_PER_POINT).clamp(MIN_SKILL, 1.0)
+            }
+            _ => bot.base_skill,
+        };
+        let drift = SKILL_DRIFT * time.delta_seconds();
+        bot.skill += (target - bot.skill).clamp(-drift, drift);
+    }
+}
+
+/// What a bot can check for in its behavior tree.
+#[derive(Deserialize, Clone, Copy)]
+pub enum BotCondition {
+    HasBall,
+    /// Has held on to the ball for as long as its skill allows.
+    ReadyToThrow,
+    BallIncoming,
+    /// Has had time to react to an incoming ball, and noticed it.
+    Reacted,
+    /// A ball is lying around on the bot's own half.
+    LooseBall,
+}
+
+/// What a bot can do in its behavior tree.
+#[derive(Deserialize, Clone, Copy)]
+pub enum BotTask {
+    Throw,
+    Catch,
+    MoveToBall,
+    PickUp,
+    MoveHome,
+}
+
+#[derive(Deserialize, TypeUuid)]
+#[uuid = "3c7d52a4-1f8e-4b6a-9d20-7e5f0a1b8c93"]
+pub struct BotBehavior {
+    pub root: Node<BotCondition, BotTask>,
+}
+
+impl RonAsset for BotBehavior {
+    const EXTENSIONS: &'static [&'static str] = &["bot.ron"];
+}
+
+#[derive(AssetCollection)]
+pub struct AiAssets {
+    #[asset(path = "ai/default.bot.ron")]
+    pub bot: Handle<BotBehavior>,
+    #[asset(path = "ai/finale.boss.ron")]
+    pub boss: Handle<BossBehavior>,
+    #[asset(path = "ai/bots.names.ron")]
+    pub names: Handle<NameList>,
+}
+
+/// Names for bots to pick from.
+#[derive(Deserialize, TypeUuid)]
+#[uuid = "a4e9c1d7-5b32-4f08-8c6e-d19f2b7a3e50"]
+pub struct NameList {
+    pub names: Vec<String>,
+}
+
+impl RonAsset for NameList {
+    const EXTENSIONS: &'static [&'static str] = &["names.ron"];
+}
+
+/// Gives new bots a name nobody else is using and a shade of their team's color of their own.
+fn dress_bots(
+    mut commands: Commands,
+    assets: Res<AiAssets>,
+    name_lists: Res<Assets<NameList>>,
+    // Players handing over to a bot keep their own name and look.
+    bots: Query<(Entity, &Children), (Added<Bot>, Without<PlayerSlot>)>,
+    names: Query<&PlayerName>,
+    mut tints: Query<&mut TeamTint>,
+) {
+    let mut rng = rand::thread_rng();
+    let mut free = name_lists
+        .get(&assets.names)
+        .map(|list| {
+            list.names
+                .iter()
+                .filter(|name| names.iter().all(|taken| taken.0 != **name))
+                .cloned()
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    free.shuffle(&mut rng);
+
+    for (index, (bot, children)) in bots.iter().enumerate() {
+        let name = free.pop().unwrap_or_else(|| format!("Bot {}", index + 1));
+        commands.entity(bot).insert(PlayerName(name));
+        for child in children {
+            if let Ok(mut tint) = tints.get_mut(*child) {
+                tint.strength = rng.gen_range(BOT_TINT_RANGE.0..=BOT_TINT_RANGE.1);
+            }
+        }
+    }
+}
+
+pub fn drive_bots(
+    time: Res<GameTime>,
+    assets: Res<AiAssets>,
+    behaviors: Res<Assets<BotBehavior>>,
+    mut bots: Query<(
+        &mut Bot,
+        &mut ActionState<Action>,
+        &CharacterState,
+        &GlobalTransform,
+        Option<&HoldingBall>,
+    )>,
+    balls: Query<(&GlobalTransform, Option<&Velocity>), (With<Ball>, Without<HeldBy>)>,
+    gate: Option<Res<StartGate>>,
+) {
+    let behavior = if let Some(behavior) = behaviors.get(&assets.bot) {
+        behavior
+    } else {
+        return;
+    };
+    let mut rng = rand::thread_rng();
+    for (mut bot, mut action_state, state, transform, has_ball) in &mut bots {
+        action_state.release_all();
+        // Bots wait for GO rather than give away a false start.
+        if !state.can_move() || gate.is_some() {
+            continue;
+        }
+        let position = transform.translation();
+        let side = if position.y > 0.0 { 1.0 } else { -1.0 };
+        let has_ball = has_ball.is_some();
+
+        let mut ready_to_throw = false;
+        if has_ball {
+            let hold = bot.hold_seconds();
+            let timer = bot
+                .holding
+                .get_or_insert_with(|| TickTimer::from_seconds(hold));
+            ready_to_throw = timer.tick(time.ticks()).finished();
+        } else {
+            bot.holding = None;
+        }
+
+        let incoming = balls.iter().any(|(ball, velocity)| {
+            let offset = position - ball.translation();
+            velocity.map_or(false, |velocity| {
+                velocity.linvel.length() >= LIVE_BALL_SPEED
+                    && velocity.linvel.dot(offset) > 0.0
+                    && offset.length() <= bot.catch_distance()
+            })
+        });
+        let mut reacted = false;
+        if incoming {
+            let reaction = bot.reaction_seconds();
+            let skill = bot.skill;
+            let timer = bot
+                .reacting
+                .get_or_insert_with(|| TickTimer::from_seconds(reaction));
+            reacted = timer.tick(time.ticks()).just_finished() && rng.gen::<f32>() < skill;
+        } else {
+            bot.reacting = None;
+        }
+
+        let loose_ball = balls
+            .iter()
+            .map(|(ball, _)| ball.translation())
+            .filter(|ball| ball.y * side > 0.0)
+            .min_by(|a, b| a.distance(position).total_cmp(&b.distance(position)));
+        let home = Vec3::new(0.0, side * HOME_DISTANCE, position.z);
+
+        let mut threw = false;
+        behavior.root.tick(
+            &mut |condition| match condition {
+                BotCondition::HasBall => has_ball,
+                BotCondition::ReadyToThrow => ready_to_throw,
+                BotCondition::BallIncoming => incoming,
+                BotCondition::Reacted => reacted,
+                BotCondition::LooseBall => loose_ball.is_some(),
+            },
+            &mut |task| match task {
+                BotTask::Throw => {
+                    action_state.press(Action::Throw);
+                    threw = true;
+                    Status::Success
+                }
+                BotTask::Catch => {
+                    action_state.press(Action::Catch);
+                    Status::Success
+                }
+                BotTask::MoveToBall => loose_ball.map_or(Status::Failure, |ball| {
+                    move_towards(&mut action_state, position, ball)
+                }),
+                BotTask::PickUp => {
+                    action_state.press(Action::Pickup);
+                    Status::Success
+                }
+                BotTask::MoveHome => move_towards(&mut action_state, position, home),
+            },
+        );
+        if threw {
+            bot.holding = None;
+        }
+    }
+}
+
+fn move_towards(action_state: &mut ActionState<Action>, position: Vec3, target: Vec3) -> Status {
+    let offset = (target - position).truncate();
+    if offset.y < -ARRIVE_DISTANCE {
+        action_state.press(Action::MoveLeft);
+    } else if offset.y > ARRIVE_DISTANCE {
+        action_state.press(Action::MoveRight);
+    }
+    if offset.x < -ARRIVE_DISTANCE {
+        action_state.press(Action::MoveAway);
+    } else if offset.x > ARRIVE_DISTANCE {
+        action_state.press(Action::MoveTowards);
+    }
+    if offset.length() <= ARRIVE_DISTANCE {
+        Status::Success
+    } else {
+        Status::Running
+    }
+}
@@ -0,0 +1,132 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::{
+    character::CharacterState,
+    sprite_animation::{ClipSet, SpriteAnimation},
+    HoldingBall,
+};
+
+/// Characters moving slower than this across the court stand idle rather than run.
+const RUN_SPEED: f32 = 1.0;
+
+pub struct CharacterAnimationPlugin;
+
+impl Plugin for CharacterAnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(drive_animations);
+    }
+}
+
+/// Picks the clip a character's sprite plays from what the character is doing.
+#[derive(Component, Default)]
+pub struct AnimationMachine {
+    state: AnimationState,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+enum AnimationState {
+    #[default]
+    Idle,
+    Run,
+    Jump,
+    Fall,
+    Throw,
+    Catch,
+    Hit,
+    KnockedOut,
+}
+
+impl AnimationState {
+    fn from_character(state: CharacterState, speed: f32) -> Self {
+        match state {
+            CharacterState::Idle | CharacterState::Run if speed >= RUN_SPEED => Self::Run,
+            CharacterState::Idle | CharacterState::Run => Self::Idle,
+            CharacterState::Jump => Self::Jump,
+            CharacterState::Fall => Self::Fall,
+            CharacterState::Throwing(_) => Self::Throw,
+            CharacterState::Catching(_) => Self::Catch,
+            CharacterState::Stunned(_) => Self::Hit,
+            CharacterState::Respawning(_) | CharacterState::Eliminated => Self::KnockedOut,
+        }
+    }
+
+    fn clip(self) -> &'static str {
+        match self {
+            Self::Idle => "idle",
+            Self::Run => "run",
+            Self::Jump => "jump",
+            Self::Fall => "fall",
+            Self::Throw => "throw",
+            Self::Catch => "catch",
+            Self::Hit => "hit",
+            Self::KnockedOut => "knocked_out",
+        }
+    }
+
+    /// One-shot clips play through to the end even once the move is over, before handing back
+    /// to locomotion.
+    fn is_one_shot(self) -> bool {
+        matches!(self, Self::Throw | Self::Catch)
+    }
+
+    /// Which states can cut a playing one-shot short. Anything at least as urgent can.
+    fn priority(self) -> u8 {
+        match self {
+            Self::Idle | Self::Run | Self::Jump | Self::Fall => 0,
+            Self::Throw | Self::Catch => 1,
+            Self::Hit => 2,
+            Self::KnockedOut => 3,
+        }
+    }
+
+    /// Locomotion has variants for carrying a ball, where the clip set has them.
+    fn clip_name(self, holding: bool, clips: Option<&ClipSet>) -> String {
+        let clip = self.clip();
+        if holding && self.priority() == 0 {
+            let holding_clip = format!("{clip}_holding");
+            if clips.map_or(false, |clips| clips.clips.contains_key(&holding_clip)) {
+                return holding_clip;
+            }
+        }
+        clip.to_string()
+    }
+}
+
+fn drive_animations(
+    clip_sets: Res<Assets<ClipSet>>,
+    mut characters: Query<(
+        &CharacterState,
+        &Velocity,
+        Option<&HoldingBall>,
+        &mut AnimationMachine,
+        &Children,
+    )>,
+    mut sprites: Query<&mut SpriteAnimation>,
+) {
+    for (state, velocity, holding, mut machine, children) in &mut characters {
+        let target = AnimationState::from_character(*state, velocity.linvel.truncate().length());
+        for child in children.iter() {
+            let mut animation = if let Ok(animation) = sprites.get_mut(*child) {
+                animation
+            } else {
+                continue;
+            };
+            let busy = machine.state.is_one_shot() && !animation.is_finished();
+            if target != machine.state && (!busy || target.priority() >= machine.state.priority()) {
+                machine.state = target;
+                if target.is_one_shot() {
+                    animation.restart(target.clip());
+                    continue;
+                }
+            }
+            if machine.state.is_one_shot() {
+                continue;
+            }
+            let clip = machine
+                .state
+                .clip_name(holding.is_some(), clip_sets.get(animation.clips()));
+            animation.play(&clip);
+        }
+    }
+}
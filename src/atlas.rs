@@ -0,0 +1,146 @@
+use bevy::{
+    asset::{AssetLoader, AssetPath, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+    utils::{BoxedFuture, HashMap},
+};
+use serde::Deserialize;
+
+pub struct AtlasPlugin;
+
+impl Plugin for AtlasPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<AtlasManifest>()
+            .init_asset_loader::<AtlasManifestLoader>()
+            .init_resource::<PackedAtlases>()
+            .add_system(pack_atlases);
+    }
+}
+
+/// A set of small named sprites to be packed into a single texture atlas when loaded, so
+/// everything drawn with them shares one texture.
+#[derive(Deserialize, TypeUuid)]
+#[uuid = "d2a8f6c1-4b7e-4e39-a5d0-8c1f3b9e6a27"]
+pub struct AtlasManifest {
+    /// Image paths by sprite name.
+    sprites: HashMap<String, String>,
+    #[serde(skip)]
+    images: Vec<(String, Handle<Image>)>,
+}
+
+#[derive(Default)]
+struct AtlasManifestLoader;
+
+impl AssetLoader for AtlasManifestLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let mut manifest: AtlasManifest = ron::de::from_bytes(bytes)?;
+            manifest.images = manifest
+                .sprites
+                .iter()
+                .map(|(name, path)| (name.clone(), load_context.get_handle(path.as_str())))
+                .collect();
+            let dependencies = manifest
+                .sprites
+                .values()
+                .map(|path| AssetPath::from(path.as_str()).to_owned())
+                .collect();
+            load_context
+                .set_default_asset(LoadedAsset::new(manifest).with_dependencies(dependencies));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["atlas.ron"]
+    }
+}
+
+/// The atlases packed from each loaded [`AtlasManifest`].
+#[derive(Default)]
+pub struct PackedAtlases(HashMap<Handle<AtlasManifest>, PackedAtlas>);
+
+impl PackedAtlases {
+    pub fn get(&self, manifest: &Handle<AtlasManifest>) -> Option<&PackedAtlas> {
+        self.0.get(manifest)
+    }
+}
+
+pub struct PackedAtlas {
+    pub atlas: Handle<TextureAtlas>,
+    indices: HashMap<String, usize>,
+}
+
+impl PackedAtlas {
+    /// The atlas index of the sprite with this name, as used by `AtlasSprite3d`.
+    pub fn index(&self, name: &str) -> Option<usize> {
+        self.indices.get(name).copied()
+    }
+}
+
+/// Packs manifests once all of their images have loaded, and again whenever one is changed.
+fn pack_atlases(
+    mut events: EventReader<AssetEvent<AtlasManifest>>,
+    mut pending: Local<Vec<Handle<AtlasManifest>>>,
+    manifests: Res<Assets<AtlasManifest>>,
+    mut images: ResMut<Assets<Image>>,
+    mut atlases: ResMut<Assets<TextureAtlas>>,
+    mut packed: ResMut<PackedAtlases>,
+) {
+    for event in events.iter() {
+        match event {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => {
+                pending.push(handle.clone_weak());
+            }
+            AssetEvent::Removed { handle } => {
+                packed.0.remove(handle);
+            }
+        }
+    }
+
+    pending.retain(|handle| {
+        let manifest = if let Some(manifest) = manifests.get(handle) {
+            manifest
+        } else {
+            return false;
+        };
+        if manifest
+            .images
+            .iter()
+            .any(|(_, image)| images.get(image).is_none())
+        {
+            return true;
+        }
+
+        let mut builder = TextureAtlasBuilder::default();
+        for (_, image) in &manifest.images {
+            if let Some(texture) = images.get(image) {
+                builder.add_texture(image.clone(), texture);
+            }
+        }
+        let atlas = match builder.finish(&mut images) {
+            Ok(atlas) => atlas,
+            Err(error) => {
+                warn!("Could not pack texture atlas: {error:?}");
+                return false;
+            }
+        };
+        let indices = manifest
+            .images
+            .iter()
+            .filter_map(|(name, image)| Some((name.clone(), atlas.get_texture_index(image)?)))
+            .collect();
+        packed.0.insert(
+            handle.clone_weak(),
+            PackedAtlas {
+                atlas: atlases.add(atlas),
+                indices,
+            },
+        );
+        false
+    });
+}
@@ -0,0 +1,199 @@
+use std::fs;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    celebration::MatchOver,
+    game_time::{HoldPhysics, PhysicsHold},
+    hit::Health,
+    input::{InputContext, InputLayer},
+    rules::{match_active, MatchMode, Ruleset},
+    score::Score,
+    team::Team,
+    training::{LoadScenario, Scenario, SnapshotBalls, SnapshotPlayers},
+    ui::{
+        text::styled_text,
+        widgets::{self, WidgetEvent, WidgetStyle},
+    },
+    GameState, Player,
+};
+
+/// Where the match in progress is saved, relative to the working directory.
+const AUTOSAVE_PATH: &str = "autosave.ron";
+
+pub struct AutosavePlugin;
+
+impl Plugin for AutosavePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Autosaving>()
+            .add_system_set(SystemSet::on_enter(GameState::Ready).with_system(offer_resume))
+            .add_system(resume_prompt_events)
+            .add_system(discard_finished_match)
+            .add_system(autosave.with_run_criteria(match_active));
+    }
+}
+
+/// Everything needed to pick a match back up after a crash or quit.
+#[derive(Serialize, Deserialize, Clone)]
+struct MatchSave {
+    mode: MatchMode,
+    scenario: Scenario,
+    /// Points by team index.
+    score: Vec<(usize, u32)>,
+    /// Health left for each player, in the same order as the scenario's players.
+    health: Vec<u32>,
+}
+
+/// Whether the match is being saved as it goes. Held off until an earlier save has been resumed
+/// or thrown away, so it isn't overwritten before the player gets to choose.
+#[derive(Default)]
+struct Autosaving(bool);
+
+#[derive(Component)]
+struct ResumePrompt(MatchSave);
+
+#[derive(Component)]
+enum ResumeButton {
+    Resume,
+    Discard,
+}
+
+/// Only long local matches are worth picking back up.
+fn autosaves(mode: MatchMode) -> bool {
+    matches!(mode, MatchMode::Versus | MatchMode::Survival)
+}
+
+fn read_autosave() -> Option<MatchSave> {
+    let bytes = fs::read(AUTOSAVE_PATH).ok()?;
+    ron::de::from_bytes(&bytes)
+        .map_err(|error| warn!("Couldn't parse the autosave: {error}"))
+        .ok()
+}
+
+fn discard_autosave() {
+    if let Err(error) = fs::remove_file(AUTOSAVE_PATH) {
+        if error.kind() != std::io::ErrorKind::NotFound {
+            warn!("Couldn't remove the autosave: {error}");
+        }
+    }
+}
+
+/// Offers to pick up where the last match of this mode left off. Gameplay is held, opening
+/// countdown and power-up timers included, until it's answered.
+fn offer_resume(
+    mut commands: Commands,
+    ruleset: Res<Ruleset>,
+    style: Res<WidgetStyle>,
+    mut autosaving: ResMut<Autosaving>,
+    mut holds: EventWriter<HoldPhysics>,
+) {
+    autosaving.0 = false;
+    if !autosaves(ruleset.mode) {
+        return;
+    }
+    let save = if let Some(save) = read_autosave().filter(|save| save.mode == ruleset.mode) {
+        save
+    } else {
+        autosaving.0 = true;
+        return;
+    };
+    holds.send(HoldPhysics {
+        hold: PhysicsHold::ResumePrompt,
+        held: true,
+    });
+    widgets::panel(&mut commands, &style)
+        .insert_bundle((ResumePrompt(save), InputLayer(InputContext::Menu)))
+        .with_children(|parent| {
+            parent.spawn_bundle(styled_text("Unfinished match", "title"));
+            widgets::button(parent, &style, "Resume").insert(ResumeButton::Resume);
+            widgets::button(parent, &style, "Start over").insert(ResumeButton::Discard);
+        });
+}
+
+fn resume_prompt_events(
+    mut commands: Commands,
+    mut events: EventReader<WidgetEvent>,
+    mut autosaving: ResMut<Autosaving>,
+    mut score: ResMut<Score>,
+    mut holds: EventWriter<HoldPhysics>,
+    mut loads: EventWriter<LoadScenario>,
+    buttons: Query<&ResumeButton>,
+    prompts: Query<(Entity, &ResumePrompt)>,
+    mut players: Query<(Entity, &mut Health), With<Player>>,
+) {
+    for event in events.iter() {
+        let button = if let WidgetEvent::Pressed(entity) = event {
+            if let Ok(button) = buttons.get(*entity) {
+                button
+            } else {
+                continue;
+            }
+        } else {
+            continue;
+        };
+        for (prompt, ResumePrompt(save)) in &prompts {
+            if let ResumeButton::Resume = button {
+                info!("Resuming the autosaved match");
+                loads.send(LoadScenario(save.scenario.clone()));
+                score.0 = save
+                    .score
+                    .iter()
+                    .map(|(team, points)| (Team(*team), *points))
+                    .collect();
+                let mut players = players.iter_mut().collect::<Vec<_>>();
+                players.sort_by_key(|(entity, _)| *entity);
+                for ((_, health), current) in players.iter_mut().zip(&save.health) {
+                    health.current = (*current).min(health.max);
+                }
+            } else {
+                discard_autosave();
+            }
+            commands.entity(prompt).despawn_recursive();
+        }
+        autosaving.0 = true;
+        holds.send(HoldPhysics {
+            hold: PhysicsHold::ResumePrompt,
+            held: false,
+        });
+    }
+}
+
+/// Saves the match each time a point is scored, so it can be resumed from the last point.
+fn autosave(
+    ruleset: Res<Ruleset>,
+    autosaving: Res<Autosaving>,
+    score: Res<Score>,
+    players: SnapshotPlayers,
+    balls: SnapshotBalls,
+    health: Query<(Entity, &Health), With<Player>>,
+) {
+    if !autosaving.0 || !autosaves(ruleset.mode) || !score.is_changed() {
+        return;
+    }
+    let mut health = health.iter().collect::<Vec<_>>();
+    health.sort_by_key(|(entity, _)| *entity);
+    let save = MatchSave {
+        mode: ruleset.mode,
+        scenario: Scenario::capture("Autosave".to_string(), &players, &balls),
+        score: score
+            .0
+            .iter()
+            .map(|(team, points)| (team.0, *points))
+            .collect(),
+        health: health.iter().map(|(_, health)| health.current).collect(),
+    };
+    let result = ron::ser::to_string_pretty(&save, ron::ser::PrettyConfig::default())
+        .map_err(|error| error.to_string())
+        .and_then(|ron| fs::write(AUTOSAVE_PATH, ron).map_err(|error| error.to_string()));
+    if let Err(error) = result {
+        warn!("Couldn't autosave the match: {error}");
+    }
+}
+
+/// A match that was played to the end has nothing to resume.
+fn discard_finished_match(mut events: EventReader<MatchOver>, autosaving: Res<Autosaving>) {
+    if events.iter().last().is_some() && autosaving.0 {
+        discard_autosave();
+    }
+}
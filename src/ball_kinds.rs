@@ -0,0 +1,289 @@
+use std::collections::{HashMap, HashSet};
+
+use bevy::{prelude::*, reflect::TypeUuid};
+use bevy_rapier3d::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    ball_spawner::BallSpawner,
+    game_time::GameTime,
+    hit::kill_balls,
+    interactions::BallKind,
+    ron_loader::{RonAsset, RonLoader},
+    rules::{match_active, MatchMode, Ruleset},
+    spawn_ball,
+    stage::{ActiveStage, Ground},
+    tick_timer::TickTimer,
+    Ball, BallState, DataAssets, GameState, ThrownBy, BALL_DROP_HEIGHT,
+};
+
+/// How far either side of a split ball's course the pieces fly off, in radians.
+const SPLIT_SPREAD: f32 = 0.5;
+
+pub struct BallKindsPlugin;
+
+impl Plugin for BallKindsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<BallKindTable>()
+            .init_asset_loader::<RonLoader<BallKindTable>>()
+            .add_system_set(SystemSet::on_enter(GameState::Ready).with_system(start_power_ups))
+            .add_system(dress_balls)
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(match_active)
+                    .with_system(split_balls.before(kill_balls))
+                    .with_system(vanish_balls.after(kill_balls))
+                    .with_system(spawn_power_ups),
+            );
+    }
+}
+
+/// How a kind of ball looks, flies and hits.
+#[derive(Deserialize, Clone, Copy)]
+pub struct BallProperties {
+    /// Size relative to a standard ball.
+    pub scale: f32,
+    pub density: f32,
+    /// Multiplies the speed the ball is thrown at.
+    pub speed: f32,
+    /// Multiplies how far a hit knocks the player back.
+    pub knockback: f32,
+    pub color: Color,
+    /// Breaks into this many balls of this kind when it first bounces while live.
+    #[serde(default)]
+    pub split: Option<(BallKind, u32)>,
+    /// Disappears as soon as it goes dead after being thrown, so it's only good for one throw.
+    #[serde(default)]
+    pub vanishes: bool,
+}
+
+impl Default for BallProperties {
+    fn default() -> Self {
+        Self {
+            scale: 1.0,
+            density: 1.0,
+            speed: 1.0,
+            knockback: 1.0,
+            color: Color::WHITE,
+            split: None,
+            vanishes: false,
+        }
+    }
+}
+
+/// Every kind of ball's properties, and how power-up balls are brought into a match.
+#[derive(Deserialize, TypeUuid)]
+#[uuid = "5e27c9b1-8d43-4a6f-b0e2-71c4d9f3a865"]
+pub struct BallKindTable {
+    kinds: HashMap<BallKind, BallProperties>,
+    /// Seconds between power-up balls being dropped onto the court.
+    power_up_seconds: f32,
+    /// The kinds a power-up ball can be, weighted by how often each turns up.
+    power_ups: Vec<(BallKind, f32)>,
+}
+
+impl BallKindTable {
+    /// The properties of a kind, falling back to those of a standard ball if the table leaves it
+    /// out.
+    pub fn get(&self, kind: BallKind) -> BallProperties {
+        self.kinds.get(&kind).copied().unwrap_or_default()
+    }
+}
+
+impl RonAsset for BallKindTable {
+    const EXTENSIONS: &'static [&'static str] = &["balls.ron"];
+}
+
+/// Counts down to the next power-up ball.
+struct PowerUpTimer(TickTimer);
+
+/// Sizes, weighs and colors balls for their kind whenever it changes.
+fn dress_balls(
+    mut commands: Commands,
+    mut cached_materials: Local<HashMap<BallKind, Handle<StandardMaterial>>>,
+    data: Option<Res<DataAssets>>,
+    tables: Res<Assets<BallKindTable>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut balls: Query<
+        (
+            Entity,
+            &BallKind,
+            &mut Transform,
+            &mut Handle<StandardMaterial>,
+        ),
+        (With<Ball>, Changed<BallKind>),
+    >,
+) {
+    let table = if let Some(table) = data.and_then(|data| tables.get(&data.ball_kinds)) {
+        table
+    } else {
+        return;
+    };
+    for (ball, kind, mut transform, mut material) in &mut balls {
+        let properties = table.get(*kind);
+        transform.scale = Vec3::splat(properties.scale);
+        *material = cached_materials
+            .entry(*kind)
+            .or_insert_with(|| materials.add(properties.color.into()))
+            .clone();
+        commands
+            .entity(ball)
+            .insert(ColliderMassProperties::Density(properties.density));
+    }
+}
+
+/// Breaks balls that split into pieces when they bounce, each piece still live and flying off
+/// to either side of where the ball was heading.
+fn split_balls(
+    mut commands: Commands,
+    mut collisions: EventReader<CollisionEvent>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    data: Res<DataAssets>,
+    tables: Res<Assets<BallKindTable>>,
+    balls: Query<
+        (
+            &GlobalTransform,
+            &Velocity,
+            &BallKind,
+            &BallState,
+            Option<&ThrownBy>,
+        ),
+        With<Ball>,
+    >,
+    ground: Query<(), With<Ground>>,
+) {
+    let table = if let Some(table) = tables.get(&data.ball_kinds) {
+        table
+    } else {
+        return;
+    };
+    for event in collisions.iter() {
+        let ball = match event {
+            CollisionEvent::Started(e1, e2, _) if ground.contains(*e1) => *e2,
+            CollisionEvent::Started(e1, e2, _) if ground.contains(*e2) => *e1,
+            _ => continue,
+        };
+        let (transform, velocity, kind, state, thrown_by) = if let Ok(ball) = balls.get(ball) {
+            ball
+        } else {
+            continue;
+        };
+        let (piece_kind, count) = if let Some(split) = table.get(*kind).split {
+            split
+        } else {
+            continue;
+        };
+        if *state != BallState::Live || count == 0 {
+            continue;
+        }
+
+        info!("Ball {ball:?} split into {count}");
+        commands.entity(ball).despawn_recursive();
+        let course = velocity.linvel.truncate();
+        for index in 0..count {
+            let angle = if count == 1 {
+                0.0
+            } else {
+                SPLIT_SPREAD * (2.0 * index as f32 / (count - 1) as f32 - 1.0)
+            };
+            let linvel = Vec2::from_angle(angle)
+                .rotate(course)
+                .extend(velocity.linvel.z.abs());
+            let piece = spawn_ball(transform.translation(), &mut commands, &mut meshes);
+            commands.entity(piece).insert_bundle((
+                Velocity::linear(linvel),
+                BallState::Live,
+                piece_kind,
+            ));
+            if let Some(ThrownBy(thrower)) = thrown_by {
+                commands.entity(piece).insert(ThrownBy(*thrower));
+            }
+        }
+    }
+}
+
+/// Removes balls that only last until they go dead, like power-ups, the pieces of a split ball
+/// and the boss's barrage.
+///
+/// Only a throw running out counts, so a ball dropped from someone's hands is still there to
+/// pick up.
+fn vanish_balls(
+    mut commands: Commands,
+    data: Res<DataAssets>,
+    tables: Res<Assets<BallKindTable>>,
+    mut live: Local<HashSet<Entity>>,
+    balls: Query<(Entity, &BallKind, &BallState), With<Ball>>,
+) {
+    let table = if let Some(table) = tables.get(&data.ball_kinds) {
+        table
+    } else {
+        return;
+    };
+    let was_live = std::mem::take(&mut *live);
+    for (ball, kind, state) in &balls {
+        match state {
+            BallState::Live => {
+                live.insert(ball);
+            }
+            BallState::Dead if was_live.contains(&ball) && table.get(*kind).vanishes => {
+                commands.entity(ball).despawn_recursive();
+            }
+            BallState::Dead | BallState::Held => {}
+        }
+    }
+}
+
+fn start_power_ups(
+    mut commands: Commands,
+    data: Res<DataAssets>,
+    tables: Res<Assets<BallKindTable>>,
+    ruleset: Res<Ruleset>,
+) {
+    commands.remove_resource::<PowerUpTimer>();
+    if !ruleset.power_ups || ruleset.mode == MatchMode::Training {
+        return;
+    }
+    if let Some(table) = tables.get(&data.ball_kinds) {
+        commands.insert_resource(PowerUpTimer(TickTimer::from_seconds(
+            table.power_up_seconds,
+        )));
+    }
+}
+
+/// Drops a power-up ball onto the center line every so often.
+fn spawn_power_ups(
+    mut commands: Commands,
+    time: Res<GameTime>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    data: Res<DataAssets>,
+    tables: Res<Assets<BallKindTable>>,
+    timer: Option<ResMut<PowerUpTimer>>,
+    spawner: Option<ResMut<BallSpawner>>,
+    stage: Option<Res<ActiveStage>>,
+) {
+    let (mut timer, mut spawner, stage, table) =
+        if let (Some(timer), Some(spawner), Some(stage), Some(table)) =
+            (timer, spawner, stage, tables.get(&data.ball_kinds))
+        {
+            (timer, spawner, stage, table)
+        } else {
+            return;
+        };
+    if !timer.0.tick(time.ticks()).just_finished() {
+        return;
+    }
+    timer.0 = TickTimer::from_seconds(table.power_up_seconds);
+    let kind = if let Some(kind) = spawner.pick_weighted(&table.power_ups) {
+        kind
+    } else {
+        return;
+    };
+    info!("Dropping in a {kind:?} ball");
+    let position = spawner.neutral(stage.size);
+    let ball = spawn_ball(
+        position.extend(BALL_DROP_HEIGHT),
+        &mut commands,
+        &mut meshes,
+    );
+    commands.entity(ball).insert(kind);
+}
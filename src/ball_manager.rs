@@ -0,0 +1,94 @@
+use bevy::prelude::*;
+
+use crate::{
+    ball_kinds::BallKindTable,
+    ball_spawner::BallSpawner,
+    game_time::GameTime,
+    interactions::BallKind,
+    rules::{match_active, MatchMode, Ruleset},
+    spawn_ball,
+    stage::ActiveStage,
+    tick_timer::TickTimer,
+    Ball, DataAssets, GameState, BALL_DROP_HEIGHT,
+};
+
+/// How long a lost ball is out of play before it's dropped back in.
+const RESPAWN_SECONDS: f32 = 2.0;
+
+pub struct BallManagerPlugin;
+
+impl Plugin for BallManagerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BallRespawns>()
+            .add_system_set(SystemSet::on_enter(GameState::Ready).with_system(clear_respawns))
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(match_active)
+                    .with_system(keep_ball_count)
+                    .with_system(respawn_balls.after(keep_ball_count)),
+            );
+    }
+}
+
+/// Balls that left play, waiting to be dropped back in on the center line.
+#[derive(Default)]
+struct BallRespawns {
+    pending: Vec<TickTimer>,
+}
+
+fn clear_respawns(mut respawns: ResMut<BallRespawns>) {
+    respawns.pending.clear();
+}
+
+/// Queues a respawn for every ball missing from the ruleset's count, whether loose, in hand or
+/// already on its way back. Training sets up its own balls, so it's left alone.
+///
+/// Only the match's own balls count. Power-ups, the pieces of split balls and the boss's
+/// barrage all vanish once they go dead, so they don't hold back a lost ball's replacement.
+fn keep_ball_count(
+    ruleset: Res<Ruleset>,
+    data: Res<DataAssets>,
+    tables: Res<Assets<BallKindTable>>,
+    mut respawns: ResMut<BallRespawns>,
+    balls: Query<&BallKind, With<Ball>>,
+) {
+    if ruleset.mode == MatchMode::Training {
+        return;
+    }
+    let table = tables.get(&data.ball_kinds);
+    let regular = |kind: BallKind| !table.map_or(false, |table| table.get(kind).vanishes);
+    let in_play = balls.iter().filter(|kind| regular(**kind)).count() + respawns.pending.len();
+    for _ in in_play..ruleset.ball_count {
+        respawns
+            .pending
+            .push(TickTimer::from_seconds(RESPAWN_SECONDS));
+    }
+}
+
+fn respawn_balls(
+    mut commands: Commands,
+    time: Res<GameTime>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut respawns: ResMut<BallRespawns>,
+    spawner: Option<ResMut<BallSpawner>>,
+    stage: Option<Res<ActiveStage>>,
+) {
+    let (mut spawner, stage) = if let (Some(spawner), Some(stage)) = (spawner, stage) {
+        (spawner, stage)
+    } else {
+        return;
+    };
+    let ticks = time.ticks();
+    respawns.pending.retain_mut(|timer| {
+        if !timer.tick(ticks).finished() {
+            return true;
+        }
+        let position = spawner.neutral(stage.size);
+        spawn_ball(
+            position.extend(BALL_DROP_HEIGHT),
+            &mut commands,
+            &mut meshes,
+        );
+        false
+    });
+}
@@ -0,0 +1,110 @@
+use bevy::{prelude::*, reflect::TypeUuid};
+use bevy_rapier3d::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    ron_loader::{RonAsset, RonLoader},
+    Ball,
+};
+
+pub struct BallPhysicsPlugin;
+
+impl Plugin for BallPhysicsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<BallPhysicsConfig>()
+            .init_asset_loader::<RonLoader<BallPhysicsConfig>>()
+            .init_resource::<BallPhysicsConfig>()
+            .init_resource::<BallPhysicsHandle>()
+            .add_system(load_ball_physics)
+            .add_system(apply_ball_physics.after(load_ball_physics));
+    }
+}
+
+/// How balls bounce, slide and fly, read from a data file so the feel can be tuned without
+/// recompiling. Changes to the file apply to balls already on the court too.
+#[derive(Deserialize, TypeUuid, Clone, Copy)]
+#[uuid = "c3a81f5e-2d67-4b90-8e14-9f0b6d2c7a41"]
+pub struct BallPhysicsConfig {
+    pub restitution: f32,
+    pub friction: f32,
+    pub linear_damping: f32,
+    pub angular_damping: f32,
+    pub gravity_scale: f32,
+}
+
+impl Default for BallPhysicsConfig {
+    fn default() -> Self {
+        Self {
+            restitution: 0.8,
+            friction: 0.5,
+            linear_damping: 0.0,
+            angular_damping: 0.0,
+            gravity_scale: 1.0,
+        }
+    }
+}
+
+impl RonAsset for BallPhysicsConfig {
+    const EXTENSIONS: &'static [&'static str] = &["ball_physics.ron"];
+}
+
+struct BallPhysicsHandle(Handle<BallPhysicsConfig>);
+
+impl FromWorld for BallPhysicsHandle {
+    fn from_world(world: &mut World) -> Self {
+        Self(
+            world
+                .resource::<AssetServer>()
+                .load("rules/default.ball_physics.ron"),
+        )
+    }
+}
+
+fn load_ball_physics(
+    mut events: EventReader<AssetEvent<BallPhysicsConfig>>,
+    handle: Res<BallPhysicsHandle>,
+    configs: Res<Assets<BallPhysicsConfig>>,
+    mut config: ResMut<BallPhysicsConfig>,
+) {
+    for event in events.iter() {
+        let changed = match event {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => handle,
+            AssetEvent::Removed { .. } => continue,
+        };
+        if *changed != handle.0 {
+            continue;
+        }
+        if let Some(loaded) = configs.get(&handle.0) {
+            info!("Applying ball physics");
+            *config = *loaded;
+        }
+    }
+}
+
+/// Gives new balls their physics material, and every ball a new one when the config changes.
+fn apply_ball_physics(
+    mut commands: Commands,
+    config: Res<BallPhysicsConfig>,
+    balls: Query<Entity, With<Ball>>,
+    new_balls: Query<Entity, Added<Ball>>,
+) {
+    let balls = if config.is_changed() {
+        balls.iter().collect::<Vec<_>>()
+    } else {
+        new_balls.iter().collect()
+    };
+    for ball in balls {
+        commands.entity(ball).insert_bundle((
+            Restitution {
+                coefficient: config.restitution,
+                combine_rule: CoefficientCombineRule::Max,
+            },
+            Friction::coefficient(config.friction),
+            Damping {
+                linear_damping: config.linear_damping,
+                angular_damping: config.angular_damping,
+            },
+            GravityScale(config.gravity_scale),
+        ));
+    }
+}
@@ -0,0 +1,105 @@
+use bevy::prelude::*;
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+use crate::rules::Ruleset;
+
+/// How far from the edges of the court balls are placed.
+const EDGE_MARGIN: f32 = 0.5;
+/// Keeps balls placed on a half from landing right on the center line.
+const CENTER_MARGIN: f32 = 1.0;
+
+/// Where neutral balls are placed at the start of a match.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BallSpawnPattern {
+    /// Spread evenly along the center line.
+    CenterLine,
+    /// Scattered over one half and mirrored onto the other, with any odd ball on the center line.
+    Mirrored,
+    /// Scattered over both halves in turn, starting with a random side.
+    Alternating,
+}
+
+/// Places balls with a seeded RNG, so neither team ever gets the better spots.
+pub struct BallSpawner {
+    rng: StdRng,
+    pattern: BallSpawnPattern,
+    /// The side the next ball goes on, for [`BallSpawnPattern::Alternating`].
+    next_side: f32,
+}
+
+impl BallSpawner {
+    pub fn new(ruleset: &Ruleset) -> Self {
+        let seed = ruleset
+            .ball_seed
+            .unwrap_or_else(|| rand::thread_rng().gen());
+        info!("Placing balls from seed {seed}");
+        let mut rng = StdRng::seed_from_u64(seed);
+        let next_side = if rng.gen() { 1.0 } else { -1.0 };
+        Self {
+            rng,
+            pattern: if ruleset.opening_rush {
+                BallSpawnPattern::CenterLine
+            } else {
+                ruleset.ball_spawns
+            },
+            next_side,
+        }
+    }
+
+    /// Positions on the floor of a court this size for `count` balls.
+    pub fn positions(&mut self, count: usize, court: Vec2) -> Vec<Vec2> {
+        let half = court / 2.0 - Vec2::splat(EDGE_MARGIN);
+        match self.pattern {
+            BallSpawnPattern::CenterLine => center_line(count, court.x),
+            BallSpawnPattern::Mirrored => {
+                let mut positions = Vec::with_capacity(count);
+                for _ in 0..count / 2 {
+                    let position = self.on_half(half, 1.0);
+                    positions.push(position);
+                    positions.push(Vec2::new(position.x, -position.y));
+                }
+                if count % 2 == 1 {
+                    positions.push(Vec2::new(self.rng.gen_range(-half.x..=half.x), 0.0));
+                }
+                positions
+            }
+            BallSpawnPattern::Alternating => (0..count)
+                .map(|_| {
+                    let side = self.next_side;
+                    self.next_side = -side;
+                    self.on_half(half, side)
+                })
+                .collect(),
+        }
+    }
+
+    /// A spot on the center line, favouring neither team, for a ball brought back into play.
+    pub fn neutral(&mut self, court: Vec2) -> Vec2 {
+        let half_width = (court.x / 2.0 - EDGE_MARGIN).max(0.0);
+        Vec2::new(self.rng.gen_range(-half_width..=half_width), 0.0)
+    }
+
+    /// Picks one of `options` by its weight, from the same seeded RNG as the balls' places, so
+    /// what gets dropped in comes out the same when the match is replayed.
+    pub fn pick_weighted<T: Copy>(&mut self, options: &[(T, f32)]) -> Option<T> {
+        options
+            .choose_weighted(&mut self.rng, |(_, weight)| *weight)
+            .ok()
+            .map(|(option, _)| *option)
+    }
+
+    fn on_half(&mut self, half: Vec2, side: f32) -> Vec2 {
+        let y = if half.y > CENTER_MARGIN {
+            self.rng.gen_range(CENTER_MARGIN..=half.y)
+        } else {
+            half.y.max(0.0)
+        };
+        Vec2::new(self.rng.gen_range(-half.x..=half.x), y * side)
+    }
+}
+
+fn center_line(count: usize, width: f32) -> Vec<Vec2> {
+    (0..count)
+        .map(|index| Vec2::new(width * ((index as f32 + 0.5) / count as f32 - 0.5), 0.0))
+        .collect()
+}
@@ -0,0 +1,66 @@
+use serde::Deserialize;
+
+/// The outcome of ticking a behavior tree node.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Status {
+    Success,
+    Failure,
+    Running,
+}
+
+/// A behavior tree over a set of conditions `C` and tasks `T`, which each AI defines for itself.
+///
+/// Trees are re-evaluated from the root every tick. Tasks that take several ticks report
+/// [`Status::Running`] and keep whatever state they need on the agent.
+#[derive(Deserialize)]
+pub enum Node<C, T> {
+    /// Runs children in order until one does not succeed.
+    Sequence(Vec<Node<C, T>>),
+    /// Runs children in order until one does not fail.
+    Selector(Vec<Node<C, T>>),
+    Invert(Box<Node<C, T>>),
+    Condition(C),
+    Task(T),
+}
+
+impl<C: Copy, T: Copy> Node<C, T> {
+    pub fn tick(
+        &self,
+        check: &mut impl FnMut(C) -> bool,
+        run: &mut impl FnMut(T) -> Status,
+    ) -> Status {
+        match self {
+            Node::Sequence(children) => {
+                for child in children {
+                    match child.tick(check, run) {
+                        Status::Success => {}
+                        status => return status,
+                    }
+                }
+                Status::Success
+            }
+            Node::Selector(children) => {
+                for child in children {
+                    match child.tick(check, run) {
+                        Status::Failure => {}
+                        status => return status,
+                    }
+                }
+                Status::Failure
+            }
+            Node::Invert(child) => match child.tick(check, run) {
+                Status::Success => Status::Failure,
+                Status::Failure => Status::Success,
+                Status::Running => Status::Running,
+            },
+            Node::Condition(condition) => {
+                if check(*condition) {
+                    Status::Success
+                } else {
+                    Status::Failure
+                }
+            }
+            Node::Task(task) => run(*task),
+        }
+    }
+}
@@ -0,0 +1,218 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use leafwing_input_manager::prelude::*;
+
+use crate::{
+    character::CharacterState,
+    drop_held_ball,
+    game_time::GameTime,
+    hit::detect_hits,
+    input_buffer::InputBuffer,
+    interactions::{BallKind, BlockOutcome, InteractionTable},
+    rules::{match_active, Blocks, Ruleset},
+    team::Team,
+    throw::start_throw,
+    tick_timer::TickTimer,
+    Action, Ball, BallState, DataAssets, Facing, HoldingBall, Player, ThrownBy,
+};
+
+/// How long the held ball is raised for after pressing catch.
+const BLOCK_SECONDS: f32 = 0.3;
+const BLOCK_COOLDOWN_SECONDS: f32 = 0.5;
+/// How far in front of the blocker the held ball is raised, and how much of the ball it covers.
+const SHIELD_REACH: f32 = 0.35;
+const SHIELD_RADIUS: f32 = 0.25;
+/// A blocked ball bounces back off the held one with this fraction of its speed.
+const BLOCK_REBOUND: f32 = 0.3;
+
+pub struct BlockPlugin;
+
+impl Plugin for BlockPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::new()
+                .with_run_criteria(match_active)
+                .with_system(start_block.after(start_throw))
+                .with_system(end_block)
+                .with_system(aim_shields)
+                .with_system(resolve_blocks.before(detect_hits)),
+        );
+    }
+}
+
+/// A player raising the ball they hold to block throws with it.
+#[derive(Component)]
+pub struct Blocking {
+    timer: TickTimer,
+    shield: Entity,
+}
+
+#[derive(Component)]
+struct BlockCooldown(TickTimer);
+
+/// The held ball's hitbox while blocking, a sensor in front of the blocker.
+#[derive(Component)]
+struct BlockShield {
+    blocker: Entity,
+}
+
+fn start_block(
+    mut commands: Commands,
+    ruleset: Res<Ruleset>,
+    mut players: Query<
+        (
+            Entity,
+            &ActionState<Action>,
+            &mut InputBuffer,
+            &CharacterState,
+            &Facing,
+        ),
+        (
+            With<Player>,
+            With<HoldingBall>,
+            Without<Blocking>,
+            Without<BlockCooldown>,
+        ),
+    >,
+) {
+    if ruleset.blocks == Blocks::Off {
+        return;
+    }
+    for (player, action_state, mut buffer, state, facing) in &mut players {
+        if !state.is_free() || !buffer.take(action_state, Action::Catch) {
+            continue;
+        }
+        let shield = commands
+            .spawn_bundle(TransformBundle::from_transform(
+                Transform::from_translation((facing.0 * SHIELD_REACH).extend(0.0)),
+            ))
+            .insert_bundle((
+                Collider::ball(SHIELD_RADIUS),
+                Sensor,
+                ColliderMassProperties::Density(0.0),
+                ActiveEvents::COLLISION_EVENTS,
+                BlockShield { blocker: player },
+            ))
+            .id();
+        commands.entity(player).add_child(shield).insert(Blocking {
+            timer: TickTimer::from_seconds(BLOCK_SECONDS),
+            shield,
+        });
+    }
+}
+
+fn end_block(
+    mut commands: Commands,
+    time: Res<GameTime>,
+    mut blockers: Query<(Entity, &mut Blocking, Option<&HoldingBall>)>,
+    mut cooldowns: Query<(Entity, &mut BlockCooldown)>,
+) {
+    for (player, mut blocking, holding) in &mut blockers {
+        // A block is over as soon as there's no ball left to block with.
+        if blocking.timer.tick(time.ticks()).finished() || holding.is_none() {
+            lower_block(&mut commands, player, blocking.shield);
+        }
+    }
+    for (player, mut cooldown) in &mut cooldowns {
+        if cooldown.0.tick(time.ticks()).finished() {
+            commands.entity(player).remove::<BlockCooldown>();
+        }
+    }
+}
+
+/// Puts the blocker's ball back down and starts the cooldown before they can block again.
+fn lower_block(commands: &mut Commands, player: Entity, shield: Entity) {
+    commands.entity(shield).despawn_recursive();
+    commands
+        .entity(player)
+        .remove::<Blocking>()
+        .insert(BlockCooldown(TickTimer::from_seconds(
+            BLOCK_COOLDOWN_SECONDS,
+        )));
+}
+
+/// Keeps the held ball in front of the blocker as they turn.
+fn aim_shields(
+    players: Query<&Facing>,
+    mut shields: Query<(&BlockShield, &mut Transform), Without<Facing>>,
+) {
+    for (shield, mut transform) in &mut shields {
+        if let Ok(facing) = players.get(shield.blocker) {
+            transform.translation = (facing.0 * SHIELD_REACH).extend(0.0);
+        }
+    }
+}
+
+/// Deadens opposing live balls that strike a raised ball, before they can hit the blocker. How
+/// each kind of ball is blocked comes from the [`InteractionTable`].
+fn resolve_blocks(
+    mut commands: Commands,
+    mut collisions: EventReader<CollisionEvent>,
+    ruleset: Res<Ruleset>,
+    data: Res<DataAssets>,
+    tables: Res<Assets<InteractionTable>>,
+    shields: Query<(&BlockShield, &GlobalTransform)>,
+    mut balls: Query<(&mut BallState, &mut Velocity, &BallKind, Option<&ThrownBy>), With<Ball>>,
+    blockers: Query<(&Team, &CharacterState)>,
+    teams: Query<&Team>,
+    held: Query<&HoldingBall>,
+) {
+    let table = if let Some(table) = tables.get(&data.interactions) {
+        table
+    } else {
+        return;
+    };
+    for event in collisions.iter() {
+        let (e1, e2) = if let CollisionEvent::Started(e1, e2, _) = event {
+            (*e1, *e2)
+        } else {
+            continue;
+        };
+        let (shield_entity, ball) = if shields.contains(e1) {
+            (e1, e2)
+        } else if shields.contains(e2) {
+            (e2, e1)
+        } else {
+            continue;
+        };
+        let (shield, shield_transform) = shields.get(shield_entity).unwrap();
+        let (mut state, mut velocity, kind, thrown_by) = if let Ok(ball) = balls.get_mut(ball) {
+            ball
+        } else {
+            continue;
+        };
+        let (blocker_team, blocker_state) = if let Ok(blocker) = blockers.get(shield.blocker) {
+            blocker
+        } else {
+            continue;
+        };
+        // Only throws that could hit the blocker are worth blocking.
+        let thrower = thrown_by.map(|ThrownBy(thrower)| *thrower);
+        let thrower_team = thrower.and_then(|thrower| teams.get(thrower).ok());
+        let teammate = thrower_team == Some(blocker_team) && !ruleset.friendly_fire;
+        if *state != BallState::Live || teammate || thrower == Some(shield.blocker) {
+            continue;
+        }
+
+        let outcome = table.get(*kind, *blocker_state).block;
+        if outcome == BlockOutcome::BreaksBlock {
+            info!("Ball {ball:?} broke player {:?}'s block", shield.blocker);
+            lower_block(&mut commands, shield.blocker, shield_entity);
+            continue;
+        }
+        info!("Player {:?} blocked ball {ball:?}", shield.blocker);
+        *state = BallState::Dead;
+        velocity.linvel *= -BLOCK_REBOUND;
+        commands.entity(ball).remove::<ThrownBy>();
+        if outcome == BlockOutcome::KnocksBallLoose || ruleset.blocks == Blocks::DropBall {
+            if let Ok(held) = held.get(shield.blocker) {
+                drop_held_ball(
+                    shield.blocker,
+                    held,
+                    shield_transform.translation(),
+                    &mut commands,
+                );
+            }
+        }
+    }
+}
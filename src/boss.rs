@@ -0,0 +1,326 @@
+use bevy::{prelude::*, reflect::TypeUuid};
+use bevy_rapier3d::prelude::*;
+use bevy_sprite3d::Sprite3dParams;
+use rand::Rng;
+use serde::Deserialize;
+
+use crate::{
+    ai::{AiAssets, Bot},
+    behavior::{Node, Status},
+    character::CharacterState,
+    devices::PlayerSlot,
+    game_time::GameTime,
+    hit::Health,
+    interactions::BallKind,
+    particles::ParticleEmitter,
+    ron_loader::RonAsset,
+    rules::{match_active, MatchActive, MatchMode, Ruleset},
+    spawn_ball, spawn_player,
+    team::Team,
+    tick_timer::TickTimer,
+    BallState, DataAssets, ImageAssets, Player, ThrownBy,
+};
+
+const BOSS_SCALE: f32 = 2.5;
+const BOSS_HEALTH: u32 = 12;
+const BOSS_DENSITY: f32 = 8.0;
+const RECOVER_SECONDS: f32 = 1.5;
+const BARRAGE_SHOTS: u32 = 5;
+const BARRAGE_INTERVAL: f32 = 0.2;
+/// Half-angle of the fan a barrage is spread over, in radians.
+const BARRAGE_SPREAD: f32 = 0.35;
+const BARRAGE_SPEED: f32 = 11.0;
+const BARRAGE_LIFT: f32 = 3.0;
+const CHARGE_SPEED: f32 = 8.0;
+const CHARGE_SECONDS: f32 = 1.5;
+/// How close to the center line a charge may take the boss.
+const CHARGE_DEPTH: f32 = 1.0;
+const SLAM_SECONDS: f32 = 0.8;
+const SLAM_RADIUS: f32 = 3.0;
+const SLAM_KNOCKBACK: f32 = 1.0;
+const SLAM_STUN_SECONDS: f32 = 1.2;
+
+pub struct BossPlugin;
+
+impl Plugin for BossPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::new()
+                .with_run_criteria(match_active)
+                .with_system(survival_finale)
+                .with_system(boss_attacks),
+        );
+    }
+}
+
+/// An oversized opponent with its own attack patterns, fought at the end of survival mode.
+#[derive(Component)]
+pub struct Boss {
+    phase: BossPhase,
+}
+
+enum BossPhase {
+    /// Waiting for the behavior tree to pick the next attack.
+    Idle,
+    Recover(TickTimer),
+    /// Throws a fan of balls at the nearest opponent.
+    Barrage {
+        shots: u32,
+        cooldown: TickTimer,
+    },
+    /// Rushes towards the nearest opponent, ending in a slam once it gets there or runs out of
+    /// time.
+    Charge {
+        target: Vec3,
+        timer: TickTimer,
+    },
+    /// Stuns and knocks back everyone close by as it lands, then stays planted for a moment.
+    Slam(TickTimer),
+}
+
+/// What the boss can check for in its behavior tree.
+#[derive(Deserialize, Clone, Copy)]
+pub enum BossCondition {
+    /// There is an opponent left to attack.
+    HasTarget,
+    /// Succeeds with the given probability.
+    Chance(f32),
+}
+
+/// The attacks the boss can pick from in its behavior tree, each played out in full once picked.
+#[derive(Deserialize, Clone, Copy)]
+pub enum BossTask {
+    Recover(f32),
+    Barrage,
+    Charge,
+}
+
+#[derive(Deserialize, TypeUuid)]
+#[uuid = "9e41b7c2-5d3a-4f08-8c6e-2a7b1d0f4e58"]
+pub struct BossBehavior {
+    pub root: Node<BossCondition, BossTask>,
+}
+
+impl RonAsset for BossBehavior {
+    const EXTENSIONS: &'static [&'static str] = &["boss.ron"];
+}
+
+/// Brings out the boss once every bot of the opposing team has been eliminated, once a match.
+fn survival_finale(
+    mut commands: Commands,
+    mut spawned: Local<bool>,
+    active: Res<MatchActive>,
+    ruleset: Res<Ruleset>,
+    images: Res<ImageAssets>,
+    mut sprite_params: Sprite3dParams,
+    bots: Query<(&CharacterState, &Team), (With<Bot>, Without<PlayerSlot>)>,
+) {
+    if active.is_added() {
+        *spawned = false;
+    }
+    if *spawned || ruleset.mode != MatchMode::Survival || bots.is_empty() {
+        return;
+    }
+    if bots.iter().any(|(state, _)| state.is_active()) {
+        return;
+    }
+
+    let team = bots.iter().next().map_or(Team(1), |(_, team)| *team);
+    info!("The boss has entered the court");
+    spawn_player(
+        &mut commands,
+        &images,
+        &mut sprite_params,
+        Vec3::new(0.0, 6.0, 0.25 * BOSS_SCALE),
+        team,
+        BOSS_SCALE,
+    )
+    .insert_bundle((
+        Boss {
+            phase: BossPhase::Recover(TickTimer::from_seconds(RECOVER_SECONDS)),
+        },
+        Health::new(BOSS_HEALTH),
+        ColliderMassProperties::Density(BOSS_DENSITY),
+    ));
+    *spawned = true;
+}
+
+fn boss_attacks(
+    mut commands: Commands,
+    time: Res<GameTime>,
+    data: Res<DataAssets>,
+    assets: Res<AiAssets>,
+    behaviors: Res<Assets<BossBehavior>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut bosses: Query<(Entity, &mut Boss, &CharacterState, &Team, &mut Velocity)>,
+    mut players: Query<
+        (
+            &GlobalTransform,
+            &Team,
+            &mut CharacterState,
+            &mut ExternalImpulse,
+        ),
+        (With<Player>, Without<Boss>),
+    >,
+    transforms: Query<&GlobalTransform>,
+) {
+    let behavior = if let Some(behavior) = behaviors.get(&assets.boss) {
+        behavior
+    } else {
+        return;
+    };
+    let ticks = time.ticks();
+    // Only for how far a charge carries the boss this frame, which physics moves it by.
+    let delta = time.delta_seconds();
+    let mut rng = rand::thread_rng();
+    for (entity, mut boss, state, team, mut velocity) in &mut bosses {
+        let position = if let Ok(transform) = transforms.get(entity) {
+            transform.translation()
+        } else {
+            continue;
+        };
+        if !state.can_move() {
+            velocity.linvel = Vec3::Z * velocity.linvel.z;
+            continue;
+        }
+        let nearest = players
+            .iter()
+            .filter(|(_, other, state, _)| *other != team && state.is_active())
+            .map(|(transform, _, _, _)| transform.translation())
+            .min_by(|a, b| a.distance(position).total_cmp(&b.distance(position)));
+        let side = if position.y > 0.0 { 1.0 } else { -1.0 };
+
+        let mut movement = Vec3::ZERO;
+        boss.phase = match boss.phase {
+            BossPhase::Idle => BossPhase::Idle,
+            BossPhase::Recover(mut timer) => {
+                if timer.tick(ticks).finished() {
+                    BossPhase::Idle
+                } else {
+                    BossPhase::Recover(timer)
+                }
+            }
+            BossPhase::Barrage { shots: 0, .. } => {
+                BossPhase::Recover(TickTimer::from_seconds(RECOVER_SECONDS))
+            }
+            BossPhase::Barrage {
+                shots,
+                mut cooldown,
+            } => {
+                if cooldown.tick(ticks).finished() {
+                    if let Some(target) = nearest {
+                        let angle = rng.gen_range(-BARRAGE_SPREAD..=BARRAGE_SPREAD);
+                        let aim = Quat::from_rotation_z(angle)
+                            * (target - position)
+                                .truncate()
+                                .extend(0.0)
+                                .normalize_or_zero();
+                        let ball = spawn_ball(
+                            position + aim * BOSS_SCALE * 0.3 + Vec3::Z * BOSS_SCALE * 0.2,
+                            &mut commands,
+                            &mut meshes,
+                        );
+                        commands.entity(ball).insert_bundle((
+                            Velocity {
+                                linvel: aim * BARRAGE_SPEED + Vec3::Z * BARRAGE_LIFT,
+                                ..default()
+                            },
+                            ThrownBy(entity),
+                            BallState::Live,
+                            // Only good for the one throw, so the boss's throws don't pile up on
+                            // the court.
+                            BallKind::Barrage,
+                        ));
+                    }
+                    BossPhase::Barrage {
+                        shots: shots - 1,
+                        cooldown: TickTimer::from_seconds(BARRAGE_INTERVAL),
+                    }
+                } else {
+                    BossPhase::Barrage { shots, cooldown }
+                }
+            }
+            BossPhase::Charge { target, mut timer } => {
+                let offset = (target - position).truncate();
+                if offset.length() < CHARGE_SPEED * delta || timer.tick(ticks).finished() {
+                    slam(&mut commands, &data, position, team, &mut players);
+                    BossPhase::Slam(TickTimer::from_seconds(SLAM_SECONDS))
+                } else {
+                    movement = offset.normalize().extend(0.0) * CHARGE_SPEED;
+                    BossPhase::Charge { target, timer }
+                }
+            }
+            BossPhase::Slam(mut timer) => {
+                if timer.tick(ticks).finished() {
+                    BossPhase::Recover(TickTimer::from_seconds(RECOVER_SECONDS))
+                } else {
+                    BossPhase::Slam(timer)
+                }
+            }
+        };
+
+        if let BossPhase::Idle = boss.phase {
+            let mut next = BossPhase::Idle;
+            behavior.root.tick(
+                &mut |condition| match condition {
+                    BossCondition::HasTarget => nearest.is_some(),
+                    BossCondition::Chance(chance) => rng.gen::<f32>() < chance,
+                },
+                &mut |task| {
+                    next = match (task, nearest) {
+                        (BossTask::Recover(seconds), _) => {
+                            BossPhase::Recover(TickTimer::from_seconds(seconds))
+                        }
+                        (BossTask::Barrage, Some(_)) => BossPhase::Barrage {
+                            shots: BARRAGE_SHOTS,
+                            cooldown: TickTimer::from_ticks(0),
+                        },
+                        (BossTask::Charge, Some(target)) => BossPhase::Charge {
+                            target: Vec3::new(
+                                target.x,
+                                side * CHARGE_DEPTH.max(target.y * side),
+                                0.0,
+                            ),
+                            timer: TickTimer::from_seconds(CHARGE_SECONDS),
+                        },
+                        (_, None) => return Status::Failure,
+                    };
+                    Status::Success
+                },
+            );
+            boss.phase = next;
+        }
+        velocity.linvel = movement + Vec3::Z * velocity.linvel.z;
+    }
+}
+
+fn slam(
+    commands: &mut Commands,
+    data: &DataAssets,
+    position: Vec3,
+    team: &Team,
+    players: &mut Query<
+        (
+            &GlobalTransform,
+            &Team,
+            &mut CharacterState,
+            &mut ExternalImpulse,
+        ),
+        (With<Player>, Without<Boss>),
+    >,
+) {
+    commands
+        .spawn_bundle(SpatialBundle {
+            transform: Transform::from_translation(position.truncate().extend(0.0)),
+            ..default()
+        })
+        .insert(ParticleEmitter::new(data.dust.clone()));
+    for (transform, other, mut state, mut impulse) in players.iter_mut() {
+        let offset = (transform.translation() - position).truncate();
+        if other == team || !state.is_active() || offset.length() > SLAM_RADIUS {
+            continue;
+        }
+        impulse.impulse += offset.normalize_or_zero().extend(0.5) * SLAM_KNOCKBACK;
+        *state = CharacterState::Stunned(TickTimer::from_seconds(SLAM_STUN_SECONDS));
+    }
+}
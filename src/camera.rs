@@ -0,0 +1,168 @@
+use std::f32::consts::PI;
+
+use bevy::{prelude::*, render::camera::Projection, utils::HashMap};
+
+use crate::{
+    sequence::Sequence,
+    stage::ActiveStage,
+    tween::{Ease, Tween},
+    GameState, Player,
+};
+
+const TRANSITION_SECONDS: f32 = 1.2;
+const FOLLOW_SPEED: f32 = 4.0;
+
+pub struct CameraPlugin;
+
+impl Plugin for CameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CameraAnchors>()
+            .add_event::<FlyCamera>()
+            .add_startup_system(spawn_camera)
+            .add_system(fly_to_anchor)
+            .add_system(fly_camera)
+            .add_system(apply_stage_camera)
+            .add_system(follow_players.after(apply_stage_camera));
+    }
+}
+
+/// Where the game camera rests in each state, flown between on state changes.
+pub struct CameraAnchors(HashMap<GameState, Transform>);
+
+impl CameraAnchors {
+    pub fn get(&self, state: &GameState) -> Option<Transform> {
+        self.0.get(state).copied()
+    }
+
+    pub fn insert(&mut self, state: GameState, anchor: Transform) {
+        self.0.insert(state, anchor);
+    }
+}
+
+impl Default for CameraAnchors {
+    fn default() -> Self {
+        Self(HashMap::from_iter([
+            (
+                GameState::Loading,
+                Transform::from_xyz(20.0, 0.0, 20.0).looking_at(Vec3::ZERO, Vec3::NEG_X),
+            ),
+            (
+                GameState::Ready,
+                Transform::from_xyz(10.0, 0.0, 5.0).looking_at(Vec3::ZERO, Vec3::NEG_X),
+            ),
+        ]))
+    }
+}
+
+/// Flies the game camera from wherever it is to `to`, for scripted moments.
+pub struct FlyCamera {
+    pub to: Transform,
+    pub seconds: f32,
+    pub ease: Ease,
+}
+
+fn spawn_camera(mut commands: Commands, anchors: Res<CameraAnchors>) {
+    commands.spawn_bundle(Camera3dBundle {
+        projection: Projection::Perspective(PerspectiveProjection {
+            fov: PI / 6.0,
+            ..default()
+        }),
+        transform: anchors.get(&GameState::Loading).unwrap_or_default(),
+        ..default()
+    });
+}
+
+fn fly_to_anchor(
+    mut commands: Commands,
+    state: Res<State<GameState>>,
+    anchors: Res<CameraAnchors>,
+    cameras: Query<(Entity, &Transform), With<Camera3d>>,
+) {
+    if !state.is_changed() {
+        return;
+    }
+    let anchor = if let Some(anchor) = anchors.get(state.current()) {
+        anchor
+    } else {
+        return;
+    };
+    for (entity, transform) in &cameras {
+        if *transform != anchor {
+            commands.entity(entity).insert(
+                Tween::transform(*transform, anchor, TRANSITION_SECONDS).ease(Ease::SineInOut),
+            );
+        }
+    }
+}
+
+fn fly_camera(
+    mut commands: Commands,
+    mut events: EventReader<FlyCamera>,
+    cameras: Query<(Entity, &Transform), With<Camera3d>>,
+) {
+    let event = if let Some(event) = events.iter().last() {
+        event
+    } else {
+        return;
+    };
+    for (entity, transform) in &cameras {
+        commands
+            .entity(entity)
+            .insert(Tween::transform(*transform, event.to, event.seconds).ease(event.ease));
+    }
+}
+
+fn apply_stage_camera(stage: Option<Res<ActiveStage>>, mut anchors: ResMut<CameraAnchors>) {
+    if let Some(stage) = stage {
+        if stage.is_changed() {
+            anchors.insert(GameState::Ready, stage.camera.transform(Vec3::ZERO));
+        }
+    }
+}
+
+/// Pans the camera along the court to keep the players in frame, widening the view as they
+/// spread out, unless a transition or scripted sequence is moving it.
+fn follow_players(
+    time: Res<Time>,
+    stage: Option<Res<ActiveStage>>,
+    players: Query<&GlobalTransform, With<Player>>,
+    sequences: Query<(), With<Sequence>>,
+    mut cameras: Query<
+        (&mut Transform, &mut Projection),
+        (With<Camera3d>, Without<Tween<Transform>>),
+    >,
+) {
+    let stage = if let Some(stage) = stage {
+        stage
+    } else {
+        return;
+    };
+    if !sequences.is_empty() || players.is_empty() {
+        return;
+    }
+
+    let (near, far) = players
+        .iter()
+        .fold((f32::MAX, f32::MIN), |(near, far), player| {
+            let y = player.translation().y;
+            (near.min(y), far.max(y))
+        });
+    let config = stage.camera;
+    let target = Vec3::new(
+        0.0,
+        ((near + far) / 2.0).clamp(config.follow_bounds.0, config.follow_bounds.1),
+        0.0,
+    );
+    let spread = ((far - near) / stage.size.y).clamp(0.0, 1.0);
+    let fov = (config.fov.0 + (config.fov.1 - config.fov.0) * spread).to_radians();
+
+    let smoothing = 1.0 - (-FOLLOW_SPEED * time.delta_seconds()).exp();
+    let goal = config.transform(target);
+    for (mut transform, mut projection) in &mut cameras {
+        transform.translation = transform.translation.lerp(goal.translation, smoothing);
+        transform.rotation = transform.rotation.slerp(goal.rotation, smoothing);
+        if let Projection::Perspective(perspective) = &mut *projection {
+            perspective.fov += (fov - perspective.fov) * smoothing;
+        }
+    }
+}
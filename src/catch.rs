@@ -0,0 +1,217 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use leafwing_input_manager::prelude::*;
+
+use crate::{
+    character::CharacterState,
+    game_time::GameTime,
+    hit::{detect_hits, Ricochets},
+    hold_ball,
+    input_buffer::InputBuffer,
+    interactions::{BallKind, InteractionTable},
+    rules::match_active,
+    team::Team,
+    tick_timer::{seconds_to_ticks, TickTimer},
+    Action, Ball, BallState, DataAssets, HoldingBall, Player, ThrownBy,
+};
+
+/// How long after pressing catch a ball is caught.
+const CATCH_SECONDS: f32 = 0.3;
+/// How long after pressing catch a ball is deflected back instead, for a well timed press.
+const DEFLECT_SECONDS: f32 = 0.08;
+/// How long after a catch window closes before catch can be pressed again.
+const CATCH_COOLDOWN_SECONDS: f32 = 0.4;
+const DEFLECT_SPEEDUP: f32 = 1.2;
+/// The speed cap of a deflected ball, raised with each deflection in a rally.
+const RALLY_BASE_SPEED: f32 = 15.0;
+const RALLY_SPEED_STEP: f32 = 3.0;
+const RALLY_MAX_SPEED: f32 = 30.0;
+/// Deflections aim this far above the thrower so the ball arcs in rather than dropping short.
+const DEFLECT_LIFT: f32 = 0.5;
+
+pub struct CatchPlugin;
+
+impl Plugin for CatchPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Caught>()
+            .add_event::<Whiffed>()
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(match_active)
+                    .with_system(start_catch)
+                    .with_system(resolve_catches.after(start_catch).after(detect_hits)),
+            );
+    }
+}
+
+#[derive(Component)]
+pub struct CatchCooldown(TickTimer);
+
+/// Sent when a player catches a ball outright, rather than deflecting it.
+pub struct Caught {
+    pub player: Entity,
+    /// Whoever threw the ball, if anyone.
+    pub thrower: Option<Entity>,
+}
+
+/// Sent when a catch window closes without anything being caught. The player can't try again
+/// until the cooldown is over.
+pub struct Whiffed {
+    pub player: Entity,
+}
+
+/// Counts how many times a ball has been deflected back and forth.
+#[derive(Component)]
+pub struct Rally(pub u32);
+
+fn start_catch(
+    mut commands: Commands,
+    time: Res<GameTime>,
+    mut whiffs: EventWriter<Whiffed>,
+    mut players: Query<
+        (
+            Entity,
+            &ActionState<Action>,
+            &mut InputBuffer,
+            &mut CharacterState,
+            Option<&mut CatchCooldown>,
+            Option<&HoldingBall>,
+        ),
+        With<Player>,
+    >,
+) {
+    for (entity, action_state, mut buffer, mut state, cooldown, has_ball) in &mut players {
+        if let CharacterState::Catching(mut window) = *state {
+            if window.tick(time.ticks()).finished() {
+                *state = CharacterState::Idle;
+                whiffs.send(Whiffed { player: entity });
+                commands
+                    .entity(entity)
+                    .insert(CatchCooldown(TickTimer::from_seconds(
+                        CATCH_COOLDOWN_SECONDS,
+                    )));
+            } else {
+                *state = CharacterState::Catching(window);
+            }
+        } else if let Some(mut cooldown) = cooldown {
+            if cooldown.0.tick(time.ticks()).finished() {
+                commands.entity(entity).remove::<CatchCooldown>();
+            }
+        } else if state.is_free() && has_ball.is_none() && buffer.take(action_state, Action::Catch)
+        {
+            *state = CharacterState::Catching(TickTimer::from_seconds(CATCH_SECONDS));
+        }
+    }
+}
+
+/// Catches or deflects opposing live balls that reach a player while they're ready to catch.
+///
+/// A ball reaching several catchers in the same frame goes to the closest of them, then the lower
+/// entity, as with [pickups](crate::pickup), so it is only ever caught once.
+fn resolve_catches(
+    mut commands: Commands,
+    mut collisions: EventReader<CollisionEvent>,
+    mut caught: EventWriter<Caught>,
+    data: Res<DataAssets>,
+    tables: Res<Assets<InteractionTable>>,
+    mut players: Query<(&mut CharacterState, &Team), Without<HoldingBall>>,
+    mut balls: Query<
+        (
+            &GlobalTransform,
+            &mut Velocity,
+            &BallKind,
+            &BallState,
+            Option<&ThrownBy>,
+            Option<&Rally>,
+        ),
+        With<Ball>,
+    >,
+    transforms: Query<&GlobalTransform>,
+    teams: Query<&Team>,
+) {
+    let table = if let Some(table) = tables.get(&data.interactions) {
+        table
+    } else {
+        return;
+    };
+    let mut contacts = Vec::new();
+    for event in collisions.iter() {
+        let (e1, e2) = if let CollisionEvent::Started(e1, e2, _) = event {
+            (*e1, *e2)
+        } else {
+            continue;
+        };
+        let (player, ball) = if players.contains(e1) && balls.contains(e2) {
+            (e1, e2)
+        } else if players.contains(e2) && balls.contains(e1) {
+            (e2, e1)
+        } else {
+            continue;
+        };
+        let (state, team) = players.get(player).unwrap();
+        let (ball_transform, _, kind, ball_state, thrown_by, _) = balls.get(ball).unwrap();
+        if !matches!(state, CharacterState::Catching(_))
+            || *ball_state != BallState::Live
+            || !table.get(*kind, *state).catch
+        {
+            continue;
+        }
+        // A teammate's pass, or a player's own throw, isn't theirs to catch.
+        let thrower_team = thrown_by.and_then(|ThrownBy(thrower)| teams.get(*thrower).ok());
+        if thrower_team == Some(team) {
+            continue;
+        }
+        let distance = transforms.get(player).map_or(f32::MAX, |transform| {
+            transform
+                .translation()
+                .distance(ball_transform.translation())
+        });
+        contacts.push((distance, player, ball));
+    }
+    contacts.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)).then(a.2.cmp(&b.2)));
+
+    let mut taken = Vec::new();
+    let mut catchers = Vec::new();
+    for (_, player, ball) in contacts {
+        if taken.contains(&ball) || catchers.contains(&player) {
+            continue;
+        }
+        taken.push(ball);
+        catchers.push(player);
+        let (mut state, _) = players.get_mut(player).unwrap();
+        let window = if let CharacterState::Catching(window) = *state {
+            window
+        } else {
+            continue;
+        };
+        let (ball_transform, mut velocity, kind, _, thrown_by, rally) =
+            balls.get_mut(ball).unwrap();
+        let speed = velocity.linvel.length();
+
+        let thrower = thrown_by
+            .filter(|_| window.elapsed_ticks() <= seconds_to_ticks(DEFLECT_SECONDS))
+            .and_then(|thrown_by| Some((thrown_by.0, transforms.get(thrown_by.0).ok()?)));
+        if let Some((thrower, thrower_transform)) = thrower {
+            let rally = rally.map_or(1, |rally| rally.0 + 1);
+            let cap = (RALLY_BASE_SPEED + RALLY_SPEED_STEP * rally as f32).min(RALLY_MAX_SPEED);
+            let target = thrower_transform.translation() + Vec3::Z * DEFLECT_LIFT;
+            velocity.linvel = (target - ball_transform.translation()).normalize_or_zero()
+                * (speed * DEFLECT_SPEEDUP).min(cap);
+            info!("Player {player:?} deflected ball {ball:?} back at {thrower:?}");
+            commands.entity(ball).insert_bundle((
+                ThrownBy(player),
+                Rally(rally),
+                Ricochets::default(),
+            ));
+        } else {
+            info!("Player {player:?} caught ball {ball:?}");
+            hold_ball(player, ball, *kind, &mut commands);
+            commands.entity(ball).remove::<Rally>();
+            caught.send(Caught {
+                player,
+                thrower: thrown_by.map(|thrown_by| thrown_by.0),
+            });
+        }
+        *state = CharacterState::Idle;
+    }
+}
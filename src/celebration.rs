@@ -0,0 +1,202 @@
+use bevy::prelude::*;
+
+use crate::{
+    game_time::{HoldPhysics, PhysicsHold},
+    graphics::SpriteDepth,
+    particles::ParticleEmitter,
+    sequence::Sequence,
+    tween::{Ease, Tween},
+    ui::text::styled_text,
+    DataAssets,
+};
+
+const CHEER_SECONDS: f32 = 3.0;
+const FREEZE_SECONDS: f32 = 2.0;
+const CAMERA_SECONDS: f32 = 0.8;
+const SCORE_POP_SECONDS: f32 = 0.4;
+const CAMERA_OFFSET: Vec3 = Vec3::new(5.0, 0.0, 2.5);
+const SLUMP_SCALE: Vec3 = Vec3::new(1.0, 0.8, 1.0);
+
+pub struct CelebrationPlugin;
+
+impl Plugin for CelebrationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<MatchOver>()
+            .add_event::<ShowResults>()
+            .add_event::<CelebrationCue>()
+            .add_system(start_celebration)
+            .add_system(play_celebration);
+    }
+}
+
+/// Sent when a match has been decided.
+pub struct MatchOver {
+    pub winners: Vec<Entity>,
+    pub losers: Vec<Entity>,
+    pub score: String,
+}
+
+/// Sent once the victory celebration has finished and the results can be shown.
+pub struct ShowResults;
+
+#[derive(Component)]
+struct CelebrationEntity;
+
+/// The beats of the celebration, sent by its sequence as it plays out.
+enum CelebrationCue {
+    Cheer {
+        winners: Vec<Vec3>,
+        losers: Vec<Entity>,
+    },
+    FreezeFrame(String),
+    Finish {
+        losers: Vec<Entity>,
+    },
+}
+
+fn start_celebration(
+    mut commands: Commands,
+    mut events: EventReader<MatchOver>,
+    transforms: Query<&GlobalTransform>,
+    cameras: Query<&Transform, With<Camera3d>>,
+) {
+    let event = if let Some(event) = events.iter().last() {
+        event
+    } else {
+        return;
+    };
+
+    let winners = event
+        .winners
+        .iter()
+        .filter_map(|winner| transforms.get(*winner).ok())
+        .map(GlobalTransform::translation)
+        .collect::<Vec<_>>();
+    let losers = event.losers.clone();
+    let camera = cameras.iter().next().copied().unwrap_or_default();
+    let focus = winners.first().copied().unwrap_or_default();
+    let close_up = Transform::from_translation(focus + CAMERA_OFFSET).looking_at(focus, Vec3::Z);
+
+    commands.spawn().insert(
+        Sequence::new()
+            .send(CelebrationCue::Cheer {
+                winners,
+                losers: losers.clone(),
+            })
+            .move_camera(close_up, CAMERA_SECONDS, Ease::QuadInOut)
+            .wait(CHEER_SECONDS - CAMERA_SECONDS)
+            .send(CelebrationCue::FreezeFrame(event.score.clone()))
+            .wait(FREEZE_SECONDS)
+            .send(CelebrationCue::Finish { losers })
+            .move_camera(camera, CAMERA_SECONDS, Ease::QuadInOut)
+            .send(ShowResults),
+    );
+}
+
+fn play_celebration(
+    mut commands: Commands,
+    mut cues: EventReader<CelebrationCue>,
+    data: Option<Res<DataAssets>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut holds: EventWriter<HoldPhysics>,
+    celebration_entities: Query<Entity, With<CelebrationEntity>>,
+    children: Query<&Children>,
+    mut sprites: Query<&mut Transform, With<SpriteDepth>>,
+) {
+    for cue in cues.iter() {
+        match cue {
+            CelebrationCue::Cheer { winners, losers } => {
+                // Only optional because this runs from startup, before the data assets load.
+                let confetti = if let Some(data) = &data {
+                    data.confetti.clone()
+                } else {
+                    continue;
+                };
+                let spotlight_mesh = meshes.add(shape::Circle::new(0.5).into());
+                let spotlight_material = materials.add(StandardMaterial {
+                    base_color: Color::rgba(1.0, 1.0, 0.8, 0.35),
+                    unlit: true,
+                    alpha_mode: AlphaMode::Blend,
+                    ..default()
+                });
+                for position in winners {
+                    commands
+                        .spawn_bundle(SpatialBundle {
+                            transform: Transform::from_translation(*position + Vec3::Z * 2.0),
+                            ..default()
+                        })
+                        .insert_bundle((ParticleEmitter::new(confetti.clone()), CelebrationEntity));
+                    commands
+                        .spawn_bundle(PbrBundle {
+                            mesh: spotlight_mesh.clone(),
+                            material: spotlight_material.clone(),
+                            transform: Transform::from_translation(
+                                position.truncate().extend(0.01),
+                            ),
+                            ..default()
+                        })
+                        .insert(CelebrationEntity);
+                }
+                for loser in losers {
+                    set_sprite_scale(*loser, SLUMP_SCALE, &children, &mut sprites);
+                }
+            }
+            CelebrationCue::FreezeFrame(score) => {
+                holds.send(HoldPhysics {
+                    hold: PhysicsHold::Celebration,
+                    held: true,
+                });
+                commands
+                    .spawn_bundle(NodeBundle {
+                        style: Style {
+                            size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        color: Color::NONE.into(),
+                        ..default()
+                    })
+                    .insert(CelebrationEntity)
+                    .with_children(|parent| {
+                        parent
+                            .spawn_bundle(styled_text(score.clone(), "title"))
+                            .insert(
+                                Tween::scale(Vec3::ZERO, Vec3::ONE, SCORE_POP_SECONDS)
+                                    .ease(Ease::BackOut),
+                            );
+                    });
+            }
+            CelebrationCue::Finish { losers } => {
+                holds.send(HoldPhysics {
+                    hold: PhysicsHold::Celebration,
+                    held: false,
+                });
+                for entity in &celebration_entities {
+                    commands.entity(entity).despawn_recursive();
+                }
+                for loser in losers {
+                    set_sprite_scale(*loser, Vec3::ONE, &children, &mut sprites);
+                }
+            }
+        }
+    }
+}
+
+fn set_sprite_scale(
+    entity: Entity,
+    scale: Vec3,
+    children: &Query<&Children>,
+    sprites: &mut Query<&mut Transform, With<SpriteDepth>>,
+) {
+    for child in children
+        .get(entity)
+        .into_iter()
+        .flat_map(|children| children.iter())
+    {
+        if let Ok(mut transform) = sprites.get_mut(*child) {
+            transform.scale = scale;
+        }
+    }
+}
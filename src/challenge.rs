@@ -0,0 +1,188 @@
+use bevy::{prelude::*, reflect::TypeUuid};
+use serde::Deserialize;
+
+use crate::{
+    catch::Caught,
+    devices::PlayerSlot,
+    game_time::GameTime,
+    hit::Hit,
+    profile::{Medal, Profile},
+    ron_loader::{RonAsset, RonLoader},
+    rules::{match_active, MatchMode, Ruleset},
+    style_meter::NearMiss,
+    tick_timer::{TickTimer, TICK_RATE},
+    DataAssets, GameState,
+};
+
+pub struct ChallengePlugin;
+
+impl Plugin for ChallengePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<ChallengeList>()
+            .init_asset_loader::<RonLoader<ChallengeList>>()
+            .add_system_set(SystemSet::on_enter(GameState::Ready).with_system(start_challenge))
+            .add_system_set(SystemSet::on_exit(GameState::Ready).with_system(end_challenge))
+            .add_system(track_challenge.with_run_criteria(match_active));
+    }
+}
+
+/// Authored challenges, in the order they are offered.
+#[derive(Deserialize, TypeUuid)]
+#[uuid = "5b0e7c2d-94a1-4e3f-8d6b-2f17c9a0e4b3"]
+pub struct ChallengeList {
+    pub challenges: Vec<Challenge>,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct Challenge {
+    /// Names the challenge in the profile, so it keeps its medals if it is renamed or moved.
+    pub id: String,
+    pub name: String,
+    pub goal: Goal,
+    /// How many times the goal has to be met.
+    pub count: u32,
+    pub seconds: f32,
+    /// Whether being hit fails the challenge.
+    #[serde(default)]
+    pub no_hits: bool,
+    /// Seconds left on the clock for silver and for gold. Finishing at all earns bronze.
+    pub medals: (f32, f32),
+}
+
+/// What the local player has to do, counted from gameplay events.
+#[derive(Deserialize, Clone, Copy)]
+pub enum Goal {
+    Dodge,
+    Catch,
+    Hit,
+}
+
+/// The challenge being played, and how it's going.
+pub struct ChallengeRun {
+    pub challenge: Challenge,
+    pub timer: TickTimer,
+    pub progress: u32,
+    pub outcome: Option<Outcome>,
+}
+
+#[derive(Clone, Copy)]
+pub enum Outcome {
+    Passed(Medal),
+    Failed,
+}
+
+impl ChallengeRun {
+    pub fn seconds_left(&self) -> f32 {
+        self.timer.remaining_ticks() as f32 / TICK_RATE as f32
+    }
+}
+
+fn start_challenge(
+    mut commands: Commands,
+    ruleset: Res<Ruleset>,
+    data: Res<DataAssets>,
+    lists: Res<Assets<ChallengeList>>,
+) {
+    let index = if let MatchMode::Challenge(index) = ruleset.mode {
+        index
+    } else {
+        return;
+    };
+    let challenge = if let Some(challenge) = lists
+        .get(&data.challenges)
+        .and_then(|list| list.challenges.get(index))
+    {
+        challenge.clone()
+    } else {
+        warn!("There is no challenge {index}");
+        return;
+    };
+    info!("Starting challenge {}", challenge.name);
+    commands.insert_resource(ChallengeRun {
+        timer: TickTimer::from_seconds(challenge.seconds),
+        challenge,
+        progress: 0,
+        outcome: None,
+    });
+}
+
+fn end_challenge(mut commands: Commands) {
+    commands.remove_resource::<ChallengeRun>();
+}
+
+fn track_challenge(
+    time: Res<GameTime>,
+    run: Option<ResMut<ChallengeRun>>,
+    mut profile: ResMut<Profile>,
+    mut near_misses: EventReader<NearMiss>,
+    mut caught: EventReader<Caught>,
+    mut hits: EventReader<Hit>,
+    players: Query<Entity, With<PlayerSlot>>,
+) {
+    let mut run = if let Some(run) = run {
+        run
+    } else {
+        return;
+    };
+    let player = if let Some(player) = players.iter().next() {
+        player
+    } else {
+        return;
+    };
+    if run.outcome.is_some() {
+        return;
+    }
+
+    let mut progress = 0;
+    let mut was_hit = false;
+    match run.challenge.goal {
+        Goal::Dodge => {
+            progress += near_misses
+                .iter()
+                .filter(|miss| miss.player == player)
+                .count();
+        }
+        Goal::Catch => progress += caught.iter().filter(|catch| catch.player == player).count(),
+        Goal::Hit => {}
+    }
+    for hit in hits.iter() {
+        was_hit |= hit.player == player;
+        if matches!(run.challenge.goal, Goal::Hit) && hit.thrower == Some(player) {
+            progress += 1;
+        }
+    }
+    run.progress += progress as u32;
+
+    let finished = run.timer.tick(time.ticks()).finished();
+    let outcome = if was_hit && run.challenge.no_hits {
+        Outcome::Failed
+    } else if run.progress >= run.challenge.count {
+        let (silver, gold) = run.challenge.medals;
+        let left = run.seconds_left();
+        Outcome::Passed(if left >= gold {
+            Medal::Gold
+        } else if left >= silver {
+            Medal::Silver
+        } else {
+            Medal::Bronze
+        })
+    } else if finished {
+        Outcome::Failed
+    } else {
+        return;
+    };
+
+    if let Outcome::Passed(medal) = outcome {
+        info!("Passed challenge {} with {medal:?}", run.challenge.name);
+        if profile.award(&run.challenge.id, medal) {
+            profile.save();
+        }
+    } else {
+        info!("Failed challenge {}", run.challenge.name);
+    }
+    run.outcome = Some(outcome);
+}
+
+impl RonAsset for ChallengeList {
+    const EXTENSIONS: &'static [&'static str] = &["challenges.ron"];
+}
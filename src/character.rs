@@ -0,0 +1,257 @@
+use std::mem::discriminant;
+
+use bevy::{prelude::*, reflect::TypeUuid};
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    game_time::GameTime,
+    ron_loader::{RonAsset, RonLoader},
+    rules::match_active,
+    stage::Ground,
+    throw::FULL_CHARGE_SECONDS,
+    tick_timer::{seconds_to_ticks, TickTimer},
+    DataAssets,
+};
+
+/// Horizontal speed below which a grounded character counts as standing still.
+const RUN_THRESHOLD: f32 = 0.1;
+
+pub struct CharacterPlugin;
+
+impl Plugin for CharacterPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<StateChanged>()
+            .add_asset::<CharacterStats>()
+            .init_asset_loader::<RonLoader<CharacterStats>>()
+            .add_system(apply_character_stats)
+            .add_system(ground_contact)
+            .add_system(update_locomotion.after(ground_contact))
+            .add_system(recover_from_stuns.with_run_criteria(match_active))
+            .add_system_to_stage(CoreStage::PostUpdate, detect_transitions);
+    }
+}
+
+/// The one authoritative state of a character, which animation, input gating and networking
+/// all key off.
+///
+/// The movement states are derived from physics each frame. The others are entered by the
+/// systems that own them, which hand the character back to [`CharacterState::Idle`] when done.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum CharacterState {
+    Idle,
+    Run,
+    Jump,
+    Fall,
+    Throwing(ThrowPhase),
+    /// Ready to catch until the window runs out.
+    Catching(TickTimer),
+    /// Unable to act until the timer runs out.
+    Stunned(TickTimer),
+    /// Off the court after losing a life, coming back once the timer runs out.
+    Respawning(TickTimer),
+    Eliminated,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ThrowPhase {
+    /// Winding up and charging for as long as throw is held, for this many ticks so far.
+    WindUp(u32),
+    /// Recovering from a cancelled throw until the timer runs out.
+    Feint(TickTimer),
+}
+
+impl CharacterState {
+    /// Whether the character is just moving around, free to start any action.
+    pub fn is_free(self) -> bool {
+        matches!(self, Self::Idle | Self::Run | Self::Jump | Self::Fall)
+    }
+
+    /// How charged a throw being wound up is, from 0 to 1.
+    pub fn throw_charge(self) -> Option<f32> {
+        if let Self::Throwing(ThrowPhase::WindUp(elapsed)) = self {
+            Some((elapsed as f32 / seconds_to_ticks(FULL_CHARGE_SECONDS) as f32).min(1.0))
+        } else {
+            None
+        }
+    }
+
+    pub fn is_grounded(self) -> bool {
+        matches!(self, Self::Idle | Self::Run)
+    }
+
+    /// Whether the character's movement follows its input.
+    pub fn can_move(self) -> bool {
+        !matches!(
+            self,
+            Self::Stunned(_) | Self::Respawning(_) | Self::Eliminated
+        )
+    }
+
+    /// Whether the character takes part in the match.
+    pub fn is_active(self) -> bool {
+        self != Self::Eliminated
+    }
+}
+
+/// How fast a character moves, and how quickly they get their throws off and recover from them.
+/// Read from a data file, so each character can be tuned without recompiling.
+#[derive(Component, Deserialize, TypeUuid, Clone, Copy)]
+#[uuid = "4f7d2a90-6c1e-4b38-9a5f-d03e8b71c2a6"]
+#[serde(default)]
+pub struct CharacterStats {
+    /// Top running speed, in metres per second.
+    pub run_speed: f32,
+    /// What a sprint multiplies running speed by, how long it lasts and how long after it
+    /// starts before the next one can.
+    pub sprint_multiplier: f32,
+    pub sprint_seconds: f32,
+    pub sprint_cooldown_seconds: f32,
+    /// The least time a throw is wound up for before the ball can leave.
+    pub wind_up_seconds: f32,
+    /// How long after a throw before the character can throw or jump again.
+    pub throw_recovery_seconds: f32,
+    /// How fast the character moves while recovering from a throw, relative to their usual speed.
+    pub recovery_speed: f32,
+}
+
+impl Default for CharacterStats {
+    fn default() -> Self {
+        Self {
+            run_speed: 10.0,
+            sprint_multiplier: 1.6,
+            sprint_seconds: 0.6,
+            sprint_cooldown_seconds: 2.0,
+            wind_up_seconds: 0.25,
+            throw_recovery_seconds: 0.3,
+            recovery_speed: 0.5,
+        }
+    }
+}
+
+/// Gives newly spawned characters the stats from the data file, and everyone new ones when the
+/// file changes.
+fn apply_character_stats(
+    mut events: EventReader<AssetEvent<CharacterStats>>,
+    data: Option<Res<DataAssets>>,
+    assets: Res<Assets<CharacterStats>>,
+    mut characters: Query<(&mut CharacterStats, ChangeTrackers<CharacterStats>)>,
+) {
+    let stats = if let Some(stats) = data.and_then(|data| assets.get(&data.character_stats)) {
+        *stats
+    } else {
+        return;
+    };
+    let modified = events
+        .iter()
+        .any(|event| matches!(event, AssetEvent::Modified { .. }));
+    for (mut character, tracker) in &mut characters {
+        if modified || tracker.is_added() {
+            *character = stats;
+        }
+    }
+}
+
+impl RonAsset for CharacterStats {
+    const EXTENSIONS: &'static [&'static str] = &["stats.ron"];
+}
+
+/// Sent when a character leaves one state for another, to run enter and exit hooks.
+///
+/// Progress within a state, such as a timer counting up, does not count as a change.
+pub struct StateChanged {
+    pub entity: Entity,
+    pub from: CharacterState,
+    pub to: CharacterState,
+}
+
+/// Whether a character is standing on the ground.
+#[derive(Component)]
+pub struct GroundContact(pub bool);
+
+fn ground_contact(
+    mut events: EventReader<CollisionEvent>,
+    mut characters: Query<&mut GroundContact>,
+    ground: Query<(), With<Ground>>,
+) {
+    for event in events.iter() {
+        let (e1, e2, grounded) = match event {
+            CollisionEvent::Started(e1, e2, _) => (e1, e2, true),
+            CollisionEvent::Stopped(e1, e2, _) => (e1, e2, false),
+        };
+        let contact = if ground.contains(*e2) {
+            characters.get_mut(*e1)
+        } else if ground.contains(*e1) {
+            characters.get_mut(*e2)
+        } else {
+            continue;
+        };
+        if let Ok(mut contact) = contact {
+            contact.0 = grounded;
+        }
+    }
+}
+
+fn update_locomotion(mut characters: Query<(&mut CharacterState, &GroundContact, &Velocity)>) {
+    for (mut state, contact, velocity) in &mut characters {
+        if !state.is_free() {
+            continue;
+        }
+        // Contact with the ground only ends after the physics step following a jump.
+        let taking_off = *state == CharacterState::Jump && velocity.linvel.z > 0.0;
+        let next = if contact.0 && !taking_off {
+            if velocity.linvel.truncate().length() > RUN_THRESHOLD {
+                CharacterState::Run
+            } else {
+                CharacterState::Idle
+            }
+        } else if velocity.linvel.z > 0.0 {
+            CharacterState::Jump
+        } else {
+            CharacterState::Fall
+        };
+        if *state != next {
+            *state = next;
+        }
+    }
+}
+
+fn recover_from_stuns(time: Res<GameTime>, mut characters: Query<&mut CharacterState>) {
+    for mut state in &mut characters {
+        if let CharacterState::Stunned(mut timer) = *state {
+            *state = if timer.tick(time.ticks()).finished() {
+                CharacterState::Idle
+            } else {
+                CharacterState::Stunned(timer)
+            };
+        }
+    }
+}
+
+/// The state a character was in when transitions were last checked.
+#[derive(Component)]
+struct PreviousState(CharacterState);
+
+fn detect_transitions(
+    mut commands: Commands,
+    mut events: EventWriter<StateChanged>,
+    mut characters: Query<
+        (Entity, &CharacterState, Option<&mut PreviousState>),
+        Changed<CharacterState>,
+    >,
+) {
+    for (entity, state, previous) in &mut characters {
+        if let Some(mut previous) = previous {
+            if discriminant(&previous.0) != discriminant(state) {
+                events.send(StateChanged {
+                    entity,
+                    from: previous.0,
+                    to: *state,
+                });
+            }
+            previous.0 = *state;
+        } else {
+            commands.entity(entity).insert(PreviousState(*state));
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use std::cmp::Ordering;
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::{
+    ball_kinds::{BallKindTable, BallProperties},
+    game_time::Hitstop,
+    interactions::BallKind,
+    particles::ParticleEmitter,
+    rules::{match_active, Clashes, Ruleset},
+    separation::{DEAD_BALL_GROUP, PLAYER_GROUP},
+    Ball, BallState, DataAssets, ThrownBy,
+};
+
+/// Solver group of live balls. They don't push each other around physically, so a clash can be
+/// resolved from the velocities they had going into it.
+const LIVE_BALL_GROUP: u32 = 1 << 1;
+const CLASH_HITSTOP_SECONDS: f32 = 0.05;
+
+pub struct ClashPlugin;
+
+impl Plugin for ClashPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<BallClash>().add_system_set(
+            SystemSet::new()
+                .with_run_criteria(match_active)
+                .with_system(group_balls)
+                .with_system(resolve_clashes)
+                .with_system(clash_effects.after(resolve_clashes)),
+        );
+    }
+}
+
+/// Sent when two live balls meet in the air.
+pub struct BallClash {
+    pub position: Vec3,
+}
+
+/// Moves balls into the solver group for their state: live balls pass through each other, and
+/// dead ones through players.
+fn group_balls(
+    mut commands: Commands,
+    balls: Query<(Entity, &BallState, Option<&SolverGroups>), With<Ball>>,
+) {
+    for (entity, state, groups) in &balls {
+        let wanted = match state {
+            BallState::Live => SolverGroups::new(LIVE_BALL_GROUP, !LIVE_BALL_GROUP),
+            BallState::Dead | BallState::Held => SolverGroups::new(DEAD_BALL_GROUP, !PLAYER_GROUP),
+        };
+        if groups.map_or(true, |groups| groups.memberships != wanted.memberships) {
+            commands.entity(entity).insert(wanted);
+        }
+    }
+}
+
+fn resolve_clashes(
+    mut commands: Commands,
+    mut collisions: EventReader<CollisionEvent>,
+    mut clashes: EventWriter<BallClash>,
+    ruleset: Res<Ruleset>,
+    data: Res<DataAssets>,
+    tables: Res<Assets<BallKindTable>>,
+    mut balls: Query<(&GlobalTransform, &mut Velocity, &BallKind, &mut BallState), With<Ball>>,
+) {
+    let properties = |kind: BallKind| {
+        tables
+            .get(&data.ball_kinds)
+            .map_or_else(BallProperties::default, |table| table.get(kind))
+    };
+    for event in collisions.iter() {
+        let (e1, e2) = if let CollisionEvent::Started(e1, e2, _) = event {
+            (*e1, *e2)
+        } else {
+            continue;
+        };
+        let [(t1, mut v1, k1, mut s1), (t2, mut v2, k2, mut s2)] =
+            if let Ok(pair) = balls.get_many_mut([e1, e2]) {
+                pair
+            } else {
+                continue;
+            };
+        if *s1 != BallState::Live || *s2 != BallState::Live {
+            continue;
+        }
+        let normal = (t2.translation() - t1.translation()).normalize_or_zero();
+        match ruleset.ball_clashes {
+            Clashes::Deflect => {
+                v1.linvel = reflect(v1.linvel, normal);
+                v2.linvel = reflect(v2.linvel, normal);
+            }
+            Clashes::HeavierWins => {
+                match weight(properties(*k1)).total_cmp(&weight(properties(*k2))) {
+                    Ordering::Greater => drop_dead(&mut commands, e2, &mut v2, &mut s2),
+                    Ordering::Less => drop_dead(&mut commands, e1, &mut v1, &mut s1),
+                    Ordering::Equal => {
+                        drop_dead(&mut commands, e1, &mut v1, &mut s1);
+                        drop_dead(&mut commands, e2, &mut v2, &mut s2);
+                    }
+                }
+            }
+            Clashes::BothDead => {
+                drop_dead(&mut commands, e1, &mut v1, &mut s1);
+                drop_dead(&mut commands, e2, &mut v2, &mut s2);
+            }
+        }
+        clashes.send(BallClash {
+            position: t1.translation().lerp(t2.translation(), 0.5),
+        });
+    }
+}
+
+/// Bounces a velocity off the plane facing `normal`, keeping its speed.
+fn reflect(velocity: Vec3, normal: Vec3) -> Vec3 {
+    velocity - 2.0 * velocity.dot(normal) * normal
+}
+
+/// How heavy a kind of ball is relative to a standard one, from how dense and how big it is.
+fn weight(properties: BallProperties) -> f32 {
+    properties.density * properties.scale.powi(3)
+}
+
+/// Stops a ball in its tracks so it falls to the court, no longer anyone's throw.
+fn drop_dead(
+    commands: &mut Commands,
+    ball: Entity,
+    velocity: &mut Velocity,
+    state: &mut BallState,
+) {
+    *state = BallState::Dead;
+    velocity.linvel = Vec3::new(0.0, 0.0, velocity.linvel.z.min(0.0));
+    velocity.angvel = Vec3::ZERO;
+    commands.entity(ball).remove::<ThrownBy>();
+}
+
+fn clash_effects(
+    mut commands: Commands,
+    mut clashes: EventReader<BallClash>,
+    mut hitstop: EventWriter<Hitstop>,
+    data: Res<DataAssets>,
+) {
+    for clash in clashes.iter() {
+        commands
+            .spawn_bundle(SpatialBundle {
+                transform: Transform::from_translation(clash.position),
+                ..default()
+            })
+            .insert(ParticleEmitter::new(data.clash.clone()));
+        hitstop.send(Hitstop(CLASH_HITSTOP_SECONDS));
+    }
+}
@@ -0,0 +1,27 @@
+use bevy::prelude::*;
+
+use crate::GameState;
+
+pub struct CleanupPlugin;
+
+impl Plugin for CleanupPlugin {
+    fn build(&self, app: &mut App) {
+        for state in [GameState::Loading, GameState::Ready] {
+            app.add_system_set(SystemSet::on_exit(state).with_system(despawn_on_exit(state)));
+        }
+    }
+}
+
+/// Despawns an entity, along with its children, when the game leaves this state.
+#[derive(Component)]
+pub struct DespawnOnExit(pub GameState);
+
+fn despawn_on_exit(state: GameState) -> impl FnMut(Commands, Query<(Entity, &DespawnOnExit)>) {
+    move |mut commands, entities| {
+        for (entity, despawn) in &entities {
+            if despawn.0 == state {
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+    }
+}
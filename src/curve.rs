@@ -0,0 +1,83 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::{game_time::GameTime, rules::match_active, tick_timer::TickTimer, BallState};
+
+pub struct CurvePlugin;
+
+impl Plugin for CurvePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CurveSettings>()
+            .add_system(apply_curves.with_run_criteria(match_active));
+    }
+}
+
+/// How hard curveballs bend, tunable without touching the throw itself.
+pub struct CurveSettings {
+    /// Sideways acceleration, in metres per second squared.
+    pub strength: f32,
+    /// How long a throw keeps bending after it leaves the hand.
+    pub seconds: f32,
+    /// Spin put on the ball, just for show.
+    pub spin: f32,
+}
+
+impl Default for CurveSettings {
+    fn default() -> Self {
+        Self {
+            strength: 12.0,
+            seconds: 0.6,
+            spin: 20.0,
+        }
+    }
+}
+
+/// Bends a thrown ball sideways, the way spin would through the air.
+#[derive(Component)]
+pub struct Curve {
+    /// The horizontal direction the ball bends towards.
+    direction: Vec3,
+    timer: TickTimer,
+}
+
+impl Curve {
+    /// Bends a throw aimed along `aim`. A throw angled out bends back in across its line, and a
+    /// straight one bends towards the middle of the court from where it was thrown.
+    pub fn new(settings: &CurveSettings, aim: Vec2, from: Vec3) -> Self {
+        let side = if aim.x.abs() > f32::EPSILON {
+            -aim.x.signum()
+        } else {
+            -from.x.signum()
+        };
+        let across = Vec2::new(aim.y, -aim.x);
+        let across = if across.x.signum() == side {
+            across
+        } else {
+            -across
+        };
+        Self {
+            direction: across.extend(0.0).normalize_or_zero(),
+            timer: TickTimer::from_seconds(settings.seconds),
+        }
+    }
+
+    /// The spin to throw the ball with, around the vertical axis.
+    pub fn spin(&self, settings: &CurveSettings) -> Vec3 {
+        Vec3::Z * settings.spin * self.direction.x.signum()
+    }
+}
+
+fn apply_curves(
+    mut commands: Commands,
+    time: Res<GameTime>,
+    settings: Res<CurveSettings>,
+    mut balls: Query<(Entity, &mut Velocity, &mut Curve, &BallState)>,
+) {
+    for (ball, mut velocity, mut curve, state) in &mut balls {
+        if *state != BallState::Live || curve.timer.tick(time.ticks()).finished() {
+            commands.entity(ball).remove::<Curve>();
+            continue;
+        }
+        velocity.linvel += curve.direction * settings.strength * time.delta_seconds();
+    }
+}
@@ -0,0 +1,158 @@
+use bevy::{
+    input::gamepad::{GamepadEvent, GamepadEventType},
+    prelude::*,
+};
+use leafwing_input_manager::prelude::*;
+
+use crate::{
+    game_time::{HoldPhysics, PhysicsHold},
+    input::{InputContext, InputLayer},
+    settings::{ControlPreset, Settings},
+    ui::{
+        text::styled_text,
+        widgets::{self, WidgetStyle},
+    },
+    Action,
+};
+
+pub struct DevicePlugin;
+
+impl Plugin for DevicePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(gamepad_connections)
+            .add_system(reassign_devices.after(gamepad_connections))
+            .add_system(reconnect_prompt.after(reassign_devices));
+    }
+}
+
+/// Which local player this is, counting from zero.
+#[derive(Component, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct PlayerSlot(pub usize);
+
+/// Marks a player whose controller was disconnected and who is waiting for a new one.
+#[derive(Component)]
+pub struct AwaitingDevice;
+
+#[derive(Component)]
+struct ReconnectPrompt;
+
+fn gamepad_connections(
+    mut commands: Commands,
+    mut events: EventReader<GamepadEvent>,
+    settings: Res<Settings>,
+    mut players: Query<(
+        Entity,
+        &PlayerSlot,
+        &mut InputMap<Action>,
+        Option<&AwaitingDevice>,
+    )>,
+) {
+    for event in events.iter() {
+        match event.event_type {
+            GamepadEventType::Connected => {
+                if players
+                    .iter()
+                    .any(|(_, _, input_map, _)| input_map.gamepad() == Some(event.gamepad))
+                {
+                    continue;
+                }
+                // Players who lost their controller pick up a new one by pressing a button on
+                // it instead, so it is clear which controller goes to whom.
+                let free = players
+                    .iter_mut()
+                    .filter(|(_, slot, input_map, awaiting)| {
+                        input_map.gamepad().is_none()
+                            && awaiting.is_none()
+                            && (slot.0 != 0 || settings.controls == ControlPreset::Gamepad)
+                    })
+                    .min_by_key(|(_, slot, _, _)| **slot);
+                if let Some((_, slot, mut input_map, _)) = free {
+                    info!("Assigned {:?} to player {}", event.gamepad, slot.0 + 1);
+                    input_map.set_gamepad(event.gamepad);
+                }
+            }
+            GamepadEventType::Disconnected => {
+                for (entity, slot, mut input_map, _) in &mut players {
+                    if input_map.gamepad() == Some(event.gamepad) {
+                        info!("Player {} lost their controller", slot.0 + 1);
+                        input_map.clear_gamepad();
+                        commands.entity(entity).insert(AwaitingDevice);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Hands the first controller to press a button to the waiting player with the lowest slot.
+fn reassign_devices(
+    mut commands: Commands,
+    buttons: Res<Input<GamepadButton>>,
+    mut players: Query<(
+        Entity,
+        &PlayerSlot,
+        &mut InputMap<Action>,
+        Option<&AwaitingDevice>,
+    )>,
+) {
+    for button in buttons.get_just_pressed() {
+        if players
+            .iter()
+            .any(|(_, _, input_map, _)| input_map.gamepad() == Some(button.gamepad))
+        {
+            continue;
+        }
+        let waiting = players
+            .iter_mut()
+            .filter(|(_, _, _, awaiting)| awaiting.is_some())
+            .min_by_key(|(_, slot, _, _)| **slot);
+        if let Some((entity, slot, mut input_map, _)) = waiting {
+            info!("Assigned {:?} to player {}", button.gamepad, slot.0 + 1);
+            input_map.set_gamepad(button.gamepad);
+            commands.entity(entity).remove::<AwaitingDevice>();
+        }
+    }
+}
+
+/// Pauses the match and asks for a controller while any player is waiting for one.
+fn reconnect_prompt(
+    mut commands: Commands,
+    style: Res<WidgetStyle>,
+    mut holds: EventWriter<HoldPhysics>,
+    mut shown: Local<Vec<usize>>,
+    waiting: Query<&PlayerSlot, With<AwaitingDevice>>,
+    prompts: Query<Entity, With<ReconnectPrompt>>,
+) {
+    let mut slots = waiting.iter().map(|slot| slot.0).collect::<Vec<_>>();
+    slots.sort_unstable();
+    if slots == *shown {
+        return;
+    }
+
+    for prompt in &prompts {
+        commands.entity(prompt).despawn_recursive();
+    }
+    holds.send(HoldPhysics {
+        hold: PhysicsHold::Reconnect,
+        held: !slots.is_empty(),
+    });
+    if !slots.is_empty() {
+        // The prompt takes over input, so nobody keeps playing while the match is paused.
+        widgets::panel(&mut commands, &style)
+            .insert_bundle((ReconnectPrompt, InputLayer(InputContext::Menu)))
+            .with_children(|parent| {
+                for slot in &slots {
+                    parent.spawn_bundle(styled_text(
+                        format!("Reconnect controller for Player {}", slot + 1),
+                        "widget",
+                    ));
+                }
+                parent.spawn_bundle(styled_text(
+                    "Press a button on any controller to take over",
+                    "hud",
+                ));
+            });
+    }
+    *shown = slots;
+}
@@ -0,0 +1,105 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use leafwing_input_manager::prelude::*;
+
+use crate::{
+    character::CharacterState,
+    game_time::GameTime,
+    input_buffer::InputBuffer,
+    opening::gate_open,
+    player_control,
+    status::{StatusEffect, StatusEffects},
+    tick_timer::TickTimer,
+    Action, Facing, Player,
+};
+
+/// How fast a roll carries the player, and for how long.
+const ROLL_SPEED: f32 = 14.0;
+const ROLL_SECONDS: f32 = 0.3;
+/// How long into a roll live balls pass through the player. Shorter than the roll itself, so
+/// the end of it can still be punished.
+const IFRAME_SECONDS: f32 = 0.2;
+/// How long after a roll starts before the next one can, so it can't be chained forever.
+const COOLDOWN_SECONDS: f32 = 0.8;
+
+pub struct DodgePlugin;
+
+impl Plugin for DodgePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::new()
+                .with_run_criteria(gate_open)
+                .with_system(start_rolls)
+                // Rolling overrides whatever movement was read from input this frame.
+                .with_system(roll.after(start_rolls).after(player_control))
+                .with_system(cool_down),
+        );
+    }
+}
+
+/// A roll in progress, in this direction across the court.
+#[derive(Component)]
+pub struct Rolling {
+    direction: Vec2,
+    timer: TickTimer,
+}
+
+#[derive(Component)]
+struct RollCooldown(TickTimer);
+
+fn start_rolls(
+    mut commands: Commands,
+    mut players: Query<
+        (
+            Entity,
+            &ActionState<Action>,
+            &mut InputBuffer,
+            &CharacterState,
+            &Facing,
+            &mut StatusEffects,
+        ),
+        (With<Player>, Without<Rolling>, Without<RollCooldown>),
+    >,
+) {
+    for (entity, action_state, mut buffer, state, facing, mut effects) in &mut players {
+        if !state.is_free() || !state.is_grounded() || !buffer.take(action_state, Action::Dodge) {
+            continue;
+        }
+        // Facing follows the movement input, so the roll goes the way the player is pushing.
+        commands.entity(entity).insert_bundle((
+            Rolling {
+                direction: facing.0,
+                timer: TickTimer::from_seconds(ROLL_SECONDS),
+            },
+            RollCooldown(TickTimer::from_seconds(COOLDOWN_SECONDS)),
+        ));
+        effects.add(StatusEffect::Invulnerable, Some(IFRAME_SECONDS));
+    }
+}
+
+fn roll(
+    mut commands: Commands,
+    time: Res<GameTime>,
+    mut players: Query<(Entity, &mut Rolling, &mut Velocity, &CharacterState)>,
+) {
+    for (entity, mut rolling, mut velocity, state) in &mut players {
+        // Getting hit cuts the roll short.
+        if rolling.timer.tick(time.ticks()).finished() || !state.can_move() {
+            commands.entity(entity).remove::<Rolling>();
+            continue;
+        }
+        velocity.linvel = (rolling.direction * ROLL_SPEED).extend(velocity.linvel.z);
+    }
+}
+
+fn cool_down(
+    mut commands: Commands,
+    time: Res<GameTime>,
+    mut players: Query<(Entity, &mut RollCooldown)>,
+) {
+    for (entity, mut cooldown) in &mut players {
+        if cooldown.0.tick(time.ticks()).finished() {
+            commands.entity(entity).remove::<RollCooldown>();
+        }
+    }
+}
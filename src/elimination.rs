@@ -0,0 +1,226 @@
+use bevy::{prelude::*, utils::HashMap};
+use bevy_rapier3d::prelude::*;
+
+use crate::{
+    celebration::MatchOver,
+    character::{CharacterState, StateChanged},
+    drop_held_ball,
+    hit::Health,
+    jail::Jails,
+    rules::{match_active, MatchActive, MatchMode, Ruleset},
+    score::Score,
+    stage::ActiveStage,
+    team::{Team, TeamTint},
+    HoldingBall, Player,
+};
+
+/// How far outside the court eliminated players wait, and how far apart.
+const SIDELINE_MARGIN: f32 = 1.0;
+const SIDELINE_SPACING: f32 = 0.8;
+/// How far from the center line the first eliminated player of a team waits.
+const SIDELINE_START: f32 = 1.5;
+const ELIMINATED_COLOR: Color = Color::rgb(0.45, 0.45, 0.45);
+/// Height players are put back on the court at, as when they are first spawned.
+const SPAWN_HEIGHT: f32 = 0.25;
+
+pub struct EliminationPlugin;
+
+impl Plugin for EliminationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Reinstate>()
+            .add_system(sideline_eliminated)
+            .add_system(reinstate_players)
+            .add_system(last_team_standing.with_run_criteria(match_active));
+    }
+}
+
+/// Marks an eliminated player waiting out the rest of the match beside the court.
+#[derive(Component)]
+pub struct Sidelined;
+
+/// Sent to bring an eliminated player back into play at a spot on the court, with their health
+/// restored.
+pub struct Reinstate {
+    pub player: Entity,
+    pub position: Vec2,
+}
+
+/// Takes eliminated players off the court: balls pass through them, they stop falling, and they
+/// are greyed out and lined up along their own half's sideline, or in their jail when playing
+/// with jails. Any ball they were holding is dropped where they fell.
+fn sideline_eliminated(
+    mut commands: Commands,
+    mut events: EventReader<StateChanged>,
+    ruleset: Res<Ruleset>,
+    stage: Option<Res<ActiveStage>>,
+    mut jails: ResMut<Jails>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut players: Query<
+        (
+            &Team,
+            &mut Transform,
+            &mut Velocity,
+            &Children,
+            Option<&HoldingBall>,
+        ),
+        With<Player>,
+    >,
+    sidelined: Query<&Team, With<Sidelined>>,
+    sprites: Query<&Handle<StandardMaterial>>,
+) {
+    let half_width = stage.as_ref().map_or(0.0, |stage| stage.size.x / 2.0);
+    let mut waiting = HashMap::<Team, usize>::default();
+    for team in &sidelined {
+        *waiting.entry(*team).or_default() += 1;
+    }
+    for event in events.iter() {
+        if event.to != CharacterState::Eliminated {
+            continue;
+        }
+        let (team, mut transform, mut velocity, children, held) =
+            if let Ok(player) = players.get_mut(event.entity) {
+                player
+            } else {
+                continue;
+            };
+        if let Some(held) = held {
+            drop_held_ball(event.entity, held, transform.translation, &mut commands);
+        }
+        let index = waiting.entry(*team).or_default();
+        let jail = stage
+            .as_ref()
+            .and_then(|stage| stage.jails.get(team.0))
+            .filter(|_| ruleset.jail);
+        let position = if let Some(jail) = jail {
+            jails.lock_up(*team, event.entity);
+            // Inmates line up across the jail, alternating either side of it.
+            let side = if *index % 2 == 0 { 1.0 } else { -1.0 };
+            *jail + Vec2::X * side * ((*index + 1) / 2) as f32 * SIDELINE_SPACING
+        } else {
+            let side = if team.0 == 0 { -1.0 } else { 1.0 };
+            Vec2::new(
+                half_width + SIDELINE_MARGIN,
+                side * (SIDELINE_START + *index as f32 * SIDELINE_SPACING),
+            )
+        };
+        *index += 1;
+        transform.translation.x = position.x;
+        transform.translation.y = position.y;
+        *velocity = Velocity::zero();
+        commands.entity(event.entity).insert_bundle((
+            Sidelined,
+            RigidBody::Fixed,
+            CollisionGroups::new(0, 0),
+        ));
+        for child in children.iter() {
+            if let Some(material) = sprites
+                .get(*child)
+                .ok()
+                .and_then(|handle| materials.get_mut(handle))
+            {
+                let alpha = material.base_color.a();
+                material.base_color = ELIMINATED_COLOR;
+                material.base_color.set_a(alpha);
+            }
+        }
+    }
+}
+
+/// Undoes [`sideline_eliminated`] for players being brought back.
+fn reinstate_players(
+    mut commands: Commands,
+    mut events: EventReader<Reinstate>,
+    mut jails: ResMut<Jails>,
+    mut players: Query<
+        (
+            &mut CharacterState,
+            &mut Health,
+            &mut Transform,
+            &mut Velocity,
+            &Children,
+        ),
+        With<Sidelined>,
+    >,
+    mut tints: Query<&mut TeamTint>,
+) {
+    for event in events.iter() {
+        let (mut state, mut health, mut transform, mut velocity, children) =
+            if let Ok(player) = players.get_mut(event.player) {
+                player
+            } else {
+                continue;
+            };
+        jails.free(event.player);
+        *state = CharacterState::Idle;
+        health.current = health.max;
+        transform.translation = event.position.extend(SPAWN_HEIGHT);
+        *velocity = Velocity::zero();
+        commands
+            .entity(event.player)
+            .remove::<Sidelined>()
+            .remove::<CollisionGroups>()
+            .insert(RigidBody::Dynamic);
+        for child in children.iter() {
+            if let Ok(mut tint) = tints.get_mut(*child) {
+                tint.set_changed();
+            }
+        }
+    }
+}
+
+/// Ends a versus match once every player left standing is on the same team, or as a draw if the
+/// last of them went out together.
+///
+/// [`MatchActive`] is only removed once the commands sent on [`MatchOver`] are applied, so the
+/// match is remembered as decided to keep it from being ended twice.
+fn last_team_standing(
+    ruleset: Res<Ruleset>,
+    score: Res<Score>,
+    active: Res<MatchActive>,
+    mut decided: Local<bool>,
+    mut match_over: EventWriter<MatchOver>,
+    players: Query<(Entity, &Team, &CharacterState), With<Player>>,
+) {
+    if active.is_added() {
+        *decided = false;
+    }
+    if ruleset.mode != MatchMode::Versus || *decided {
+        return;
+    }
+    let mut standing = players
+        .iter()
+        .filter(|(_, _, state)| state.is_active())
+        .map(|(_, team, _)| *team);
+    let winner = standing.next();
+    if standing.any(|team| Some(team) != winner) {
+        return;
+    }
+    let (winners, losers) = players
+        .iter()
+        .partition::<Vec<_>, _>(|(_, team, _)| Some(**team) == winner);
+    if losers.is_empty() {
+        return;
+    }
+    let mut teams = players
+        .iter()
+        .map(|(_, team, _)| team.0)
+        .collect::<Vec<_>>();
+    teams.sort_unstable();
+    teams.dedup();
+    let score = teams
+        .iter()
+        .map(|team| score.get(Team(*team)).to_string())
+        .collect::<Vec<_>>()
+        .join(" - ");
+    if let Some(winner) = winner {
+        info!("{winner:?} is the last team standing");
+    } else {
+        info!("Nobody is left standing, so the match is a draw");
+    }
+    *decided = true;
+    match_over.send(MatchOver {
+        winners: winners.iter().map(|(entity, _, _)| *entity).collect(),
+        losers: losers.iter().map(|(entity, _, _)| *entity).collect(),
+        score,
+    });
+}
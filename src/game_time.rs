@@ -0,0 +1,235 @@
+use std::time::Duration;
+
+use bevy::{
+    diagnostic::{Diagnostic, DiagnosticId, Diagnostics},
+    prelude::*,
+    time::TimeSystem,
+    utils::HashSet,
+};
+use bevy_rapier3d::prelude::*;
+
+use crate::tick_timer::TICK_RATE;
+
+/// The most ticks gameplay catches up on in one frame. Time beyond that after a long hitch
+/// (alt-tab, an asset load) is dropped, rather than fast-forwarding through it all at once.
+const MAX_CATCH_UP_TICKS: u32 = 5;
+/// Frames longer than this are reported as hitches.
+const HITCH_THRESHOLD: Duration = Duration::from_millis(100);
+
+/// Length of the latest hitch, in milliseconds.
+pub const HITCH_DIAGNOSTIC: DiagnosticId =
+    DiagnosticId::from_u128(0x4f2a_91c3_7d6e_4b08_a5f1_3c8e_2d79_b610);
+/// Gameplay ticks dropped by the latest hitch.
+pub const DROPPED_TICKS_DIAGNOSTIC: DiagnosticId =
+    DiagnosticId::from_u128(0x8b3d_05e7_c21a_4f96_9e40_7a1b_6c58_d3f2);
+
+pub struct GameTimePlugin;
+
+impl Plugin for GameTimePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameTime>()
+            .add_event::<Hitstop>()
+            .add_event::<HoldPhysics>()
+            .add_startup_system(setup_diagnostics)
+            .add_system_to_stage(CoreStage::First, hold_physics.after(TimeSystem))
+            .add_system_to_stage(CoreStage::First, advance_game_time.after(hold_physics))
+            .add_system_to_stage(CoreStage::First, scale_physics.after(advance_game_time));
+        if cfg!(debug_assertions) {
+            app.add_system(frame_step);
+        }
+    }
+}
+
+/// The clock gameplay runs on, which can be slowed down, frozen for hitstop or a [`PhysicsHold`],
+/// or stepped a frame at a time. Physics follows it too. Menus, UI and the camera keep running on
+/// [`Time`].
+pub struct GameTime {
+    scale: f32,
+    /// Real seconds left of the current hitstop.
+    hitstop: f32,
+    paused: bool,
+    holds: HashSet<PhysicsHold>,
+    step: bool,
+    /// The scale the current frame was run at.
+    applied_scale: f32,
+    delta: Duration,
+    /// Game time not yet counted towards a whole tick.
+    leftover: Duration,
+    ticks: u32,
+}
+
+impl Default for GameTime {
+    fn default() -> Self {
+        Self {
+            scale: 1.0,
+            hitstop: 0.0,
+            paused: false,
+            holds: HashSet::default(),
+            step: false,
+            applied_scale: 1.0,
+            delta: Duration::ZERO,
+            leftover: Duration::ZERO,
+            ticks: 0,
+        }
+    }
+}
+
+/// Freezes gameplay for this many real seconds, unless a longer hitstop is already running.
+///
+/// Sent as an event so gameplay systems only ever need to read [`GameTime`], and can all run
+/// alongside each other.
+pub struct Hitstop(pub f32);
+
+/// Why gameplay is being held still. Physics and [`GameTime`] only run while nothing is holding
+/// them, so timers don't run out while the match is waiting on someone.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum PhysicsHold {
+    Reconnect,
+    StageTransition,
+    Celebration,
+    /// The first launch setup is being gone through.
+    Setup,
+    /// Waiting to hear whether an autosaved match is resumed, before the match has got going.
+    ResumePrompt,
+}
+
+/// Starts or stops holding gameplay still, without each caller needing the rapier config.
+pub struct HoldPhysics {
+    pub hold: PhysicsHold,
+    pub held: bool,
+}
+
+impl GameTime {
+    pub fn delta(&self) -> Duration {
+        self.delta
+    }
+
+    pub fn delta_seconds(&self) -> f32 {
+        self.delta.as_secs_f32()
+    }
+
+    /// How many whole gameplay ticks passed this frame, for [`TickTimer`](crate::tick_timer::TickTimer)s.
+    pub fn ticks(&self) -> u32 {
+        self.ticks
+    }
+
+    /// Sets how fast gameplay runs compared to real time, for slow motion.
+    pub fn set_scale(&mut self, scale: f32) {
+        self.scale = scale.max(0.0);
+    }
+
+    pub fn paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    /// Runs a single frame at normal speed while paused.
+    pub fn step(&mut self) {
+        self.step = true;
+    }
+
+    /// The scale gameplay runs at this frame, with holds, hitstop and pausing taken into account.
+    fn current_scale(&self) -> f32 {
+        if !self.holds.is_empty() {
+            0.0
+        } else if self.paused {
+            if self.step {
+                1.0
+            } else {
+                0.0
+            }
+        } else if self.hitstop > 0.0 {
+            0.0
+        } else {
+            self.scale
+        }
+    }
+}
+
+fn setup_diagnostics(mut diagnostics: ResMut<Diagnostics>) {
+    diagnostics.add(Diagnostic::new(HITCH_DIAGNOSTIC, "hitch_ms", 20));
+    diagnostics.add(Diagnostic::new(
+        DROPPED_TICKS_DIAGNOSTIC,
+        "dropped_ticks",
+        20,
+    ));
+}
+
+fn advance_game_time(
+    time: Res<Time>,
+    mut game_time: ResMut<GameTime>,
+    mut hitstops: EventReader<Hitstop>,
+    mut diagnostics: ResMut<Diagnostics>,
+) {
+    for Hitstop(seconds) in hitstops.iter() {
+        game_time.hitstop = game_time.hitstop.max(*seconds);
+    }
+    let scale = game_time.current_scale();
+    game_time.applied_scale = scale;
+    game_time.hitstop = (game_time.hitstop - time.delta_seconds()).max(0.0);
+    game_time.step = false;
+
+    let tick = Duration::from_secs(1) / TICK_RATE;
+    let max_delta = tick * MAX_CATCH_UP_TICKS;
+    let delta = time.delta().mul_f32(scale);
+    game_time.delta = delta.min(max_delta);
+
+    let mut leftover = game_time.leftover + game_time.delta;
+    let mut ticks = 0;
+    while leftover >= tick {
+        leftover -= tick;
+        ticks += 1;
+    }
+    game_time.leftover = leftover;
+    game_time.ticks = ticks;
+
+    if time.delta() > HITCH_THRESHOLD {
+        let dropped = (delta.saturating_sub(max_delta).as_nanos() / tick.as_nanos()) as u32;
+        warn!(
+            "Frame took {:.0} ms, dropped {dropped} gameplay ticks",
+            time.delta().as_secs_f64() * 1000.0
+        );
+        diagnostics.add_measurement(HITCH_DIAGNOSTIC, || time.delta().as_secs_f64() * 1000.0);
+        diagnostics.add_measurement(DROPPED_TICKS_DIAGNOSTIC, || f64::from(dropped));
+    }
+}
+
+/// Rapier caps each step at its `max_dt` on its own, so a hitch never asks it for more than one
+/// tick's worth of simulation either.
+fn scale_physics(game_time: Res<GameTime>, mut rapier: ResMut<RapierConfiguration>) {
+    if let TimestepMode::Variable { time_scale, .. } = &mut rapier.timestep_mode {
+        *time_scale = game_time.applied_scale;
+    }
+}
+
+fn hold_physics(
+    mut events: EventReader<HoldPhysics>,
+    mut game_time: ResMut<GameTime>,
+    mut rapier: ResMut<RapierConfiguration>,
+) {
+    let mut changed = false;
+    for event in events.iter() {
+        changed |= if event.held {
+            game_time.holds.insert(event.hold)
+        } else {
+            game_time.holds.remove(&event.hold)
+        };
+    }
+    if changed {
+        rapier.physics_pipeline_active = game_time.holds.is_empty();
+    }
+}
+
+/// Debug controls for freezing gameplay and stepping through it frame by frame.
+fn frame_step(keys: Res<Input<KeyCode>>, mut game_time: ResMut<GameTime>) {
+    if keys.just_pressed(KeyCode::Pause) {
+        let paused = !game_time.paused();
+        game_time.set_paused(paused);
+    }
+    if keys.just_pressed(KeyCode::F10) && game_time.paused() {
+        game_time.step();
+    }
+}
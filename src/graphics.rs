@@ -0,0 +1,239 @@
+use bevy::{
+    prelude::*,
+    render::{
+        camera::RenderTarget,
+        render_resource::{
+            Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
+        },
+        texture::ImageSampler,
+        view::RenderLayers,
+    },
+    window::WindowId,
+};
+
+use crate::settings::Settings;
+
+pub const PIXEL_RESOLUTION: UVec2 = UVec2::new(320, 180);
+const SPRITE_DEPTH_BIAS: f32 = 0.01;
+const SPRITE_ALPHA_CUTOFF: f32 = 0.5;
+
+pub struct GraphicsPlugin;
+
+impl Plugin for GraphicsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PixelTarget>()
+            .add_system(apply_pixel_perfect)
+            .add_system(fit_upscale_sprite.after(apply_pixel_perfect))
+            .add_system(sort_sprites)
+            .add_system(billboard)
+            .add_system(apply_sprite_alpha);
+    }
+}
+
+/// The low resolution image the game camera renders into in pixel-perfect mode.
+struct PixelTarget(Handle<Image>);
+
+impl FromWorld for PixelTarget {
+    fn from_world(world: &mut World) -> Self {
+        let size = Extent3d {
+            width: PIXEL_RESOLUTION.x,
+            height: PIXEL_RESOLUTION.y,
+            ..default()
+        };
+        let mut image = Image {
+            texture_descriptor: TextureDescriptor {
+                label: None,
+                size,
+                dimension: TextureDimension::D2,
+                format: TextureFormat::Bgra8UnormSrgb,
+                mip_level_count: 1,
+                sample_count: 1,
+                usage: TextureUsages::TEXTURE_BINDING
+                    | TextureUsages::COPY_DST
+                    | TextureUsages::RENDER_ATTACHMENT,
+            },
+            sampler_descriptor: ImageSampler::nearest(),
+            ..default()
+        };
+        image.resize(size);
+        Self(world.resource_mut::<Assets<Image>>().add(image))
+    }
+}
+
+#[derive(Component)]
+struct UpscaleCamera;
+
+#[derive(Component)]
+struct UpscaleSprite;
+
+fn apply_pixel_perfect(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    target: Res<PixelTarget>,
+    mut cameras: Query<(Entity, &mut Camera), With<Camera3d>>,
+    added_cameras: Query<(), Added<Camera3d>>,
+    upscale: Query<Entity, Or<(With<UpscaleCamera>, With<UpscaleSprite>)>>,
+) {
+    if !settings.is_changed() && added_cameras.is_empty() {
+        return;
+    }
+
+    let enabled = settings.pixel_perfect;
+    for (entity, mut camera) in &mut cameras {
+        camera.target = if enabled {
+            RenderTarget::Image(target.0.clone())
+        } else {
+            RenderTarget::Window(WindowId::primary())
+        };
+        commands
+            .entity(entity)
+            .insert(UiCameraConfig { show_ui: !enabled });
+    }
+
+    if enabled && upscale.is_empty() {
+        commands
+            .spawn_bundle(Camera2dBundle {
+                camera: Camera {
+                    priority: 1,
+                    ..default()
+                },
+                ..default()
+            })
+            .insert_bundle((RenderLayers::layer(1), UpscaleCamera));
+        commands
+            .spawn_bundle(SpriteBundle {
+                texture: target.0.clone(),
+                ..default()
+            })
+            .insert_bundle((RenderLayers::layer(1), UpscaleSprite));
+    } else if !enabled {
+        for entity in &upscale {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+/// Scales the low resolution image by the largest whole factor that fits the window.
+fn fit_upscale_sprite(windows: Res<Windows>, mut sprites: Query<&mut Sprite, With<UpscaleSprite>>) {
+    let window = if let Some(window) = windows.get_primary() {
+        window
+    } else {
+        return;
+    };
+    let resolution = PIXEL_RESOLUTION.as_vec2();
+    let scale = (window.width() / resolution.x)
+        .min(window.height() / resolution.y)
+        .floor()
+        .max(1.0);
+    for mut sprite in &mut sprites {
+        let size = Some(resolution * scale);
+        if sprite.custom_size != size {
+            sprite.custom_size = size;
+        }
+    }
+}
+
+/// Marks a sprite that is depth sorted against the other sprites on the court.
+///
+/// Sprites are nudged towards the camera in order of their distance to it, so overlapping
+/// characters always blend in the same order instead of flickering when their depths tie.
+#[derive(Component)]
+pub struct SpriteDepth;
+
+fn sort_sprites(
+    cameras: Query<&GlobalTransform, With<Camera3d>>,
+    mut sprites: Query<(Entity, &Parent, &mut Transform), With<SpriteDepth>>,
+    parents: Query<&GlobalTransform>,
+) {
+    let camera = if let Some(camera) = cameras.iter().next() {
+        camera.translation()
+    } else {
+        return;
+    };
+
+    let mut order = sprites
+        .iter()
+        .filter_map(|(entity, parent, _)| {
+            let parent = parents.get(parent.get()).ok()?.compute_transform();
+            Some((entity, parent, parent.translation.distance_squared(camera)))
+        })
+        .collect::<Vec<_>>();
+    order.sort_by(|(a, _, a_distance), (b, _, b_distance)| {
+        b_distance.total_cmp(a_distance).then(a.cmp(b))
+    });
+
+    for (rank, (entity, parent, _)) in order.into_iter().enumerate() {
+        if let Ok((_, _, mut transform)) = sprites.get_mut(entity) {
+            let towards_camera = (camera - parent.translation).normalize_or_zero();
+            transform.translation =
+                parent.rotation.inverse() * towards_camera * (rank as f32 * SPRITE_DEPTH_BIAS);
+        }
+    }
+}
+
+fn apply_sprite_alpha(
+    settings: Res<Settings>,
+    sprites: Query<&Handle<StandardMaterial>, With<SpriteDepth>>,
+    added_sprites: Query<(), Added<SpriteDepth>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    if !settings.is_changed() && added_sprites.is_empty() {
+        return;
+    }
+    let alpha_mode = if settings.sprite_alpha_cutoff {
+        AlphaMode::Mask(SPRITE_ALPHA_CUTOFF)
+    } else {
+        AlphaMode::Blend
+    };
+    for handle in &sprites {
+        if let Some(material) = materials.get_mut(handle) {
+            material.alpha_mode = alpha_mode;
+        }
+    }
+}
+
+/// Keeps a sprite facing the camera.
+#[derive(Component, Clone, Copy)]
+pub enum Billboard {
+    /// Faces the camera plane exactly, like a screen-space sprite.
+    Full,
+    /// Only turns around the world's vertical axis, so the sprite stays upright on the court.
+    Vertical,
+}
+
+fn billboard(
+    cameras: Query<&GlobalTransform, With<Camera3d>>,
+    mut sprites: Query<(
+        &Billboard,
+        &mut Transform,
+        &GlobalTransform,
+        Option<&Parent>,
+    )>,
+    parents: Query<&GlobalTransform>,
+) {
+    let camera = if let Some(camera) = cameras.iter().next() {
+        camera.compute_transform()
+    } else {
+        return;
+    };
+
+    for (billboard, mut transform, global_transform, parent) in &mut sprites {
+        let rotation = match billboard {
+            Billboard::Full => camera.rotation,
+            Billboard::Vertical => {
+                let normal = (camera.translation - global_transform.translation())
+                    .truncate()
+                    .extend(0.0)
+                    .normalize_or_zero();
+                if normal == Vec3::ZERO {
+                    continue;
+                }
+                Quat::from_mat3(&Mat3::from_cols(Vec3::Z.cross(normal), Vec3::Z, normal))
+            }
+        };
+        let parent_rotation = parent
+            .and_then(|parent| parents.get(parent.get()).ok())
+            .map_or(Quat::IDENTITY, |parent| parent.compute_transform().rotation);
+        transform.rotation = parent_rotation.inverse() * rotation;
+    }
+}
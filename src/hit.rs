@@ -0,0 +1,247 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::{
+    ball_kinds::BallKindTable,
+    boss::Boss,
+    character::CharacterState,
+    game_time::Hitstop,
+    interactions::{BallKind, InteractionTable},
+    rules::{match_active, Headshots, Ruleset, Slowed},
+    stage::{Ground, Wall},
+    status::{StatusEffect, StatusEffects},
+    team::Team,
+    tick_timer::TickTimer,
+    Ball, BallState, DataAssets, Player, ThrownBy,
+};
+
+/// Balls slower than this are rolling around rather than flying at anyone.
+pub const LIVE_BALL_SPEED: f32 = 5.0;
+/// Hits above this height relative to the character's center count as headshots.
+const HEAD_HEIGHT: f32 = 0.1;
+const HIT_KNOCKBACK: f32 = 0.3;
+/// Knockback grows with the speed of the ball, reaching [`HIT_KNOCKBACK`] at this speed, up to
+/// a cap so the fastest balls don't send players flying off the court.
+const KNOCKBACK_SPEED: f32 = 14.0;
+const MAX_KNOCKBACK_SCALE: f32 = 1.5;
+/// How long a hit leaves a player unable to act while they're knocked back.
+const HIT_STUN_SECONDS: f32 = 0.3;
+/// How long gameplay freezes on impact, in real seconds.
+const HITSTOP_SECONDS: f32 = 0.06;
+const HEADSHOT_HITSTOP_SECONDS: f32 = 0.12;
+
+pub struct HitPlugin;
+
+impl Plugin for HitPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Hit>().add_system_set(
+            SystemSet::new()
+                .with_run_criteria(match_active)
+                .with_system(detect_hits)
+                .with_system(apply_hits.after(detect_hits))
+                .with_system(kill_balls.after(detect_hits)),
+        );
+    }
+}
+
+/// The hits a character can take before it is eliminated.
+#[derive(Component)]
+pub struct Health {
+    pub current: u32,
+    pub max: u32,
+}
+
+impl Health {
+    pub fn new(max: u32) -> Self {
+        Self { current: max, max }
+    }
+}
+
+/// Sent when a live ball strikes a player.
+pub struct Hit {
+    pub player: Entity,
+    pub ball: Entity,
+    /// Horizontal direction the player is knocked towards.
+    pub direction: Vec3,
+    pub headshot: bool,
+    /// How fast the ball was flying when it struck.
+    pub speed: f32,
+    /// Whoever threw or last deflected the ball, if it still counted as their throw.
+    pub thrower: Option<Entity>,
+    /// Where the ball came from: its thrower if it had one, otherwise where it struck.
+    pub source: Vec3,
+    pub kind: BallKind,
+}
+
+pub fn detect_hits(
+    mut collisions: EventReader<CollisionEvent>,
+    mut hits: EventWriter<Hit>,
+    data: Res<DataAssets>,
+    ruleset: Res<Ruleset>,
+    tables: Res<Assets<InteractionTable>>,
+    balls: Query<
+        (
+            &GlobalTransform,
+            &BallState,
+            &BallKind,
+            Option<&ThrownBy>,
+            Option<&Velocity>,
+        ),
+        With<Ball>,
+    >,
+    players: Query<(&GlobalTransform, &CharacterState, &Team, &StatusEffects), With<Player>>,
+    transforms: Query<&GlobalTransform>,
+    teams: Query<&Team>,
+) {
+    let table = if let Some(table) = tables.get(&data.interactions) {
+        table
+    } else {
+        return;
+    };
+    for event in collisions.iter() {
+        if let CollisionEvent::Started(e1, e2, _) = event {
+            let (player, ball) = if players.contains(*e1) && balls.contains(*e2) {
+                (*e1, *e2)
+            } else if players.contains(*e2) && balls.contains(*e1) {
+                (*e2, *e1)
+            } else {
+                continue;
+            };
+            let (player_transform, state, team, effects) = players.get(player).unwrap();
+            let (ball_transform, ball_state, kind, thrown_by, velocity) = balls.get(ball).unwrap();
+            let interaction = table.get(*kind, *state);
+            // Balls that can be caught are dealt with by the catch system instead.
+            if *ball_state != BallState::Live
+                || !interaction.hit
+                || (interaction.catch && matches!(state, CharacterState::Catching(_)))
+                || effects.contains(StatusEffect::Invulnerable)
+            {
+                continue;
+            }
+            // Teammates' throws pass harmlessly unless friendly fire is on, and nobody is hit by
+            // their own.
+            let thrower_team = thrown_by.and_then(|ThrownBy(thrower)| teams.get(*thrower).ok());
+            let own_throw = thrown_by.map(|ThrownBy(thrower)| *thrower) == Some(player);
+            if (thrower_team == Some(team) && !ruleset.friendly_fire) || own_throw {
+                continue;
+            }
+
+            // The contact has already been resolved by now, so the hit is judged from where
+            // the ball is relative to the player rather than from the contact manifold.
+            let offset = player_transform.translation() - ball_transform.translation();
+            let thrower = thrown_by.map(|ThrownBy(thrower)| *thrower);
+            hits.send(Hit {
+                player,
+                ball,
+                direction: offset.truncate().extend(0.0).normalize_or_zero(),
+                headshot: -offset.z > HEAD_HEIGHT,
+                speed: velocity.map_or(0.0, |velocity| velocity.linvel.length()),
+                thrower,
+                source: thrower
+                    .and_then(|thrower| transforms.get(thrower).ok())
+                    .map_or(ball_transform.translation(), GlobalTransform::translation),
+                kind: *kind,
+            });
+        }
+    }
+}
+
+/// Wall bounces a live ball has taken since it was last thrown or deflected.
+#[derive(Component, Default)]
+pub struct Ricochets(pub u32);
+
+/// A live ball goes dead once it bounces off the court, strikes someone, slows down or bounces
+/// off more walls than the ruleset allows. It then stops being anyone's throw, so whoever picks
+/// it up next isn't blamed or credited for it.
+pub fn kill_balls(
+    mut commands: Commands,
+    mut collisions: EventReader<CollisionEvent>,
+    mut hits: EventReader<Hit>,
+    ruleset: Res<Ruleset>,
+    mut balls: Query<(Entity, &mut BallState, &mut Ricochets, Option<&Velocity>), With<Ball>>,
+    ground: Query<(), With<Ground>>,
+    walls: Query<(), With<Wall>>,
+) {
+    let mut dead = hits.iter().map(|hit| hit.ball).collect::<Vec<_>>();
+    let mut banked = Vec::new();
+    for event in collisions.iter() {
+        if let CollisionEvent::Started(e1, e2, _) = event {
+            if ground.contains(*e1) {
+                dead.push(*e2);
+            } else if ground.contains(*e2) {
+                dead.push(*e1);
+            } else if walls.contains(*e1) {
+                banked.push(*e2);
+            } else if walls.contains(*e2) {
+                banked.push(*e1);
+            }
+        }
+    }
+    for (ball, mut state, mut ricochets, velocity) in &mut balls {
+        if *state != BallState::Live {
+            continue;
+        }
+        if banked.contains(&ball) {
+            ricochets.0 += 1;
+        }
+        let slow = velocity.map_or(true, |velocity| velocity.linvel.length() < LIVE_BALL_SPEED);
+        if slow || dead.contains(&ball) || ricochets.0 > ruleset.ricochets {
+            ricochets.0 = 0;
+            *state = BallState::Dead;
+            commands
+                .entity(ball)
+                .remove::<ThrownBy>()
+                .remove::<Slowed>();
+        }
+    }
+}
+
+pub fn apply_hits(
+    ruleset: Res<Ruleset>,
+    data: Res<DataAssets>,
+    tables: Res<Assets<BallKindTable>>,
+    mut hitstops: EventWriter<Hitstop>,
+    mut hits: EventReader<Hit>,
+    mut players: Query<(
+        &mut ExternalImpulse,
+        &mut CharacterState,
+        &mut Health,
+        Option<&Boss>,
+    )>,
+) {
+    for hit in hits.iter() {
+        hitstops.send(Hitstop(if hit.headshot {
+            HEADSHOT_HITSTOP_SECONDS
+        } else {
+            HITSTOP_SECONDS
+        }));
+        let (mut impulse, mut state, mut health, boss) =
+            if let Ok(player) = players.get_mut(hit.player) {
+                player
+            } else {
+                continue;
+            };
+
+        let (damage, knockback) = match (hit.headshot, ruleset.headshots) {
+            // Bosses go by their own rules, where headshots just count double.
+            (true, _) if boss.is_some() => (2, HIT_KNOCKBACK),
+            (true, Headshots::Knockback(multiplier)) => (1, HIT_KNOCKBACK * multiplier),
+            (true, Headshots::Eliminate) => (health.current, HIT_KNOCKBACK),
+            _ => (1, HIT_KNOCKBACK),
+        };
+        let knockback = knockback
+            * (hit.speed / KNOCKBACK_SPEED).min(MAX_KNOCKBACK_SCALE)
+            * tables
+                .get(&data.ball_kinds)
+                .map_or(1.0, |table| table.get(hit.kind).knockback);
+        health.current = health.current.saturating_sub(damage);
+        impulse.impulse += hit.direction * knockback;
+        if health.current == 0 {
+            info!("Player {:?} was eliminated", hit.player);
+            *state = CharacterState::Eliminated;
+        } else {
+            // Stunned players are left alone by their controls, so the knockback carries them.
+            *state = CharacterState::Stunned(TickTimer::from_seconds(HIT_STUN_SECONDS));
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use bevy::prelude::*;
+use leafwing_input_manager::{plugin::InputManagerSystem, prelude::*};
+
+use crate::{ui::widgets::MenuAction, Action, GameState};
+
+pub struct InputContextPlugin;
+
+impl Plugin for InputContextPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ActiveInputContext>()
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                gate_actions.after(InputManagerSystem::Update),
+            );
+    }
+}
+
+/// Which set of actions the physical inputs currently drive.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum InputContext {
+    Menu,
+    Gameplay,
+    Editor,
+    Photo,
+    /// Typing into the debug console, which takes every key for itself.
+    Console,
+}
+
+impl InputContext {
+    /// The context a game state starts out in, before any layers are put on top of it.
+    fn base(state: &GameState) -> Self {
+        match state {
+            GameState::Loading => Self::Menu,
+            GameState::Ready => Self::Gameplay,
+        }
+    }
+
+    fn allows_menu_action(self, action: MenuAction) -> bool {
+        match self {
+            Self::Menu => true,
+            // The settings menu can be brought up from anywhere.
+            Self::Gameplay => matches!(
+                action,
+                MenuAction::Settings
+                    | MenuAction::Scoreboard
+                    | MenuAction::SaveScenario
+                    | MenuAction::ResetScenario
+                    | MenuAction::Scenarios
+            ),
+            Self::Editor | Self::Photo => matches!(action, MenuAction::Settings),
+            Self::Console => false,
+        }
+    }
+
+    fn allows_gameplay(self) -> bool {
+        self == Self::Gameplay
+    }
+}
+
+/// Puts an input context on top of the one the game state provides, for as long as the entity
+/// exists.
+#[derive(Component)]
+pub struct InputLayer(pub InputContext);
+
+pub struct ActiveInputContext(pub InputContext);
+
+impl Default for ActiveInputContext {
+    fn default() -> Self {
+        Self(InputContext::Menu)
+    }
+}
+
+/// Releases every action the active context does not own, right after the input managers have
+/// read this frame's input.
+///
+/// On a context switch every action is consumed as well, so a button held through the switch
+/// does not register as a fresh press in the new context.
+fn gate_actions(
+    state: Res<State<GameState>>,
+    mut context: ResMut<ActiveInputContext>,
+    layers: Query<(Entity, &InputLayer)>,
+    mut menu_actions: ResMut<ActionState<MenuAction>>,
+    mut gameplay_actions: Query<&mut ActionState<Action>>,
+) {
+    // Layers rarely overlap, and when they do any consistent pick will do.
+    let active = layers
+        .iter()
+        .max_by_key(|(entity, _)| *entity)
+        .map_or_else(|| InputContext::base(state.current()), |(_, layer)| layer.0);
+
+    if context.0 != active {
+        info!("Switching input context from {:?} to {active:?}", context.0);
+        context.0 = active;
+        for action in MenuAction::variants() {
+            menu_actions.consume(action);
+        }
+        for mut action_state in &mut gameplay_actions {
+            for action in Action::variants() {
+                action_state.consume(action);
+            }
+        }
+    }
+
+    for action in MenuAction::variants() {
+        if !active.allows_menu_action(action) {
+            menu_actions.release(action);
+        }
+    }
+    if !active.allows_gameplay() {
+        for mut action_state in &mut gameplay_actions {
+            action_state.release_all();
+        }
+    }
+}
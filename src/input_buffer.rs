@@ -0,0 +1,107 @@
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::*;
+
+use crate::{game_time::GameTime, rules::match_active, Action};
+
+pub struct InputBufferPlugin;
+
+impl Plugin for InputBufferPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<InputBufferSettings>()
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                buffer_presses.with_run_criteria(match_active),
+            );
+    }
+}
+
+/// How many ticks a press is held on to while it can't be acted on yet, part of the balance
+/// tuning.
+pub struct InputBufferSettings {
+    pub throw: u32,
+    pub catch: u32,
+    pub pickup: u32,
+    pub dodge: u32,
+}
+
+impl Default for InputBufferSettings {
+    fn default() -> Self {
+        Self {
+            throw: 8,
+            catch: 6,
+            pickup: 8,
+            dodge: 6,
+        }
+    }
+}
+
+impl InputBufferSettings {
+    fn ticks(&self, action: Action) -> u32 {
+        match action {
+            Action::Throw | Action::Lob => self.throw,
+            Action::Catch => self.catch,
+            Action::Pickup => self.pickup,
+            Action::Dodge => self.dodge,
+            _ => 0,
+        }
+    }
+}
+
+/// Presses a character couldn't act on when they happened, such as a throw pressed while still
+/// recovering from the last one, kept for a few ticks so they go off as soon as they're allowed.
+#[derive(Component)]
+pub struct InputBuffer {
+    /// Ticks left on each action's buffered press, indexed by action.
+    remaining: Vec<u32>,
+    /// Actions acted on this frame, which mustn't be buffered as well.
+    consumed: Vec<bool>,
+}
+
+impl Default for InputBuffer {
+    fn default() -> Self {
+        Self {
+            remaining: vec![0; Action::n_variants()],
+            consumed: vec![false; Action::n_variants()],
+        }
+    }
+}
+
+impl InputBuffer {
+    /// Whether the action was pressed just now or recently enough to still count.
+    pub fn pending(&self, action_state: &ActionState<Action>, action: Action) -> bool {
+        action_state.just_pressed(action) || self.remaining[action.index()] > 0
+    }
+
+    /// Like [`InputBuffer::pending`], but uses the press up. Call it only once the action is
+    /// actually going ahead.
+    pub fn take(&mut self, action_state: &ActionState<Action>, action: Action) -> bool {
+        if !self.pending(action_state, action) {
+            return false;
+        }
+        self.remaining[action.index()] = 0;
+        self.consumed[action.index()] = true;
+        true
+    }
+}
+
+/// Buffers this frame's presses that nothing acted on, once every system has had the chance to.
+fn buffer_presses(
+    time: Res<GameTime>,
+    settings: Res<InputBufferSettings>,
+    mut players: Query<(&ActionState<Action>, &mut InputBuffer)>,
+) {
+    let ticks = time.ticks();
+    for (action_state, mut buffer) in &mut players {
+        let buffer = &mut *buffer;
+        for action in Action::variants() {
+            let index = action.index();
+            let remaining = &mut buffer.remaining[index];
+            if action_state.just_pressed(action) && !buffer.consumed[index] {
+                *remaining = settings.ticks(action);
+            } else {
+                *remaining = remaining.saturating_sub(ticks);
+            }
+            buffer.consumed[index] = false;
+        }
+    }
+}
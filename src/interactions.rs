@@ -0,0 +1,125 @@
+use bevy::{prelude::*, reflect::TypeUuid};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    character::CharacterState,
+    ron_loader::{RonAsset, RonLoader},
+};
+
+pub struct InteractionPlugin;
+
+impl Plugin for InteractionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<InteractionTable>()
+            .init_asset_loader::<RonLoader<InteractionTable>>();
+    }
+}
+
+/// What sort of ball something is, for the rules to tell them apart.
+#[derive(Component, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub enum BallKind {
+    #[default]
+    Standard,
+    /// Knocks whoever it hits a long way back.
+    Heavy,
+    /// Thrown faster than any other ball.
+    Speed,
+    /// Breaks into fragments when it bounces.
+    Split,
+    /// A piece of a split ball.
+    Fragment,
+    /// Thrown by the boss in a barrage.
+    Barrage,
+}
+
+/// A character state without its progress, for rules to match against.
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StateKind {
+    Idle,
+    Run,
+    Jump,
+    Fall,
+    Throwing,
+    Catching,
+    Stunned,
+    Eliminated,
+}
+
+impl From<CharacterState> for StateKind {
+    fn from(state: CharacterState) -> Self {
+        match state {
+            CharacterState::Idle => Self::Idle,
+            CharacterState::Run => Self::Run,
+            CharacterState::Jump => Self::Jump,
+            CharacterState::Fall => Self::Fall,
+            CharacterState::Throwing(_) => Self::Throwing,
+            CharacterState::Catching(_) => Self::Catching,
+            CharacterState::Stunned(_) => Self::Stunned,
+            // Nothing can reach a character off the court, so they count as out meanwhile.
+            CharacterState::Respawning(_) | CharacterState::Eliminated => Self::Eliminated,
+        }
+    }
+}
+
+/// What happens when a live ball meets a character.
+#[derive(Deserialize, Clone, Copy, Debug)]
+pub struct BallInteraction {
+    /// Whether the ball counts as a hit.
+    pub hit: bool,
+    /// Whether the character can catch the ball, which takes precedence over a hit.
+    pub catch: bool,
+    /// What the ball does when it strikes a ball the character has raised to block with.
+    #[serde(default)]
+    pub block: BlockOutcome,
+}
+
+/// How a block against a kind of ball turns out.
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum BlockOutcome {
+    /// The ball drops dead, and the blocker keeps hold of theirs unless the ruleset says
+    /// otherwise.
+    #[default]
+    Blocked,
+    /// The ball drops dead, but knocks the blocker's ball out of their hands.
+    KnocksBallLoose,
+    /// The ball carries on through the block, which ends there and then.
+    BreaksBlock,
+}
+
+#[derive(Deserialize)]
+struct InteractionRule {
+    /// The kind of ball the rule is for, or every kind if left out.
+    #[serde(default)]
+    ball: Option<BallKind>,
+    /// The state the rule is for, or every state if left out.
+    #[serde(default)]
+    state: Option<StateKind>,
+    interaction: BallInteraction,
+}
+
+/// How every kind of ball interacts with characters in every state, kept in one data file so the
+/// rules can be read and changed in one place.
+#[derive(Deserialize, TypeUuid)]
+#[uuid = "d81c5e3a-2b94-4f70-a6e1-9c03f7b2d548"]
+pub struct InteractionTable {
+    default: BallInteraction,
+    rules: Vec<InteractionRule>,
+}
+
+impl InteractionTable {
+    /// The interaction from the first rule that matches, in file order.
+    pub fn get(&self, ball: BallKind, state: CharacterState) -> BallInteraction {
+        let state = StateKind::from(state);
+        self.rules
+            .iter()
+            .find(|rule| {
+                rule.ball.map_or(true, |kind| kind == ball)
+                    && rule.state.map_or(true, |kind| kind == state)
+            })
+            .map_or(self.default, |rule| rule.interaction)
+    }
+}
+
+impl RonAsset for InteractionTable {
+    const EXTENSIONS: &'static [&'static str] = &["interactions.ron"];
+}
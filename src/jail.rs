@@ -0,0 +1,80 @@
+use std::collections::VecDeque;
+
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{
+    catch::Caught,
+    elimination::Reinstate,
+    rules::{match_active, Ruleset},
+    stage::ActiveStage,
+    team::Team,
+    GameState,
+};
+
+pub struct JailPlugin;
+
+impl Plugin for JailPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Jails>()
+            .add_system_set(SystemSet::on_enter(GameState::Ready).with_system(empty_jails))
+            .add_system(release_on_catch.with_run_criteria(match_active));
+    }
+}
+
+/// The players each team has in jail, longest held first.
+#[derive(Default)]
+pub struct Jails(HashMap<Team, VecDeque<Entity>>);
+
+impl Jails {
+    /// Adds an eliminated player to the back of their team's jail. They are locked up by
+    /// [`sideline_eliminated`](crate::elimination), which has already dropped any ball they held.
+    pub fn lock_up(&mut self, team: Team, player: Entity) {
+        self.0.entry(team).or_default().push_back(player);
+    }
+
+    /// Lets out whoever on the team has been in jail longest.
+    pub fn release(&mut self, team: Team) -> Option<Entity> {
+        self.0.get_mut(&team)?.pop_front()
+    }
+
+    /// Takes a player out of jail wherever they are in line, for when they're brought back some
+    /// other way.
+    pub fn free(&mut self, player: Entity) {
+        for inmates in self.0.values_mut() {
+            inmates.retain(|inmate| *inmate != player);
+        }
+    }
+}
+
+fn empty_jails(mut jails: ResMut<Jails>) {
+    jails.0.clear();
+}
+
+/// Each catch frees one of the catcher's teammates, who goes back to their team's spawn point.
+fn release_on_catch(
+    ruleset: Res<Ruleset>,
+    stage: Option<Res<ActiveStage>>,
+    mut jails: ResMut<Jails>,
+    mut caught: EventReader<Caught>,
+    mut reinstate: EventWriter<Reinstate>,
+    teams: Query<&Team>,
+) {
+    for catch in caught.iter().filter(|_| ruleset.jail) {
+        let team = if let Ok(team) = teams.get(catch.player) {
+            *team
+        } else {
+            continue;
+        };
+        if let Some(player) = jails.release(team) {
+            info!("Player {player:?} is back from jail");
+            reinstate.send(Reinstate {
+                player,
+                position: stage
+                    .as_ref()
+                    .and_then(|stage| stage.spawns.get(team.0))
+                    .copied()
+                    .unwrap_or_default(),
+            });
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use bevy::{input::keyboard::KeyboardInput, prelude::*};
+use leafwing_input_manager::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{devices::PlayerSlot, player_input_map, profile::Profile, Action};
+
+/// Scan codes of the keys in the Q, W and A positions of a US keyboard, as reported on Windows
+/// and Linux. macOS numbers its keys differently, so the layout isn't guessed there.
+const Q_POSITION: u32 = 0x10;
+const W_POSITION: u32 = 0x11;
+const A_POSITION: u32 = 0x1e;
+
+pub struct KeyboardLayoutPlugin;
+
+impl Plugin for KeyboardLayoutPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(detect_keyboard_layout)
+            .add_system(apply_keyboard_layout.after(detect_keyboard_layout));
+    }
+}
+
+/// Keyboard layouts the default bindings are laid out for, so they land on the same physical
+/// keys whichever one is in use.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum KeyboardLayout {
+    #[default]
+    Qwerty,
+    Azerty,
+    Dvorak,
+}
+
+impl KeyboardLayout {
+    pub const ALL: [Self; 3] = [Self::Qwerty, Self::Azerty, Self::Dvorak];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Qwerty => "QWERTY",
+            Self::Azerty => "AZERTY",
+            Self::Dvorak => "Dvorak",
+        }
+    }
+
+    /// The default keyboard bindings: moving on the keys where WASD sits on a US keyboard, with
+    /// the other moves on the keys around them.
+    pub fn bindings(self) -> [(KeyCode, Action); 14] {
+        let [left, right, towards, away, throw, feint, catch, pickup, lob] = match self {
+            Self::Qwerty => [
+                KeyCode::A,
+                KeyCode::D,
+                KeyCode::S,
+                KeyCode::W,
+                KeyCode::E,
+                KeyCode::F,
+                KeyCode::R,
+                KeyCode::G,
+                KeyCode::T,
+            ],
+            Self::Azerty => [
+                KeyCode::Q,
+                KeyCode::D,
+                KeyCode::S,
+                KeyCode::Z,
+                KeyCode::E,
+                KeyCode::F,
+                KeyCode::R,
+                KeyCode::G,
+                KeyCode::T,
+            ],
+            Self::Dvorak => [
+                KeyCode::A,
+                KeyCode::E,
+                KeyCode::O,
+                KeyCode::Comma,
+                KeyCode::Period,
+                KeyCode::U,
+                KeyCode::P,
+                KeyCode::I,
+                KeyCode::Y,
+            ],
+        };
+        [
+            (left, Action::MoveLeft),
+            (right, Action::MoveRight),
+            (towards, Action::MoveTowards),
+            (away, Action::MoveAway),
+            (KeyCode::Space, Action::Jump),
+            (throw, Action::Throw),
+            (feint, Action::Feint),
+            (catch, Action::Catch),
+            (pickup, Action::Pickup),
+            (KeyCode::LShift, Action::Curve),
+            (lob, Action::Lob),
+            (KeyCode::F2, Action::Takeover),
+            (KeyCode::LControl, Action::Dodge),
+            (KeyCode::LAlt, Action::Sprint),
+        ]
+    }
+
+    /// Guesses the layout from the key a physical key press came out as, if it gives it away.
+    fn detect(event: &KeyboardInput) -> Option<Self> {
+        if cfg!(target_os = "macos") {
+            return None;
+        }
+        match (event.scan_code, event.key_code?) {
+            (Q_POSITION, KeyCode::Q) | (W_POSITION, KeyCode::W) => Some(Self::Qwerty),
+            (Q_POSITION, KeyCode::A) | (W_POSITION, KeyCode::Z) | (A_POSITION, KeyCode::Q) => {
+                Some(Self::Azerty)
+            }
+            (Q_POSITION, KeyCode::Apostrophe) | (W_POSITION, KeyCode::Comma) => Some(Self::Dvorak),
+            _ => None,
+        }
+    }
+}
+
+/// On first launch, works out the keyboard layout from the first telling key press and keeps
+/// it in the profile. It can be changed in the settings menu afterwards.
+fn detect_keyboard_layout(mut events: EventReader<KeyboardInput>, mut profile: ResMut<Profile>) {
+    if profile.keyboard_layout.is_some() {
+        return;
+    }
+    if let Some(layout) = events.iter().find_map(KeyboardLayout::detect) {
+        info!("Detected a {} keyboard", layout.name());
+        profile.keyboard_layout = Some(layout);
+        profile.save();
+    }
+}
+
+/// Lays the bindings of players already in a match out again when the layout changes, keeping
+/// their controllers.
+fn apply_keyboard_layout(
+    profile: Res<Profile>,
+    mut applied: Local<Option<KeyboardLayout>>,
+    mut players: Query<&mut InputMap<Action>, With<PlayerSlot>>,
+) {
+    let layout = profile.keyboard_layout.unwrap_or_default();
+    if *applied == Some(layout) {
+        return;
+    }
+    *applied = Some(layout);
+    for mut input_map in &mut players {
+        let gamepad = input_map.gamepad();
+        *input_map = player_input_map(layout);
+        if let Some(gamepad) = gamepad {
+            input_map.set_gamepad(gamepad);
+        }
+    }
+}
@@ -0,0 +1,153 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::*;
+
+use crate::{devices::PlayerSlot, ui::text::styled_text, Action};
+
+/// How many measurements the rolling average is taken over.
+const SAMPLE_COUNT: usize = 30;
+/// Presses that haven't moved the character after this long are given up on, since something
+/// other than latency (a wall, a stun) is holding it still.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(1);
+/// How far a character has to move to count as having visibly moved.
+const MOVE_THRESHOLD: f32 = 0.001;
+const READOUT_MARGIN: f32 = 16.0;
+const MOVE_ACTIONS: [Action; 4] = [
+    Action::MoveLeft,
+    Action::MoveRight,
+    Action::MoveAway,
+    Action::MoveTowards,
+];
+
+/// A dev tool that measures how long it takes from a movement press to the frame the character
+/// is drawn somewhere new. F9 toggles it.
+pub struct LatencyPlugin;
+
+impl Plugin for LatencyPlugin {
+    fn build(&self, app: &mut App) {
+        if cfg!(debug_assertions) {
+            app.init_resource::<LatencyProbe>()
+                .add_system(toggle_latency_probe)
+                .add_system(start_probe.after(toggle_latency_probe))
+                .add_system_to_stage(CoreStage::Last, finish_probe)
+                .add_system_to_stage(CoreStage::Last, show_latency.after(finish_probe));
+        }
+    }
+}
+
+#[derive(Default)]
+struct LatencyProbe {
+    enabled: bool,
+    /// The character being watched, where it was when the press came in, and when that was.
+    pending: Option<(Entity, Vec3, Instant)>,
+    samples: VecDeque<Duration>,
+}
+
+impl LatencyProbe {
+    fn average(&self) -> Option<Duration> {
+        let count = self.samples.len() as u32;
+        (count > 0).then(|| self.samples.iter().sum::<Duration>() / count)
+    }
+}
+
+#[derive(Component)]
+struct LatencyReadout;
+
+fn toggle_latency_probe(
+    mut commands: Commands,
+    keys: Res<Input<KeyCode>>,
+    mut probe: ResMut<LatencyProbe>,
+    readouts: Query<Entity, With<LatencyReadout>>,
+) {
+    if !keys.just_pressed(KeyCode::F9) {
+        return;
+    }
+    probe.enabled = !probe.enabled;
+    probe.pending = None;
+    probe.samples.clear();
+    if probe.enabled {
+        commands
+            .spawn_bundle(styled_text("Input latency: move to measure", "hud"))
+            .insert_bundle((
+                LatencyReadout,
+                Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        right: Val::Px(READOUT_MARGIN),
+                        top: Val::Px(READOUT_MARGIN),
+                        ..default()
+                    },
+                    ..default()
+                },
+            ));
+    } else {
+        for readout in &readouts {
+            commands.entity(readout).despawn_recursive();
+        }
+    }
+}
+
+fn start_probe(
+    time: Res<Time>,
+    mut probe: ResMut<LatencyProbe>,
+    players: Query<(Entity, &ActionState<Action>, &GlobalTransform), With<PlayerSlot>>,
+) {
+    if !probe.enabled || probe.pending.is_some() {
+        return;
+    }
+    // Input is read at the start of the frame, so that is the closest we get to when the press
+    // actually happened.
+    let pressed_at = time.last_update().unwrap_or_else(Instant::now);
+    probe.pending = players
+        .iter()
+        .find(|(_, action_state, _)| {
+            MOVE_ACTIONS
+                .iter()
+                .any(|action| action_state.just_pressed(*action))
+        })
+        .map(|(entity, _, transform)| (entity, transform.translation(), pressed_at));
+}
+
+/// Runs last thing in the frame, after transforms have been propagated for rendering.
+fn finish_probe(mut probe: ResMut<LatencyProbe>, transforms: Query<&GlobalTransform>) {
+    let (entity, from, pressed_at) = if let Some(pending) = probe.pending {
+        pending
+    } else {
+        return;
+    };
+    let elapsed = pressed_at.elapsed();
+    let moved = transforms.get(entity).map_or(false, |transform| {
+        transform.translation().distance(from) > MOVE_THRESHOLD
+    });
+    if moved {
+        if probe.samples.len() == SAMPLE_COUNT {
+            probe.samples.pop_front();
+        }
+        probe.samples.push_back(elapsed);
+        probe.pending = None;
+    } else if elapsed > PROBE_TIMEOUT {
+        probe.pending = None;
+    }
+}
+
+fn show_latency(probe: Res<LatencyProbe>, mut readouts: Query<&mut Text, With<LatencyReadout>>) {
+    if !probe.is_changed() {
+        return;
+    }
+    let average = if let Some(average) = probe.average() {
+        average
+    } else {
+        return;
+    };
+    for mut text in &mut readouts {
+        text.sections[0].value = format!(
+            "Input latency: {:.1} ms (last {})",
+            average.as_secs_f64() * 1000.0,
+            probe.samples.len()
+        );
+    }
+}
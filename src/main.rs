@@ -15,11 +15,11 @@
     clippy::enum_glob_use
 )]
 
-use std::f32::consts::PI;
-
 use bevy::{
+    asset::AssetServerSettings,
+    ecs::system::EntityCommands,
     prelude::*,
-    render::{camera::Projection, texture::ImageSettings},
+    render::texture::ImageSettings,
     window::{close_on_esc, PresentMode},
 };
 use bevy_asset_loader::prelude::*;
@@ -27,142 +27,354 @@ use bevy_rapier3d::prelude::*;
 use bevy_sprite3d::{AtlasSprite3d, Sprite3dParams, Sprite3dPlugin};
 use leafwing_input_manager::prelude::*;
 
+use ai::{AiAssets, Bot};
+use animation::AnimationMachine;
+use atlas::AtlasManifest;
+use ball_kinds::BallKindTable;
+use ball_spawner::BallSpawner;
+use challenge::ChallengeList;
+use character::{CharacterState, CharacterStats, GroundContact};
+use cleanup::DespawnOnExit;
+use devices::PlayerSlot;
+use graphics::{Billboard, SpriteDepth};
+use hit::{Health, Ricochets};
+use input_buffer::InputBuffer;
+use interactions::{BallKind, InteractionTable};
+use keyboard_layout::KeyboardLayout;
+use opening::gate_open;
+use particles::{EmitterAsset, ParticleEmitter};
+use profile::Profile;
+use rules::{match_active, MatchMode, Ruleset};
+use settings::Settings;
+use sound::AudioAssets;
+use sprite_animation::{ClipSet, SpriteAnimation};
+use stage::{Ground, StageAsset, StageAssets};
+use status::StatusEffects;
+use style_meter::StyleMeter;
+use team::{Team, TeamTint};
+use throw::ThrowRecovery;
+use ui::text::FontAssets;
+
+mod ai;
+mod animation;
+mod atlas;
+mod autosave;
+mod ball_kinds;
+mod ball_manager;
+mod ball_physics;
+mod ball_spawner;
+mod behavior;
+mod block;
+mod boss;
+mod camera;
+mod catch;
+mod celebration;
+mod challenge;
+mod character;
+mod clash;
+mod cleanup;
+mod curve;
+mod devices;
+mod dodge;
+mod elimination;
+mod game_time;
+mod graphics;
+mod hit;
+mod input;
+mod input_buffer;
+mod interactions;
+mod jail;
+mod keyboard_layout;
+mod latency;
+mod memory;
+#[cfg(feature = "observer")]
+mod observer;
+mod opening;
+mod out_of_bounds;
+mod particles;
+mod physics_lod;
+mod pickup;
+mod profile;
+mod respawn;
+mod ron_loader;
+mod rules;
+mod score;
+mod separation;
+mod sequence;
+mod settings;
+mod sound;
+mod speed_limit;
+mod sprint;
+mod sprite_animation;
+mod stage;
+mod stats;
+mod status;
+mod stress;
+mod stun;
+mod style_meter;
+mod takeover;
+mod target_practice;
+mod team;
+mod throw;
+mod tick_timer;
+mod training;
+mod trajectory;
+mod tween;
+mod ui;
+
 pub const CLEAR: Color = Color::BLACK;
 pub const HEIGHT: f32 = 600.0;
 pub const RESOLUTION: f32 = 16.0 / 9.0;
+const BOT_SKILL: f32 = 0.5;
+const BALL_DROP_HEIGHT: f32 = 1.0;
+/// The default pixel density of `bevy_sprite3d` sprites.
+const SPRITE_PIXELS_PER_METRE: f32 = 100.0;
+/// How strongly character sprites take on their team's color.
+const SPRITE_TEAM_TINT: f32 = 0.35;
 
 fn main() {
-    App::new()
-        .add_loading_state(
-            LoadingState::new(GameState::Loading)
-                .continue_to_state(GameState::Ready)
-                .with_collection::<ImageAssets>(),
-        )
-        .insert_resource(ImageSettings::default_nearest())
-        .insert_resource(ClearColor(CLEAR))
-        .insert_resource(WindowDescriptor {
-            width: HEIGHT * RESOLUTION,
-            height: HEIGHT,
-            title: "Bevy Template".to_string(),
-            present_mode: PresentMode::Fifo,
-            resizable: false,
-            position: WindowPosition::Centered(MonitorSelection::Number(0)),
-            ..Default::default()
-        })
-        .add_state(GameState::Loading)
-        // External plugins
-        .add_plugins(DefaultPlugins)
-        .add_plugin(Sprite3dPlugin)
-        .insert_resource(RapierConfiguration {
-            gravity: Vect::Z * -9.81,
-            ..default()
-        })
-        .add_plugin(RapierPhysicsPlugin::<NoUserData>::default())
-        .add_plugin(RapierDebugRenderPlugin::default())
-        .add_system(close_on_esc)
-        .add_plugin(InputManagerPlugin::<Action>::default())
-        // Internal plugins
-        .add_system_set(
-            SystemSet::on_enter(GameState::Ready)
-                .with_system(spawn_camera)
-                .with_system(spawn_stage)
-                .with_system(spawn_character)
-                .with_system(initial_spawn_ball),
-        )
-        .add_system_set(
-            SystemSet::on_update(GameState::Ready)
-                .with_system(player_control)
-                .with_system(character_state)
-                .with_system(take_ball),
-        )
-        .run();
-}
-
-fn spawn_camera(mut commands: Commands) {
-    let mut camera = Camera3dBundle {
-        projection: Projection::Perspective(PerspectiveProjection {
-            fov: PI / 6.0,
-            ..default()
-        }),
+    let mut app = App::new();
+    app.add_loading_state(
+        LoadingState::new(GameState::Loading)
+            .continue_to_state(GameState::Ready)
+            .with_collection::<ImageAssets>()
+            .with_collection::<FontAssets>()
+            .with_collection::<AudioAssets>()
+            .with_collection::<StageAssets>()
+            .with_collection::<DataAssets>()
+            .with_collection::<AiAssets>(),
+    )
+    .insert_resource(AssetServerSettings {
+        watch_for_changes: true,
         ..default()
-    };
-
-    camera.transform.translation = Vec3::new(10.0, 0.0, 5.0);
-    camera.transform.look_at(Vec3::ZERO, Vec3::NEG_X);
-
-    commands.spawn_bundle(camera);
-}
-
-#[derive(Component)]
-struct Ground;
-
-fn spawn_stage(
-    mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
-) {
-    let mut mat: StandardMaterial = Color::PURPLE.into();
-    mat.unlit = true;
-    commands
-        .spawn_bundle(PbrBundle {
-            mesh: meshes.add(
-                shape::Quad {
-                    size: Vec2 { x: 5.0, y: 30.0 },
-                    flip: false,
-                }
-                .into(),
-            ),
-            material: materials.add(mat),
-            ..default()
-        })
-        .insert_bundle((Collider::cuboid(2.5, 15.0, 0.01), RigidBody::Fixed, Ground));
+    })
+    .insert_resource(ImageSettings::default_nearest())
+    .insert_resource(ClearColor(CLEAR))
+    .insert_resource(WindowDescriptor {
+        width: HEIGHT * RESOLUTION,
+        height: HEIGHT,
+        title: "Bevy Template".to_string(),
+        present_mode: PresentMode::Fifo,
+        resizable: false,
+        position: WindowPosition::Centered(MonitorSelection::Number(0)),
+        ..Default::default()
+    })
+    .add_state(GameState::Loading)
+    // External plugins
+    .add_plugins(DefaultPlugins)
+    .add_plugin(Sprite3dPlugin)
+    .insert_resource(RapierConfiguration {
+        gravity: Vect::Z * -9.81,
+        ..default()
+    })
+    .add_plugin(RapierPhysicsPlugin::<NoUserData>::default())
+    .add_plugin(RapierDebugRenderPlugin::default())
+    .add_system(close_on_esc)
+    .add_plugin(InputManagerPlugin::<Action>::default())
+    // Internal plugins
+    .add_plugin(settings::SettingsPlugin)
+    .add_plugin(profile::ProfilePlugin)
+    .add_plugin(cleanup::CleanupPlugin)
+    .add_plugin(game_time::GameTimePlugin)
+    .add_plugin(sequence::SequencePlugin)
+    .add_plugin(tween::TweenPlugin)
+    .add_plugin(camera::CameraPlugin)
+    .add_plugin(stage::StagePlugin)
+    .add_plugin(character::CharacterPlugin)
+    .add_plugin(status::StatusPlugin)
+    .add_plugin(team::TeamPlugin)
+    .add_plugin(rules::RulesPlugin)
+    .add_plugin(interactions::InteractionPlugin)
+    .add_plugin(ball_kinds::BallKindsPlugin)
+    .add_plugin(ball_physics::BallPhysicsPlugin)
+    .add_plugin(separation::SeparationPlugin)
+    .add_plugin(hit::HitPlugin)
+    .add_plugin(clash::ClashPlugin)
+    .add_plugin(catch::CatchPlugin)
+    .add_plugin(block::BlockPlugin)
+    .add_plugin(throw::ThrowPlugin)
+    .add_plugin(trajectory::TrajectoryPlugin)
+    .add_plugin(pickup::PickupPlugin)
+    .add_plugin(dodge::DodgePlugin)
+    .add_plugin(elimination::EliminationPlugin)
+    .add_plugin(jail::JailPlugin)
+    .add_plugin(respawn::RespawnPlugin)
+    .add_plugin(sprint::SprintPlugin)
+    .add_plugin(curve::CurvePlugin)
+    .add_plugin(training::TrainingPlugin)
+    .add_plugin(autosave::AutosavePlugin)
+    .add_plugin(challenge::ChallengePlugin)
+    .add_plugin(target_practice::TargetPracticePlugin)
+    .add_plugin(opening::OpeningPlugin)
+    .add_plugin(out_of_bounds::OutOfBoundsPlugin)
+    .add_plugin(ball_manager::BallManagerPlugin)
+    .add_plugin(stun::StunPlugin)
+    .add_plugin(score::ScorePlugin)
+    .add_plugin(stats::StatsPlugin)
+    .add_plugin(ai::AiPlugin)
+    .add_plugin(takeover::TakeoverPlugin)
+    .add_plugin(boss::BossPlugin)
+    .add_plugin(style_meter::StyleMeterPlugin)
+    .add_plugin(celebration::CelebrationPlugin)
+    .add_plugin(graphics::GraphicsPlugin)
+    .add_plugin(atlas::AtlasPlugin)
+    .add_plugin(sprite_animation::SpriteAnimationPlugin)
+    .add_plugin(animation::CharacterAnimationPlugin)
+    .add_plugin(particles::ParticlePlugin)
+    .add_plugin(physics_lod::PhysicsLodPlugin)
+    .add_plugin(speed_limit::SpeedLimitPlugin)
+    .add_plugin(sound::SoundPlugin)
+    .add_plugin(ui::UiPlugin)
+    .add_plugin(input::InputContextPlugin)
+    .add_plugin(keyboard_layout::KeyboardLayoutPlugin)
+    .add_plugin(input_buffer::InputBufferPlugin)
+    .add_plugin(devices::DevicePlugin)
+    .add_plugin(latency::LatencyPlugin)
+    .add_plugin(memory::MemoryPlugin)
+    .add_plugin(stress::StressPlugin)
+    .add_system_set(
+        SystemSet::on_enter(GameState::Ready)
+            .with_system(spawn_character)
+            .with_system(spawn_balls),
+    )
+    .add_system_set(
+        SystemSet::new()
+            .with_run_criteria(gate_open)
+            .with_system(player_control),
+    )
+    .add_system_set(
+        SystemSet::new()
+            .with_run_criteria(match_active)
+            .with_system(ball_impact_dust)
+            .with_system(carry_held_balls),
+    );
+    #[cfg(feature = "observer")]
+    app.add_plugin(observer::ObserverPlugin);
+    app.run();
 }
 
 fn spawn_character(
     mut commands: Commands,
     images: Res<ImageAssets>,
+    stages: Res<StageAssets>,
+    stage_assets: Res<Assets<StageAsset>>,
+    ruleset: Res<Ruleset>,
+    profile: Res<Profile>,
     mut sprite_params: Sprite3dParams,
 ) {
-    let mut transform = Transform::from_translation(Vec3::new(0.0, -4.0, 0.25))
-        .with_rotation(Quat::from_axis_angle(Vec3::Z, PI * 0.5));
-    transform.rotate(Quat::from_axis_angle(Vec3::Y, PI * 0.5));
-    commands
-        .spawn_bundle(
-            AtlasSprite3d {
-                atlas: images.character_sprite.clone(),
-                partial_alpha: true,
-                transform,
-                unlit: true,
-                pivot: Some(Vec2::new(0.7, 0.5)),
-                ..default()
-            }
-            .bundle(&mut sprite_params),
-        )
-        .insert_bundle(InputManagerBundle::<Action> {
-            input_map: InputMap::new([
-                (KeyCode::A, Action::MoveLeft),
-                (KeyCode::E, Action::MoveRight),
-                (KeyCode::O, Action::MoveTowards),
-                (KeyCode::Comma, Action::MoveAway),
-                (KeyCode::Space, Action::Jump),
-                (KeyCode::Period, Action::Throw),
-            ]),
-            ..default()
+    let stage = if let Some(stage) = stage_assets.get(&stages.court) {
+        stage
+    } else {
+        return;
+    };
+    let spawn = |team| {
+        if ruleset.opening_rush {
+            stage.rush_spawn(team)
+        } else {
+            stage.spawn(team)
+        }
+    };
+    spawn_player(
+        &mut commands,
+        &images,
+        &mut sprite_params,
+        spawn(0).extend(0.25),
+        Team(0),
+        1.0,
+    )
+    .insert_bundle(InputManagerBundle::<Action> {
+        input_map: player_input_map(profile.keyboard_layout.unwrap_or_default()),
+        ..default()
+    })
+    .insert_bundle((
+        PlayerSlot(0),
+        PlayerName("Player 1".to_string()),
+        Health::new(ruleset.lives),
+    ));
+    // Target practice is played alone.
+    if ruleset.mode == MatchMode::TargetPractice {
+        return;
+    }
+    spawn_player(
+        &mut commands,
+        &images,
+        &mut sprite_params,
+        spawn(1).extend(0.25),
+        Team(1),
+        1.0,
+    )
+    .insert_bundle((
+        ActionState::<Action>::default(),
+        Bot::new(BOT_SKILL),
+        Health::new(ruleset.lives),
+    ));
+}
+
+fn spawn_player<'w, 's, 'a>(
+    commands: &'a mut Commands<'w, 's>,
+    images: &ImageAssets,
+    sprite_params: &mut Sprite3dParams,
+    position: Vec3,
+    team: Team,
+    scale: f32,
+) -> EntityCommands<'w, 's, 'a> {
+    // Everyone starts out facing the other half of the court.
+    let facing = Facing(Vec2::new(0.0, if team.0 == 0 { 1.0 } else { -1.0 }));
+    let mut player = commands.spawn_bundle(TransformBundle::from_transform(
+        Transform::from_translation(position),
+    ));
+    player
+        .insert_bundle(VisibilityBundle::default())
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(
+                    AtlasSprite3d {
+                        atlas: images.character_sprite.clone(),
+                        partial_alpha: true,
+                        unlit: true,
+                        pivot: Some(Vec2::new(0.7, 0.5)),
+                        pixels_per_metre: SPRITE_PIXELS_PER_METRE / scale,
+                        ..default()
+                    }
+                    .bundle(sprite_params),
+                )
+                .insert_bundle((
+                    Billboard::Vertical,
+                    SpriteDepth,
+                    SpriteAnimation::new(images.character_clips.clone()),
+                    TeamTint {
+                        team,
+                        strength: SPRITE_TEAM_TINT,
+                    },
+                ));
         })
         .insert_bundle((
-            Collider::cuboid(0.25, 0.25, 0.25),
+            Collider::cuboid(0.25 * scale, 0.25 * scale, 0.25 * scale),
             RigidBody::Dynamic,
             LockedAxes::ROTATION_LOCKED,
             Velocity::default(),
             ExternalImpulse::default(),
             ActiveEvents::COLLISION_EVENTS,
             Player,
-            CharacterState::Grounded,
+            CharacterState::Idle,
+            GroundContact(true),
+            facing,
+            team,
+            StatusEffects::default(),
+            StyleMeter::default(),
+            DespawnOnExit(GameState::Ready),
+        ))
+        .insert_bundle(separation::player_contacts())
+        .insert_bundle((
+            CharacterStats::default(),
+            InputBuffer::default(),
+            AnimationMachine::default(),
         ));
+    player
 }
 
-#[derive(Clone, Debug, Eq, Hash, PartialEq)]
-enum GameState {
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum GameState {
     Loading,
     Ready,
 }
@@ -172,10 +384,42 @@ struct ImageAssets {
     #[asset(texture_atlas(tile_size_x = 64., tile_size_y = 64., columns = 14, rows = 7,))]
     #[asset(path = "player/PlayerSharky(64x64).png")]
     character_sprite: Handle<TextureAtlas>,
+    #[asset(path = "player/sharky.clips.ron")]
+    character_clips: Handle<ClipSet>,
 }
 
+/// Effects and other data files used mid-match, loaded up front so first use doesn't hitch.
+#[derive(AssetCollection)]
+pub struct DataAssets {
+    #[asset(path = "effects/dust.emitter.ron")]
+    pub dust: Handle<EmitterAsset>,
+    #[asset(path = "effects/confetti.emitter.ron")]
+    pub confetti: Handle<EmitterAsset>,
+    #[asset(path = "effects/clash.emitter.ron")]
+    pub clash: Handle<EmitterAsset>,
+    /// Sprites for particle effects, referred to by name from the emitters.
+    #[asset(path = "effects/effects.atlas.ron")]
+    pub effects_atlas: Handle<AtlasManifest>,
+    #[asset(path = "rules/default.interactions.ron")]
+    pub interactions: Handle<InteractionTable>,
+    #[asset(path = "rules/default.challenges.ron")]
+    pub challenges: Handle<ChallengeList>,
+    #[asset(path = "rules/default.balls.ron")]
+    pub ball_kinds: Handle<BallKindTable>,
+    #[asset(path = "player/sharky.stats.ron")]
+    pub character_stats: Handle<CharacterStats>,
+}
+
+#[derive(Component)]
+pub struct Player;
+
+/// What a player is called in the HUD and menus.
 #[derive(Component)]
-struct Player;
+pub struct PlayerName(pub String);
+
+/// The direction a player last moved in, which is where they aim their throws.
+#[derive(Component, Clone, Copy)]
+pub struct Facing(pub Vec2);
 
 #[allow(clippy::enum_variant_names)]
 #[derive(Actionlike, Clone, Copy)]
@@ -186,113 +430,155 @@ enum Action {
     MoveTowards,
     Jump,
     Throw,
+    Feint,
+    Catch,
+    Pickup,
+    /// Held while throwing to bend the throw.
+    Curve,
+    /// Throws in a high arc over the heads of the other team.
+    Lob,
+    /// Hands the character to a bot, or takes it back.
+    Takeover,
+    /// Analog movement from a stick, shaped by the player's stick settings.
+    Move,
+    /// Rolls the way the player is moving, briefly untouchable.
+    Dodge,
+    /// A short burst of speed, with a cooldown.
+    Sprint,
+}
+
+fn player_input_map(layout: KeyboardLayout) -> InputMap<Action> {
+    let mut input_map = InputMap::new(layout.bindings());
+    input_map.insert_multiple([
+        (GamepadButtonType::DPadLeft, Action::MoveLeft),
+        (GamepadButtonType::DPadRight, Action::MoveRight),
+        (GamepadButtonType::DPadDown, Action::MoveTowards),
+        (GamepadButtonType::DPadUp, Action::MoveAway),
+        (GamepadButtonType::South, Action::Jump),
+        (GamepadButtonType::West, Action::Throw),
+        (GamepadButtonType::North, Action::Feint),
+        (GamepadButtonType::East, Action::Catch),
+        (GamepadButtonType::RightTrigger, Action::Pickup),
+        (GamepadButtonType::LeftTrigger, Action::Curve),
+        (GamepadButtonType::RightTrigger2, Action::Lob),
+        (GamepadButtonType::LeftThumb, Action::Takeover),
+        (GamepadButtonType::LeftTrigger2, Action::Dodge),
+        (GamepadButtonType::RightThumb, Action::Sprint),
+    ]);
+    // No deadzone here, since each player's own deadzone is applied in `player_control`.
+    input_map.insert(
+        DualAxis::symmetric(
+            GamepadAxisType::LeftStickX,
+            GamepadAxisType::LeftStickY,
+            0.0,
+        ),
+        Action::Move,
+    );
+    input_map
 }
 
 fn player_control(
-    mut commands: Commands,
-    mut player: Query<
+    mut players: Query<
         (
-            Entity,
             &mut Velocity,
             &mut ExternalImpulse,
             &ActionState<Action>,
             &mut CharacterState,
-            Option<&HasBall>,
-            &GlobalTransform,
+            &StatusEffects,
+            &mut Facing,
+            &CharacterStats,
+            Option<&ThrowRecovery>,
+            Option<&PlayerSlot>,
         ),
         With<Player>,
     >,
-    mut meshes: ResMut<Assets<Mesh>>,
+    settings: Res<Settings>,
 ) {
-    let (entity, mut velocity, mut impulse, action_state, mut character_state, has_ball, transform) =
-        player.single_mut();
-    let mut movement = Vec2::default();
-    for action in action_state.get_pressed() {
-        match action {
-            Action::MoveLeft => movement.y = -1.0,
-            Action::MoveRight => movement.y = 1.0,
-            Action::MoveAway => movement.x = -1.0,
-            Action::MoveTowards => movement.x = 1.0,
-            Action::Jump => {
-                if matches!(*character_state, CharacterState::Grounded) {
-                    impulse.impulse = Vec3::new(0.0, 0.0, 0.7);
-                    *character_state = CharacterState::InAir;
+    for (
+        mut velocity,
+        mut impulse,
+        action_state,
+        mut character_state,
+        effects,
+        mut facing,
+        stats,
+        recovery,
+        slot,
+    ) in &mut players
+    {
+        if !character_state.can_move() {
+            continue;
+        }
+        let mut movement = Vec2::default();
+        for action in action_state.get_pressed() {
+            match action {
+                Action::MoveLeft => movement.y = -1.0,
+                Action::MoveRight => movement.y = 1.0,
+                Action::MoveAway => movement.x = -1.0,
+                Action::MoveTowards => movement.x = 1.0,
+                Action::Jump => {
+                    if character_state.is_grounded() && recovery.is_none() {
+                        impulse.impulse = Vec3::new(0.0, 0.0, 0.7);
+                        *character_state = CharacterState::Jump;
+                    }
                 }
+                // Throwing, catching, dodging and handing over to a bot are handled by their own
+                // modules, and the stick is read below.
+                Action::Throw
+                | Action::Feint
+                | Action::Catch
+                | Action::Pickup
+                | Action::Curve
+                | Action::Lob
+                | Action::Takeover
+                | Action::Move
+                | Action::Dodge
+                | Action::Sprint => {}
             }
-            Action::Throw => {
-                if has_ball.is_some() {
-                    let ball = spawn_ball(
-                        transform.translation() + Vec3::new(0.0, 0.4, 0.1),
-                        &mut commands,
-                        &mut meshes,
-                    );
-                    commands.entity(ball).insert(Velocity {
-                        linvel: Vec3::new(0.0, 10.0, 4.0),
-                        ..default()
-                    });
-                    commands.entity(entity).remove::<HasBall>();
-                }
+        }
+        // The stick only steers when the digital directions aren't held. Pushing it up moves
+        // away, like the d-pad.
+        if movement == Vec2::ZERO && action_state.pressed(Action::Move) {
+            if let Some(axis) = action_state.axis_pair(Action::Move) {
+                let stick = settings
+                    .stick(slot.map_or(0, |slot| slot.0))
+                    .apply(axis.xy());
+                movement = Vec2::new(-stick.y, stick.x);
             }
         }
+        if movement != Vec2::ZERO {
+            facing.0 = movement.normalize();
+        }
+        let mut speed = stats.run_speed * effects.speed_multiplier();
+        if recovery.is_some() {
+            speed *= stats.recovery_speed;
+        }
+        velocity.linvel = (movement.clamp_length_max(1.0) * speed).extend(velocity.linvel.z);
     }
-    velocity.linvel = (movement.normalize_or_zero() * 10.0).extend(velocity.linvel.z);
 }
 
-#[derive(Component, Clone, Copy)]
-enum CharacterState {
-    Grounded,
-    InAir,
-}
-
-fn character_state(
-    mut events: EventReader<CollisionEvent>,
-    mut characters: Query<&mut CharacterState>,
-    ground: Query<(), With<Ground>>,
+/// Drops the match's balls onto the court, placed fairly by the ruleset's spawn pattern.
+fn spawn_balls(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    ruleset: Res<Ruleset>,
+    stages: Res<StageAssets>,
+    stage_assets: Res<Assets<StageAsset>>,
 ) {
-    for event in events.iter() {
-        match event {
-            CollisionEvent::Started(e1, e2, _) => {
-                let mut character = if let Ok(character) = characters.get_mut(*e1) {
-                    if ground.contains(*e2) {
-                        character
-                    } else {
-                        continue;
-                    }
-                } else if let Ok(character) = characters.get_mut(*e2) {
-                    if ground.contains(*e1) {
-                        character
-                    } else {
-                        continue;
-                    }
-                } else {
-                    continue;
-                };
-                *character = CharacterState::Grounded;
-            }
-            CollisionEvent::Stopped(e1, e2, _) => {
-                let mut character = if let Ok(character) = characters.get_mut(*e1) {
-                    if ground.contains(*e2) {
-                        character
-                    } else {
-                        continue;
-                    }
-                } else if let Ok(character) = characters.get_mut(*e2) {
-                    if ground.contains(*e1) {
-                        character
-                    } else {
-                        continue;
-                    }
-                } else {
-                    continue;
-                };
-                *character = CharacterState::InAir;
-            }
-        }
+    let court = if let Some(stage) = stage_assets.get(&stages.court) {
+        Vec2::from(stage.size)
+    } else {
+        return;
+    };
+    let mut spawner = BallSpawner::new(&ruleset);
+    for position in spawner.positions(ruleset.ball_count, court) {
+        spawn_ball(
+            position.extend(BALL_DROP_HEIGHT),
+            &mut commands,
+            &mut meshes,
+        );
     }
-}
-
-fn initial_spawn_ball(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>) {
-    spawn_ball(Vec3::new(0.0, 0.0, 1.0), &mut commands, &mut meshes);
+    commands.insert_resource(spawner);
 }
 
 fn spawn_ball(
@@ -315,48 +601,133 @@ fn spawn_ball(
         .insert_bundle((
             Collider::ball(0.1),
             RigidBody::Dynamic,
-            Restitution {
-                coefficient: 0.8,
-                combine_rule: CoefficientCombineRule::Max,
-            },
+            Velocity::default(),
+            ActiveEvents::COLLISION_EVENTS,
+            // Charged throws and rallies are fast enough to pass through thin obstacles.
+            Ccd::enabled(),
             Ball,
+            BallKind::default(),
+            BallState::Dead,
+            Ricochets::default(),
+            DespawnOnExit(GameState::Ready),
         ))
         .id()
 }
 
 #[derive(Component)]
-struct Ball;
+pub struct Ball;
+
+/// Whether a ball is out to hit someone, free to pick up or in someone's hands.
+///
+/// Balls go from [`Dead`](BallState::Dead) to [`Held`](BallState::Held) when picked up or
+/// caught, from [`Held`](BallState::Held) to [`Live`](BallState::Live) when thrown, or back to
+/// [`Dead`](BallState::Dead) when dropped, and from [`Live`](BallState::Live) to
+/// [`Dead`](BallState::Dead) once they stop being a threat.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BallState {
+    /// Thrown or deflected, and eliminates whoever it strikes.
+    Live,
+    /// Has bounced, struck someone or slowed down, and is free for either team to pick up.
+    Dead,
+    /// In a player's hands, hidden and out of physics until it's thrown or dropped.
+    Held,
+}
 
-fn take_ball(
+/// The player who last threw or deflected a ball.
+#[derive(Component)]
+pub struct ThrownBy(pub Entity);
+
+/// The player holding a [`BallState::Held`] ball, which is carried along with them.
+#[derive(Component)]
+pub struct HeldBy(pub Entity);
+
+/// Marks a character holding a ball, which nobody else can take from them.
+#[derive(Component)]
+pub struct HoldingBall {
+    pub ball: Entity,
+    pub kind: BallKind,
+}
+
+/// Puts a ball in a player's hands. The ball keeps its entity, and with it its kind and history,
+/// but is hidden and taken out of physics until it's let go of.
+fn hold_ball(player: Entity, ball: Entity, kind: BallKind, commands: &mut Commands) {
+    commands.entity(player).insert(HoldingBall { ball, kind });
+    commands.entity(ball).insert_bundle((
+        BallState::Held,
+        HeldBy(player),
+        RigidBody::Fixed,
+        CollisionGroups::new(0, 0),
+        Velocity::zero(),
+        Visibility { is_visible: false },
+    ));
+}
+
+/// Lets go of the ball a player is holding at `position`, putting it back into physics. The
+/// caller sets the state it leaves their hands in.
+fn release_held_ball(
+    player: Entity,
+    held: &HoldingBall,
+    position: Vec3,
+    commands: &mut Commands,
+) -> Entity {
+    let ball = held.ball;
+    commands.entity(player).remove::<HoldingBall>();
+    commands
+        .entity(ball)
+        .remove::<HeldBy>()
+        .remove::<CollisionGroups>()
+        .insert_bundle((RigidBody::Dynamic, Visibility { is_visible: true }));
+    // Only the position changes, so the ball keeps the scale its kind gave it.
+    commands.add(move |world: &mut World| {
+        if let Some(mut transform) = world.get_mut::<Transform>(ball) {
+            transform.translation = position;
+        }
+    });
+    ball
+}
+
+/// Drops the ball a player is holding where they stand, as a dead ball for anyone to pick up.
+fn drop_held_ball(player: Entity, held: &HoldingBall, position: Vec3, commands: &mut Commands) {
+    let ball = release_held_ball(player, held, position, commands);
+    commands.entity(ball).insert(BallState::Dead);
+}
+
+/// Keeps held balls with whoever is holding them, so they're let go of in the right place.
+fn carry_held_balls(
+    holders: Query<&GlobalTransform>,
+    mut balls: Query<(&HeldBy, &mut Transform), With<Ball>>,
+) {
+    for (HeldBy(holder), mut transform) in &mut balls {
+        if let Ok(holder) = holders.get(*holder) {
+            transform.translation = holder.translation();
+        }
+    }
+}
+
+fn ball_impact_dust(
     mut commands: Commands,
     mut events: EventReader<CollisionEvent>,
-    characters: Query<(), With<CharacterState>>,
-    balls: Query<(), With<Ball>>,
+    data: Res<DataAssets>,
+    balls: Query<&GlobalTransform, With<Ball>>,
+    ground: Query<(), With<Ground>>,
 ) {
     for event in events.iter() {
         if let CollisionEvent::Started(e1, e2, _) = event {
-            let (character, ball) = if characters.contains(*e1) {
-                if balls.contains(*e2) {
-                    (e1, e2)
-                } else {
-                    continue;
-                }
-            } else if characters.contains(*e2) {
-                if balls.contains(*e1) {
-                    (e2, e1)
-                } else {
-                    continue;
-                }
+            let ball = if ground.contains(*e2) {
+                balls.get(*e1)
+            } else if ground.contains(*e1) {
+                balls.get(*e2)
             } else {
                 continue;
             };
-
-            info!("Character {character:?} has picked up ball {ball:?}");
-            commands.entity(*character).insert(HasBall);
-            commands.entity(*ball).despawn_recursive();
+            if let Ok(ball) = ball {
+                commands
+                    .spawn_bundle(SpatialBundle {
+                        transform: Transform::from_translation(ball.translation()),
+                        ..default()
+                    })
+                    .insert(ParticleEmitter::new(data.dust.clone()));
+            }
         }
     }
 }
-
-#[derive(Component)]
-struct HasBall;
@@ -0,0 +1,100 @@
+use bevy::{
+    diagnostic::{Diagnostic, DiagnosticId, Diagnostics},
+    prelude::*,
+};
+
+use crate::ui::text::styled_text;
+
+const READOUT_MARGIN: f32 = 16.0;
+
+/// Bytes of texture data held by loaded images.
+pub const IMAGE_BYTES_DIAGNOSTIC: DiagnosticId =
+    DiagnosticId::from_u128(0x2c71_e8a4_93f0_4d5b_b618_0ae9_57c3_d284);
+pub const MESH_COUNT_DIAGNOSTIC: DiagnosticId =
+    DiagnosticId::from_u128(0x6e05_b3d9_1a47_4c82_8f3e_c92d_04b1_7a65);
+
+/// Tracks how much asset memory is in use, for keeping an eye on the WASM build's tighter limits.
+/// F7 toggles a readout of it in debug builds.
+pub struct MemoryPlugin;
+
+impl Plugin for MemoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(setup_diagnostics)
+            .add_system(measure_memory);
+        if cfg!(debug_assertions) {
+            app.add_system(toggle_memory_readout)
+                .add_system(show_memory.after(measure_memory));
+        }
+    }
+}
+
+#[derive(Component)]
+struct MemoryReadout;
+
+fn setup_diagnostics(mut diagnostics: ResMut<Diagnostics>) {
+    diagnostics.add(Diagnostic::new(IMAGE_BYTES_DIAGNOSTIC, "image_bytes", 1));
+    diagnostics.add(Diagnostic::new(MESH_COUNT_DIAGNOSTIC, "meshes", 1));
+}
+
+fn measure_memory(
+    mut diagnostics: ResMut<Diagnostics>,
+    images: Res<Assets<Image>>,
+    meshes: Res<Assets<Mesh>>,
+) {
+    if images.is_changed() {
+        let bytes = images
+            .iter()
+            .map(|(_, image)| image.data.len())
+            .sum::<usize>();
+        diagnostics.add_measurement(IMAGE_BYTES_DIAGNOSTIC, || bytes as f64);
+    }
+    if meshes.is_changed() {
+        diagnostics.add_measurement(MESH_COUNT_DIAGNOSTIC, || meshes.len() as f64);
+    }
+}
+
+fn toggle_memory_readout(
+    mut commands: Commands,
+    keys: Res<Input<KeyCode>>,
+    readouts: Query<Entity, With<MemoryReadout>>,
+) {
+    if !keys.just_pressed(KeyCode::F7) {
+        return;
+    }
+    if readouts.is_empty() {
+        commands
+            .spawn_bundle(styled_text("", "hud"))
+            .insert_bundle((
+                MemoryReadout,
+                Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        left: Val::Px(READOUT_MARGIN),
+                        bottom: Val::Px(READOUT_MARGIN),
+                        ..default()
+                    },
+                    ..default()
+                },
+            ));
+    } else {
+        for readout in &readouts {
+            commands.entity(readout).despawn_recursive();
+        }
+    }
+}
+
+fn show_memory(diagnostics: Res<Diagnostics>, mut readouts: Query<&mut Text, With<MemoryReadout>>) {
+    let latest = |id| {
+        diagnostics
+            .get(id)
+            .and_then(Diagnostic::value)
+            .unwrap_or_default()
+    };
+    for mut text in &mut readouts {
+        text.sections[0].value = format!(
+            "Images: {:.1} MiB  Meshes: {:.0}",
+            latest(IMAGE_BYTES_DIAGNOSTIC) / (1024.0 * 1024.0),
+            latest(MESH_COUNT_DIAGNOSTIC)
+        );
+    }
+}
@@ -0,0 +1,162 @@
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream},
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+use bevy::prelude::*;
+use serde::Serialize;
+
+use crate::{
+    hit::Health,
+    rules::MatchActive,
+    score::Score,
+    stats::MatchStats,
+    team::{Team, TeamColors},
+    Player, PlayerName,
+};
+
+/// Where overlays can fetch the match state from, as `http://127.0.0.1:7878/state`.
+const OBSERVER_ADDRESS: &str = "127.0.0.1:7878";
+/// How long a client gets to send its request or take the reply before it's dropped. Requests
+/// are served one at a time, so a client that stalls holds up every other overlay meanwhile.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Serves live match state as JSON on a local port, so stream overlays can show it.
+pub struct ObserverPlugin;
+
+impl Plugin for ObserverPlugin {
+    fn build(&self, app: &mut App) {
+        let snapshot = Snapshot::default();
+        match TcpListener::bind(OBSERVER_ADDRESS) {
+            Ok(listener) => {
+                info!("Serving match state on http://{OBSERVER_ADDRESS}/state");
+                let served = snapshot.clone();
+                thread::spawn(move || serve(&listener, &served));
+            }
+            Err(error) => warn!("Couldn't serve match state on {OBSERVER_ADDRESS}: {error}"),
+        }
+        app.insert_resource(snapshot)
+            .add_system(publish_match_state);
+    }
+}
+
+/// The latest match state, shared with the server thread.
+#[derive(Clone, Default)]
+struct Snapshot(Arc<Mutex<String>>);
+
+#[derive(Serialize)]
+struct MatchState {
+    active: bool,
+    teams: Vec<TeamState>,
+    players: Vec<PlayerState>,
+}
+
+#[derive(Serialize)]
+struct TeamState {
+    score: u32,
+    /// The team's color as a CSS hex string.
+    color: String,
+}
+
+#[derive(Serialize)]
+struct PlayerState {
+    name: Option<String>,
+    team: usize,
+    lives: Option<u32>,
+    hits: u32,
+    catches: u32,
+}
+
+/// Rebuilds the snapshot whenever something the HUD shows has changed.
+fn publish_match_state(
+    snapshot: Res<Snapshot>,
+    mut was_active: Local<bool>,
+    active: Option<Res<MatchActive>>,
+    score: Res<Score>,
+    stats: Res<MatchStats>,
+    team_colors: Res<TeamColors>,
+    players: Query<(Entity, &Team, Option<&PlayerName>, Option<&Health>), With<Player>>,
+    changed: Query<(), Or<(Changed<Team>, Changed<PlayerName>, Changed<Health>)>>,
+) {
+    let active = active.is_some();
+    if active == *was_active
+        && !score.is_changed()
+        && !stats.is_changed()
+        && !team_colors.is_changed()
+        && changed.is_empty()
+    {
+        return;
+    }
+    *was_active = active;
+
+    let mut players = players.iter().collect::<Vec<_>>();
+    players.sort_by_key(|(entity, team, ..)| (team.0, *entity));
+    let team_count = players
+        .iter()
+        .map(|(_, team, ..)| team.0 + 1)
+        .max()
+        .unwrap_or_default();
+    let state = MatchState {
+        active,
+        teams: (0..team_count)
+            .map(Team)
+            .map(|team| {
+                let [r, g, b, _] = team_colors.get(team).as_rgba_u8();
+                TeamState {
+                    score: score.get(team),
+                    color: format!("#{r:02x}{g:02x}{b:02x}"),
+                }
+            })
+            .collect(),
+        players: players
+            .into_iter()
+            .map(|(entity, team, name, health)| {
+                let player_stats = stats.get(entity);
+                PlayerState {
+                    name: name.map(|name| name.0.clone()),
+                    team: team.0,
+                    lives: health.map(|health| health.current),
+                    hits: player_stats.hits,
+                    catches: player_stats.catches,
+                }
+            })
+            .collect(),
+    };
+    match serde_json::to_string(&state) {
+        Ok(json) => *snapshot.0.lock().unwrap() = json,
+        Err(error) => warn!("Couldn't serialize match state: {error}"),
+    }
+}
+
+fn serve(listener: &TcpListener, snapshot: &Snapshot) {
+    for stream in listener.incoming().flatten() {
+        if let Err(error) = respond(stream, snapshot) {
+            warn!("Observer request failed: {error}");
+        }
+    }
+}
+
+fn respond(mut stream: TcpStream, snapshot: &Snapshot) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+    stream.set_write_timeout(Some(REQUEST_TIMEOUT))?;
+    let mut request_line = String::new();
+    BufReader::new(&stream).read_line(&mut request_line)?;
+    let (status, body) = if request_line.starts_with("GET /state ") {
+        ("200 OK", snapshot.0.lock().unwrap().clone())
+    } else {
+        ("404 Not Found", String::new())
+    };
+    // Browser sources load overlays from files or other ports, so allow any origin.
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\n\
+         Content-Type: application/json\r\n\
+         Access-Control-Allow-Origin: *\r\n\
+         Content-Length: {}\r\n\
+         Connection: close\r\n\r\n{body}",
+        body.len()
+    )
+}
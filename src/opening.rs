@@ -0,0 +1,188 @@
+use bevy::{ecs::schedule::ShouldRun, prelude::*};
+use leafwing_input_manager::prelude::*;
+
+use crate::{
+    character::CharacterState,
+    cleanup::DespawnOnExit,
+    devices::PlayerSlot,
+    game_time::GameTime,
+    rules::{match_active, MatchActive, Ruleset},
+    settings::Settings,
+    tick_timer::{TickTimer, TICK_RATE},
+    tween::{Tween, TweenCompleted},
+    ui::{text::styled_text, widgets::WidgetStyle},
+    Action, GameState, Player,
+};
+
+const COUNTDOWN_SECONDS: u32 = 3;
+const GO_FADE_SECONDS: f32 = 0.6;
+/// How long a player who moved before GO is held up once everyone else gets going.
+const FALSE_START_STUN_SECONDS: f32 = 1.0;
+/// Pressing any of these before GO is a false start, as is pushing the stick past the player's
+/// deadzone.
+const FALSE_START_ACTIONS: [Action; 5] = [
+    Action::MoveLeft,
+    Action::MoveRight,
+    Action::MoveAway,
+    Action::MoveTowards,
+    Action::Dodge,
+];
+
+pub struct OpeningPlugin;
+
+impl Plugin for OpeningPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_enter(GameState::Ready).with_system(close_start_gate))
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(match_active)
+                    .with_system(call_false_starts)
+                    .with_system(count_down.after(call_false_starts)),
+            )
+            .add_system(remove_countdown);
+    }
+}
+
+/// Holds everyone at their back wall until the countdown reaches GO, for an opening rush.
+pub struct StartGate {
+    countdown: TickTimer,
+    /// Players who moved before GO.
+    fouls: Vec<Entity>,
+}
+
+impl StartGate {
+    fn seconds_left(&self) -> u32 {
+        (self.countdown.remaining_ticks() + TICK_RATE - 1) / TICK_RATE
+    }
+}
+
+/// Runs systems only while a match is being played and nobody is held at the start gate.
+pub fn gate_open(active: Option<Res<MatchActive>>, gate: Option<Res<StartGate>>) -> ShouldRun {
+    if gate.is_some() {
+        ShouldRun::No
+    } else {
+        match_active(active)
+    }
+}
+
+#[derive(Component)]
+struct Countdown;
+
+#[derive(Component)]
+struct CountdownLabel;
+
+fn close_start_gate(mut commands: Commands, ruleset: Res<Ruleset>) {
+    if !ruleset.opening_rush {
+        return;
+    }
+    commands.insert_resource(StartGate {
+        countdown: TickTimer::from_ticks(COUNTDOWN_SECONDS * TICK_RATE),
+        fouls: Vec::new(),
+    });
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            color: Color::NONE.into(),
+            ..default()
+        })
+        .insert_bundle((Countdown, DespawnOnExit(GameState::Ready)))
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(styled_text(COUNTDOWN_SECONDS.to_string(), "title"))
+                .insert(CountdownLabel);
+        });
+}
+
+fn call_false_starts(
+    gate: Option<ResMut<StartGate>>,
+    settings: Res<Settings>,
+    players: Query<(Entity, &ActionState<Action>, Option<&PlayerSlot>), With<Player>>,
+) {
+    let mut gate = if let Some(gate) = gate {
+        gate
+    } else {
+        return;
+    };
+    for (player, action_state, slot) in &players {
+        if gate.fouls.contains(&player) {
+            continue;
+        }
+        let stick = action_state
+            .axis_pair(Action::Move)
+            .map_or(Vec2::ZERO, |axis| {
+                settings
+                    .stick(slot.map_or(0, |slot| slot.0))
+                    .apply(axis.xy())
+            });
+        if stick != Vec2::ZERO
+            || FALSE_START_ACTIONS
+                .iter()
+                .any(|action| action_state.just_pressed(*action))
+        {
+            info!("Player {player:?} made a false start");
+            gate.fouls.push(player);
+        }
+    }
+}
+
+fn count_down(
+    mut commands: Commands,
+    time: Res<GameTime>,
+    style: Res<WidgetStyle>,
+    gate: Option<ResMut<StartGate>>,
+    mut players: Query<&mut CharacterState>,
+    mut labels: Query<(Entity, &mut Text), With<CountdownLabel>>,
+) {
+    let mut gate = if let Some(gate) = gate {
+        gate
+    } else {
+        return;
+    };
+    let go = gate.countdown.tick(time.ticks()).just_finished();
+    let seconds_left = gate.seconds_left();
+    for (label, mut text) in &mut labels {
+        if go {
+            text.sections[0].value = "GO!".to_string();
+            commands.entity(label).insert(Tween::<Text>::color(
+                style.text_color,
+                Color::NONE,
+                GO_FADE_SECONDS,
+            ));
+        } else {
+            let value = seconds_left.to_string();
+            if text.sections[0].value != value {
+                text.sections[0].value = value;
+            }
+        }
+    }
+    if go {
+        for player in &gate.fouls {
+            if let Ok(mut state) = players.get_mut(*player) {
+                *state = CharacterState::Stunned(TickTimer::from_seconds(FALSE_START_STUN_SECONDS));
+            }
+        }
+        commands.remove_resource::<StartGate>();
+    }
+}
+
+fn remove_countdown(
+    mut commands: Commands,
+    mut completed: EventReader<TweenCompleted>,
+    labels: Query<(), With<CountdownLabel>>,
+    countdowns: Query<Entity, With<Countdown>>,
+) {
+    if completed
+        .iter()
+        .any(|TweenCompleted { entity }| labels.contains(*entity))
+    {
+        for countdown in &countdowns {
+            commands.entity(countdown).despawn_recursive();
+        }
+    }
+}
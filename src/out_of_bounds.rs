@@ -0,0 +1,56 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::{rules::match_active, stage::ActiveStage, team::Team, Ball, HeldBy, Player};
+
+/// Anything that falls this far below the floor has left the stage for good.
+const OUT_OF_BOUNDS_DEPTH: f32 = -3.0;
+const PLAYER_SPAWN_HEIGHT: f32 = 0.25;
+
+pub struct OutOfBoundsPlugin;
+
+impl Plugin for OutOfBoundsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::new()
+                .with_run_criteria(match_active)
+                .with_system(remove_balls)
+                .with_system(respawn_players),
+        );
+    }
+}
+
+fn out_of_bounds(transform: &GlobalTransform) -> bool {
+    transform.translation().z < OUT_OF_BOUNDS_DEPTH
+}
+
+/// Removes balls that escaped the stage. The ball manager drops a new one in shortly after.
+fn remove_balls(
+    mut commands: Commands,
+    balls: Query<(Entity, &GlobalTransform), (With<Ball>, Without<HeldBy>)>,
+) {
+    for (ball, transform) in &balls {
+        if out_of_bounds(transform) {
+            commands.entity(ball).despawn_recursive();
+        }
+    }
+}
+
+/// Puts players who fell off the stage back at their team's spawn on the stage being played.
+fn respawn_players(
+    stage: Option<Res<ActiveStage>>,
+    mut players: Query<(&mut Transform, &mut Velocity, &GlobalTransform, &Team), With<Player>>,
+) {
+    let stage = if let Some(stage) = stage {
+        stage
+    } else {
+        return;
+    };
+    for (mut transform, mut velocity, global_transform, team) in &mut players {
+        if out_of_bounds(global_transform) {
+            info!("A player on team {} fell off the stage", team.0);
+            transform.translation = stage.spawn(team.0).extend(PLAYER_SPAWN_HEIGHT);
+            *velocity = Velocity::zero();
+        }
+    }
+}
@@ -0,0 +1,245 @@
+use bevy::{
+    prelude::*,
+    reflect::TypeUuid,
+    render::{texture::DEFAULT_IMAGE_HANDLE, view::NoFrustumCulling},
+};
+use rand::Rng;
+use serde::Deserialize;
+
+use crate::{
+    atlas::PackedAtlases,
+    game_time::GameTime,
+    ron_loader::{RonAsset, RonLoader},
+    DataAssets,
+};
+
+use self::render::{ParticleInstance, ParticleInstances, ParticleRenderPlugin, ParticleTexture};
+
+mod render;
+
+pub struct ParticlePlugin;
+
+impl Plugin for ParticlePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(ParticleRenderPlugin)
+            .add_asset::<EmitterAsset>()
+            .init_asset_loader::<RonLoader<EmitterAsset>>()
+            .init_resource::<ParticleMesh>()
+            .add_system(setup_emitters)
+            .add_system(simulate_particles.after(setup_emitters));
+    }
+}
+
+/// Describes how an emitter spawns particles and how they evolve over their lifetime.
+#[derive(Deserialize, TypeUuid)]
+#[uuid = "0f4c9c57-3a3e-4b8e-8f0d-6d2b8f1e7a42"]
+pub struct EmitterAsset {
+    pub mode: EmitMode,
+    pub max_particles: usize,
+    pub lifetime: (f32, f32),
+    pub speed: (f32, f32),
+    pub direction: [f32; 3],
+    /// Half-angle of the emission cone around `direction`, in radians.
+    pub spread: f32,
+    pub gravity: f32,
+    pub size: (f32, f32),
+    pub color: (Color, Color),
+    /// Name of the sprite in the effects atlas to draw particles with, tinted by `color`.
+    #[serde(default)]
+    pub sprite: Option<String>,
+}
+
+#[derive(Deserialize, Clone, Copy)]
+pub enum EmitMode {
+    /// Emits this many particles at once, then despawns the emitter when they have died.
+    Burst(u32),
+    /// Emits this many particles per second.
+    Loop(f32),
+    /// Emits this many particles per unit of distance the emitter travels.
+    Trail(f32),
+}
+
+impl RonAsset for EmitterAsset {
+    const EXTENSIONS: &'static [&'static str] = &["emitter.ron"];
+}
+
+struct ParticleMesh(Handle<Mesh>);
+
+impl FromWorld for ParticleMesh {
+    fn from_world(world: &mut World) -> Self {
+        Self(
+            world
+                .resource_mut::<Assets<Mesh>>()
+                .add(shape::Quad::new(Vec2::ONE).into()),
+        )
+    }
+}
+
+#[derive(Component)]
+pub struct ParticleEmitter {
+    pub effect: Handle<EmitterAsset>,
+    pub active: bool,
+    particles: Vec<Particle>,
+    pending: f32,
+    last_position: Option<Vec3>,
+    emitted: bool,
+}
+
+impl ParticleEmitter {
+    pub fn new(effect: Handle<EmitterAsset>) -> Self {
+        Self {
+            effect,
+            active: true,
+            particles: Vec::new(),
+            pending: 0.0,
+            last_position: None,
+            emitted: false,
+        }
+    }
+}
+
+struct Particle {
+    position: Vec3,
+    velocity: Vec3,
+    age: f32,
+    lifetime: f32,
+}
+
+fn setup_emitters(
+    mut commands: Commands,
+    mesh: Res<ParticleMesh>,
+    emitters: Query<Entity, Added<ParticleEmitter>>,
+) {
+    for entity in &emitters {
+        commands.entity(entity).insert_bundle((
+            mesh.0.clone(),
+            ParticleInstances::default(),
+            ParticleTexture(DEFAULT_IMAGE_HANDLE.typed()),
+            NoFrustumCulling,
+        ));
+    }
+}
+
+fn simulate_particles(
+    mut commands: Commands,
+    time: Res<GameTime>,
+    effects: Res<Assets<EmitterAsset>>,
+    data: Option<Res<DataAssets>>,
+    packed: Res<PackedAtlases>,
+    atlases: Res<Assets<TextureAtlas>>,
+    mut emitters: Query<(
+        Entity,
+        &mut ParticleEmitter,
+        &mut ParticleInstances,
+        &mut ParticleTexture,
+        &GlobalTransform,
+    )>,
+) {
+    let mut rng = rand::thread_rng();
+    let delta = time.delta_seconds();
+    let effects_atlas = data
+        .and_then(|data| packed.get(&data.effects_atlas))
+        .and_then(|packed| Some((packed, atlases.get(&packed.atlas)?)));
+    for (entity, mut emitter, mut instances, mut texture, transform) in &mut emitters {
+        let effect = if let Some(effect) = effects.get(&emitter.effect) {
+            effect
+        } else {
+            continue;
+        };
+        let mut uv_rect = [0.0, 0.0, 1.0, 1.0];
+        if let Some(sprite) = &effect.sprite {
+            let rect = effects_atlas.and_then(|(packed, atlas)| {
+                let rect = atlas.textures.get(packed.index(sprite)?)?;
+                Some((atlas, *rect))
+            });
+            if let Some((atlas, rect)) = rect {
+                uv_rect = [
+                    rect.min.x / atlas.size.x,
+                    rect.min.y / atlas.size.y,
+                    rect.max.x / atlas.size.x,
+                    rect.max.y / atlas.size.y,
+                ];
+                if texture.0 != atlas.texture {
+                    texture.0 = atlas.texture.clone();
+                }
+            }
+        }
+        let emitter = &mut *emitter;
+        let position = transform.translation();
+
+        if emitter.active {
+            emitter.pending += match effect.mode {
+                EmitMode::Burst(count) if !emitter.emitted => count as f32,
+                EmitMode::Burst(_) => 0.0,
+                EmitMode::Loop(rate) => rate * delta,
+                EmitMode::Trail(rate) => {
+                    emitter
+                        .last_position
+                        .map_or(0.0, |last| last.distance(position))
+                        * rate
+                }
+            };
+        }
+        emitter.emitted = true;
+        emitter.last_position = Some(position);
+
+        let direction = Vec3::from(effect.direction).normalize_or_zero();
+        while emitter.pending >= 1.0 {
+            emitter.pending -= 1.0;
+            if emitter.particles.len() >= effect.max_particles {
+                continue;
+            }
+            emitter.particles.push(Particle {
+                position,
+                velocity: random_direction(&mut rng, direction, effect.spread)
+                    * rng.gen_range(effect.speed.0..=effect.speed.1),
+                age: 0.0,
+                lifetime: rng.gen_range(effect.lifetime.0..=effect.lifetime.1),
+            });
+        }
+
+        for particle in &mut emitter.particles {
+            particle.velocity.z -= effect.gravity * delta;
+            particle.position += particle.velocity * delta;
+            particle.age += delta;
+        }
+        emitter
+            .particles
+            .retain(|particle| particle.age < particle.lifetime);
+
+        instances.0 = emitter
+            .particles
+            .iter()
+            .map(|particle| {
+                let t = particle.age / particle.lifetime;
+                let color = Vec4::from(effect.color.0.as_rgba_f32())
+                    .lerp(Vec4::from(effect.color.1.as_rgba_f32()), t);
+                ParticleInstance {
+                    position: particle.position,
+                    size: effect.size.0 + (effect.size.1 - effect.size.0) * t,
+                    color: color.to_array(),
+                    uv_rect,
+                }
+            })
+            .collect();
+
+        if matches!(effect.mode, EmitMode::Burst(_)) && emitter.particles.is_empty() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+/// Picks a direction within a cone of half-angle `spread` around `axis`.
+fn random_direction(rng: &mut impl Rng, axis: Vec3, spread: f32) -> Vec3 {
+    if axis == Vec3::ZERO {
+        return Vec3::new(
+            rng.gen_range(-1.0..=1.0),
+            rng.gen_range(-1.0..=1.0),
+            rng.gen_range(-1.0..=1.0),
+        )
+        .normalize_or_zero();
+    }
+    let tilt = Quat::from_axis_angle(axis.any_orthonormal_vector(), rng.gen_range(0.0..=spread));
+    let roll = Quat::from_axis_angle(axis, rng.gen_range(0.0..std::f32::consts::TAU));
+    roll * tilt * axis
+}
@@ -0,0 +1,381 @@
+use bevy::{
+    core_pipeline::core_3d::Transparent3d,
+    ecs::{
+        query::QueryItem,
+        system::{lifetimeless::*, SystemParamItem},
+    },
+    pbr::{MeshPipeline, MeshPipelineKey, MeshUniform, SetMeshBindGroup, SetMeshViewBindGroup},
+    prelude::*,
+    render::{
+        extract_component::{ExtractComponent, ExtractComponentPlugin},
+        mesh::{GpuBufferInfo, MeshVertexBufferLayout},
+        render_asset::RenderAssets,
+        render_phase::{
+            AddRenderCommand, DrawFunctions, EntityRenderCommand, RenderCommandResult, RenderPhase,
+            SetItemPipeline, TrackedRenderPass,
+        },
+        render_resource::*,
+        renderer::{RenderDevice, RenderQueue},
+        view::ExtractedView,
+        RenderApp, RenderStage,
+    },
+    utils::HashMap,
+};
+use bytemuck::{Pod, Zeroable};
+
+/// The fewest particles an emitter's instance buffer has room for, so small emitters never need
+/// to grow theirs.
+const MIN_INSTANCE_CAPACITY: usize = 64;
+
+/// Draws every particle of an emitter as one instanced, camera-facing quad draw call.
+pub struct ParticleRenderPlugin;
+
+impl Plugin for ParticleRenderPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(ExtractComponentPlugin::<ParticleInstances>::default())
+            .add_plugin(ExtractComponentPlugin::<ParticleTexture>::default());
+        app.sub_app_mut(RenderApp)
+            .add_render_command::<Transparent3d, DrawParticles>()
+            .init_resource::<ParticlePipeline>()
+            .init_resource::<InstanceBuffers>()
+            .init_resource::<SpecializedMeshPipelines<ParticlePipeline>>()
+            .add_system_to_stage(RenderStage::Queue, queue_particles)
+            .add_system_to_stage(RenderStage::Prepare, prepare_instance_buffers);
+    }
+}
+
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+pub struct ParticleInstance {
+    pub position: Vec3,
+    pub size: f32,
+    pub color: [f32; 4],
+    /// The corners of the particle's sprite in its texture, as UV coordinates.
+    pub uv_rect: [f32; 4],
+}
+
+#[derive(Component, Default, Deref, DerefMut)]
+pub struct ParticleInstances(pub Vec<ParticleInstance>);
+
+impl ExtractComponent for ParticleInstances {
+    type Query = &'static ParticleInstances;
+    type Filter = ();
+
+    fn extract_component(item: QueryItem<Self::Query>) -> Self {
+        ParticleInstances(item.0.clone())
+    }
+}
+
+/// The texture an emitter's particles are drawn with, usually a packed effects atlas.
+#[derive(Component, Clone)]
+pub struct ParticleTexture(pub Handle<Image>);
+
+impl ExtractComponent for ParticleTexture {
+    type Query = &'static ParticleTexture;
+    type Filter = ();
+
+    fn extract_component(item: QueryItem<Self::Query>) -> Self {
+        item.clone()
+    }
+}
+
+#[derive(Component)]
+struct ParticleTextureBindGroup(BindGroup);
+
+fn queue_particles(
+    mut commands: Commands,
+    render_device: Res<RenderDevice>,
+    images: Res<RenderAssets<Image>>,
+    transparent_3d_draw_functions: Res<DrawFunctions<Transparent3d>>,
+    particle_pipeline: Res<ParticlePipeline>,
+    msaa: Res<Msaa>,
+    mut pipelines: ResMut<SpecializedMeshPipelines<ParticlePipeline>>,
+    mut pipeline_cache: ResMut<PipelineCache>,
+    meshes: Res<RenderAssets<Mesh>>,
+    emitters: Query<
+        (Entity, &MeshUniform, &Handle<Mesh>, &ParticleTexture),
+        With<ParticleInstances>,
+    >,
+    mut views: Query<(&ExtractedView, &mut RenderPhase<Transparent3d>)>,
+) {
+    let draw_particles = transparent_3d_draw_functions
+        .read()
+        .get_id::<DrawParticles>()
+        .unwrap();
+    // Emitters sharing an atlas share a bind group.
+    let mut bind_groups = HashMap::new();
+    for (entity, _, _, texture) in &emitters {
+        let image = if let Some(image) = images.get(&texture.0) {
+            image
+        } else {
+            continue;
+        };
+        let bind_group = bind_groups
+            .entry(texture.0.id)
+            .or_insert_with(|| {
+                render_device.create_bind_group(&BindGroupDescriptor {
+                    label: Some("particle texture bind group"),
+                    layout: &particle_pipeline.texture_layout,
+                    entries: &[
+                        BindGroupEntry {
+                            binding: 0,
+                            resource: BindingResource::TextureView(&image.texture_view),
+                        },
+                        BindGroupEntry {
+                            binding: 1,
+                            resource: BindingResource::Sampler(&image.sampler),
+                        },
+                    ],
+                })
+            })
+            .clone();
+        commands
+            .entity(entity)
+            .insert(ParticleTextureBindGroup(bind_group));
+    }
+
+    let msaa_key =
+        MeshPipelineKey::from_msaa_samples(msaa.samples) | MeshPipelineKey::TRANSPARENT_MAIN_PASS;
+
+    for (view, mut transparent_phase) in &mut views {
+        let view_row_2 = view.transform.compute_matrix().inverse().row(2);
+        for (entity, mesh_uniform, mesh_handle, texture) in &emitters {
+            if !images.contains_key(&texture.0) {
+                continue;
+            }
+            if let Some(mesh) = meshes.get(mesh_handle) {
+                let key =
+                    msaa_key | MeshPipelineKey::from_primitive_topology(mesh.primitive_topology);
+                let pipeline = pipelines
+                    .specialize(&mut pipeline_cache, &particle_pipeline, key, &mesh.layout)
+                    .unwrap();
+                transparent_phase.add(Transparent3d {
+                    entity,
+                    pipeline,
+                    draw_function: draw_particles,
+                    distance: view_row_2.dot(mesh_uniform.transform.col(3)),
+                });
+            }
+        }
+    }
+}
+
+struct InstanceBuffer {
+    buffer: Buffer,
+    /// How many particles fit in the buffer, and how many of those are drawn.
+    capacity: usize,
+    length: usize,
+}
+
+impl InstanceBuffer {
+    /// Leaves room to grow, so an emitter that keeps spawning doesn't reallocate every frame.
+    fn new(render_device: &RenderDevice, needed: usize) -> Self {
+        let capacity = needed.next_power_of_two().max(MIN_INSTANCE_CAPACITY);
+        Self {
+            buffer: render_device.create_buffer(&BufferDescriptor {
+                label: Some("particle instance buffer"),
+                size: (capacity * std::mem::size_of::<ParticleInstance>()) as u64,
+                usage: BufferUsages::VERTEX | BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            }),
+            capacity,
+            length: 0,
+        }
+    }
+}
+
+/// Each emitter's instance buffer, kept from frame to frame by the emitter's entity, since
+/// render world entities are cleared every frame.
+#[derive(Default)]
+struct InstanceBuffers(HashMap<Entity, InstanceBuffer>);
+
+fn prepare_instance_buffers(
+    emitters: Query<(Entity, &ParticleInstances)>,
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+    mut buffers: ResMut<InstanceBuffers>,
+) {
+    buffers.0.retain(|entity, _| emitters.contains(*entity));
+    for (entity, instances) in &emitters {
+        let buffer = buffers
+            .0
+            .entry(entity)
+            .or_insert_with(|| InstanceBuffer::new(&render_device, instances.len()));
+        if buffer.capacity < instances.len() {
+            *buffer = InstanceBuffer::new(&render_device, instances.len());
+        }
+        buffer.length = instances.len();
+        if !instances.is_empty() {
+            render_queue.write_buffer(
+                &buffer.buffer,
+                0,
+                bytemuck::cast_slice(instances.as_slice()),
+            );
+        }
+    }
+}
+
+struct ParticlePipeline {
+    shader: Handle<Shader>,
+    mesh_pipeline: MeshPipeline,
+    texture_layout: BindGroupLayout,
+}
+
+impl FromWorld for ParticlePipeline {
+    fn from_world(world: &mut World) -> Self {
+        let texture_layout =
+            world
+                .resource::<RenderDevice>()
+                .create_bind_group_layout(&BindGroupLayoutDescriptor {
+                    label: Some("particle texture layout"),
+                    entries: &[
+                        BindGroupLayoutEntry {
+                            binding: 0,
+                            visibility: ShaderStages::FRAGMENT,
+                            ty: BindingType::Texture {
+                                multisampled: false,
+                                sample_type: TextureSampleType::Float { filterable: true },
+                                view_dimension: TextureViewDimension::D2,
+                            },
+                            count: None,
+                        },
+                        BindGroupLayoutEntry {
+                            binding: 1,
+                            visibility: ShaderStages::FRAGMENT,
+                            ty: BindingType::Sampler(SamplerBindingType::Filtering),
+                            count: None,
+                        },
+                    ],
+                });
+        Self {
+            texture_layout,
+            shader: world
+                .resource::<AssetServer>()
+                .load("shaders/particle.wgsl"),
+            mesh_pipeline: world.resource::<MeshPipeline>().clone(),
+        }
+    }
+}
+
+impl SpecializedMeshPipeline for ParticlePipeline {
+    type Key = MeshPipelineKey;
+
+    fn specialize(
+        &self,
+        key: Self::Key,
+        layout: &MeshVertexBufferLayout,
+    ) -> Result<RenderPipelineDescriptor, SpecializedMeshPipelineError> {
+        let mut descriptor = self.mesh_pipeline.specialize(key, layout)?;
+        descriptor.vertex.shader = self.shader.clone();
+        descriptor.vertex.buffers.push(VertexBufferLayout {
+            array_stride: std::mem::size_of::<ParticleInstance>() as u64,
+            step_mode: VertexStepMode::Instance,
+            attributes: vec![
+                VertexAttribute {
+                    format: VertexFormat::Float32x4,
+                    offset: 0,
+                    shader_location: 3,
+                },
+                VertexAttribute {
+                    format: VertexFormat::Float32x4,
+                    offset: VertexFormat::Float32x4.size(),
+                    shader_location: 4,
+                },
+                VertexAttribute {
+                    format: VertexFormat::Float32x4,
+                    offset: VertexFormat::Float32x4.size() * 2,
+                    shader_location: 5,
+                },
+            ],
+        });
+        if let Some(fragment) = &mut descriptor.fragment {
+            fragment.shader = self.shader.clone();
+        }
+        descriptor.layout = Some(vec![
+            self.mesh_pipeline.view_layout.clone(),
+            self.mesh_pipeline.mesh_layout.clone(),
+            self.texture_layout.clone(),
+        ]);
+        Ok(descriptor)
+    }
+}
+
+type DrawParticles = (
+    SetItemPipeline,
+    SetMeshViewBindGroup<0>,
+    SetMeshBindGroup<1>,
+    SetParticleTextureBindGroup<2>,
+    DrawMeshInstanced,
+);
+
+struct SetParticleTextureBindGroup<const I: usize>;
+
+impl<const I: usize> EntityRenderCommand for SetParticleTextureBindGroup<I> {
+    type Param = SQuery<Read<ParticleTextureBindGroup>>;
+
+    fn render<'w>(
+        _view: Entity,
+        item: Entity,
+        bind_groups: SystemParamItem<'w, '_, Self::Param>,
+        pass: &mut TrackedRenderPass<'w>,
+    ) -> RenderCommandResult {
+        if let Ok(bind_group) = bind_groups.get_inner(item) {
+            pass.set_bind_group(I, &bind_group.0, &[]);
+            RenderCommandResult::Success
+        } else {
+            RenderCommandResult::Failure
+        }
+    }
+}
+
+struct DrawMeshInstanced;
+
+impl EntityRenderCommand for DrawMeshInstanced {
+    type Param = (
+        SRes<RenderAssets<Mesh>>,
+        SQuery<Read<Handle<Mesh>>>,
+        SRes<InstanceBuffers>,
+    );
+
+    fn render<'w>(
+        _view: Entity,
+        item: Entity,
+        (meshes, mesh_query, instance_buffers): SystemParamItem<'w, '_, Self::Param>,
+        pass: &mut TrackedRenderPass<'w>,
+    ) -> RenderCommandResult {
+        let (mesh_handle, instance_buffer) = match (
+            mesh_query.get(item),
+            instance_buffers.into_inner().0.get(&item),
+        ) {
+            (Ok(mesh_handle), Some(instance_buffer)) => (mesh_handle, instance_buffer),
+            _ => return RenderCommandResult::Failure,
+        };
+        let gpu_mesh = match meshes.into_inner().get(mesh_handle) {
+            Some(gpu_mesh) => gpu_mesh,
+            None => return RenderCommandResult::Failure,
+        };
+
+        // An empty slice of a buffer isn't valid to bind, and there'd be nothing to draw anyway.
+        if instance_buffer.length == 0 {
+            return RenderCommandResult::Success;
+        }
+        pass.set_vertex_buffer(0, gpu_mesh.vertex_buffer.slice(..));
+        let used = (instance_buffer.length * std::mem::size_of::<ParticleInstance>()) as u64;
+        pass.set_vertex_buffer(1, instance_buffer.buffer.slice(..used));
+        let instances = 0..instance_buffer.length as u32;
+        match &gpu_mesh.buffer_info {
+            GpuBufferInfo::Indexed {
+                buffer,
+                index_format,
+                count,
+            } => {
+                pass.set_index_buffer(buffer.slice(..), 0, *index_format);
+                pass.draw_indexed(0..*count, 0, instances);
+            }
+            GpuBufferInfo::NonIndexed { vertex_count } => {
+                pass.draw(0..*vertex_count, instances);
+            }
+        }
+        RenderCommandResult::Success
+    }
+}
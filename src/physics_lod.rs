@@ -0,0 +1,127 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::{devices::PlayerSlot, rules::match_active, Player};
+
+/// Above this many dynamic bodies the court counts as busy, and physics starts cutting corners.
+const BUSY_BODY_COUNT: usize = 24;
+/// Bodies further than this from every local player are simplified while the court is busy.
+const FAR_DISTANCE: f32 = 8.0;
+const FULL_VELOCITY_ITERATIONS: usize = 4;
+const BUSY_VELOCITY_ITERATIONS: usize = 2;
+/// Far bodies under load fall asleep at these speeds, well above rapier's defaults.
+const BUSY_SLEEP_LINEAR: f32 = 1.5;
+const BUSY_SLEEP_ANGULAR: f32 = 2.0;
+
+pub struct PhysicsLodPlugin;
+
+impl Plugin for PhysicsLodPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::new()
+                .with_run_criteria(match_active)
+                .with_system(adjust_solver)
+                .with_system(simplify_far_bodies),
+        );
+    }
+}
+
+/// The collider a simplified body goes back to once it matters again.
+#[derive(Component)]
+struct FullCollider(Collider);
+
+fn adjust_solver(mut rapier: ResMut<RapierContext>, bodies: Query<&RigidBody>) {
+    let busy = bodies
+        .iter()
+        .filter(|body| matches!(body, RigidBody::Dynamic))
+        .count()
+        > BUSY_BODY_COUNT;
+    let iterations = if busy {
+        BUSY_VELOCITY_ITERATIONS
+    } else {
+        FULL_VELOCITY_ITERATIONS
+    };
+    if rapier.integration_parameters.max_velocity_iterations != iterations {
+        rapier.integration_parameters.max_velocity_iterations = iterations;
+    }
+}
+
+/// Swaps far away bodies to sphere colliders and lets them sleep sooner while the court is busy.
+/// Only balls and props are simplified. Every player, bots included, is always simulated in full,
+/// so how they get hit doesn't depend on where the local players are.
+fn simplify_far_bodies(
+    mut commands: Commands,
+    players: Query<&GlobalTransform, With<PlayerSlot>>,
+    mut bodies: Query<
+        (
+            Entity,
+            &RigidBody,
+            &GlobalTransform,
+            &mut Collider,
+            Option<&FullCollider>,
+            Option<&mut Sleeping>,
+        ),
+        Without<Player>,
+    >,
+) {
+    let busy = bodies
+        .iter()
+        .filter(|(_, body, ..)| matches!(body, RigidBody::Dynamic))
+        .count()
+        > BUSY_BODY_COUNT;
+    let players = players
+        .iter()
+        .map(GlobalTransform::translation)
+        .collect::<Vec<_>>();
+
+    for (entity, body, transform, mut collider, full, sleeping) in &mut bodies {
+        if !matches!(body, RigidBody::Dynamic) {
+            continue;
+        }
+        let position = transform.translation();
+        let far = busy
+            && players
+                .iter()
+                .all(|player| player.distance(position) > FAR_DISTANCE);
+
+        match (far, full) {
+            (true, None) => {
+                if collider.as_ball().is_none() {
+                    let radius = collider.raw.compute_local_aabb().half_extents().max();
+                    let simple = Collider::ball(radius);
+                    commands
+                        .entity(entity)
+                        .insert(FullCollider(std::mem::replace(&mut *collider, simple)));
+                }
+            }
+            (false, Some(FullCollider(original))) => {
+                *collider = original.clone();
+                commands.entity(entity).remove::<FullCollider>();
+            }
+            _ => {}
+        }
+
+        let (linear, angular) = if far {
+            (BUSY_SLEEP_LINEAR, BUSY_SLEEP_ANGULAR)
+        } else {
+            let default = Sleeping::default();
+            (default.linear_threshold, default.angular_threshold)
+        };
+        match sleeping {
+            Some(mut sleeping) => {
+                if (sleeping.linear_threshold - linear).abs() > f32::EPSILON {
+                    sleeping.linear_threshold = linear;
+                    sleeping.angular_threshold = angular;
+                }
+            }
+            None if far => {
+                commands.entity(entity).insert(Sleeping {
+                    linear_threshold: linear,
+                    angular_threshold: angular,
+                    ..default()
+                });
+            }
+            None => {}
+        }
+    }
+}
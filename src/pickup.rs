@@ -0,0 +1,345 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use leafwing_input_manager::prelude::*;
+
+use crate::{
+    character::CharacterState,
+    game_time::GameTime,
+    graphics::SpriteDepth,
+    hold_ball,
+    input_buffer::InputBuffer,
+    interactions::BallKind,
+    rules::match_active,
+    tick_timer::TickTimer,
+    tween::{Ease, Tween},
+    Action, Ball, BallState, HoldingBall, Player,
+};
+
+/// Balls whose center is higher off the floor than this are still bouncing around, out of reach.
+const PICKUP_HEIGHT: f32 = 0.3;
+const PICKUP_POP_SCALE: f32 = 1.3;
+const PICKUP_POP_SECONDS: f32 = 0.25;
+
+pub struct PickupPlugin;
+
+impl Plugin for PickupPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PickupSettings>()
+            .add_system(attach_pickup_sensors)
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(match_active)
+                    .with_system(track_reach)
+                    .with_system(pick_up_ball.after(track_reach))
+                    .with_system(settle_struggles.after(pick_up_ball))
+                    .with_system(attract_loose_balls),
+            );
+    }
+}
+
+/// How forgiving picking up balls is.
+pub struct PickupSettings {
+    /// Radius of the sensor around each player that balls have to be inside to be picked up.
+    /// Wider than the player's body, so a ball doesn't need to touch them.
+    pub radius: f32,
+    /// Loose balls this close to a player reaching for one roll towards them.
+    pub magnet_radius: f32,
+    /// How quickly balls in the magnet radius speed up towards the player, in metres per second
+    /// squared.
+    pub magnet_pull: f32,
+    /// Players reaching for the same ball from within this many metres of each other's distance
+    /// to it struggle for it, rather than the slightly closer one taking it outright.
+    pub contest_margin: f32,
+    /// How long a struggle over a ball lasts before whoever pressed pickup most wins it.
+    pub struggle_seconds: f32,
+}
+
+impl Default for PickupSettings {
+    fn default() -> Self {
+        Self {
+            radius: 0.5,
+            magnet_radius: 1.2,
+            magnet_pull: 4.0,
+            contest_margin: 0.15,
+            struggle_seconds: 0.5,
+        }
+    }
+}
+
+/// A sensor around a player that finds balls within their reach.
+#[derive(Component)]
+struct PickupSensor {
+    player: Entity,
+}
+
+/// The balls inside a player's pickup sensor.
+#[derive(Component, Default)]
+struct InReach(Vec<Entity>);
+
+/// A loose ball that several players grabbed at once, won by whoever mashes pickup the most.
+#[derive(Component)]
+struct Struggle {
+    /// The players struggling, closest first, which also breaks ties.
+    contenders: Vec<Entity>,
+    presses: Vec<u32>,
+    timer: TickTimer,
+}
+
+fn attach_pickup_sensors(
+    mut commands: Commands,
+    settings: Res<PickupSettings>,
+    players: Query<Entity, Added<Player>>,
+) {
+    for player in &players {
+        let sensor = commands
+            .spawn_bundle(TransformBundle::default())
+            .insert_bundle((
+                Collider::ball(settings.radius),
+                Sensor,
+                ColliderMassProperties::Density(0.0),
+                ActiveEvents::COLLISION_EVENTS,
+                PickupSensor { player },
+            ))
+            .id();
+        commands
+            .entity(player)
+            .add_child(sensor)
+            .insert(InReach::default());
+    }
+}
+
+fn track_reach(
+    mut collisions: EventReader<CollisionEvent>,
+    sensors: Query<&PickupSensor>,
+    balls: Query<(), With<Ball>>,
+    mut reaches: Query<&mut InReach>,
+) {
+    for event in collisions.iter() {
+        let (e1, e2, entered) = match event {
+            CollisionEvent::Started(e1, e2, _) => (*e1, *e2, true),
+            CollisionEvent::Stopped(e1, e2, _) => (*e1, *e2, false),
+        };
+        let (sensor, ball) = if sensors.contains(e1) && balls.contains(e2) {
+            (e1, e2)
+        } else if sensors.contains(e2) && balls.contains(e1) {
+            (e2, e1)
+        } else {
+            continue;
+        };
+        let player = sensors.get(sensor).unwrap().player;
+        if let Ok(mut reach) = reaches.get_mut(player) {
+            reach.0.retain(|other| *other != ball);
+            if entered {
+                reach.0.push(ball);
+            }
+        }
+    }
+}
+
+/// Picks up the nearest ball lying within reach when pickup is pressed. Balls in the air can't
+/// be picked up, and a player only holds one ball at a time.
+///
+/// Players reaching for the same ball in the same frame are settled by who is closest to it,
+/// then by the lower entity, so every peer in a networked match hands it to the same player. If
+/// they were about as close as each other, they [struggle](Struggle) for it instead.
+fn pick_up_ball(
+    mut commands: Commands,
+    settings: Res<PickupSettings>,
+    mut characters: Query<
+        (
+            Entity,
+            &ActionState<Action>,
+            &mut InputBuffer,
+            &CharacterState,
+            &GlobalTransform,
+            &InReach,
+            &Children,
+        ),
+        Without<HoldingBall>,
+    >,
+    balls: Query<(&GlobalTransform, &BallState, &BallKind), (With<Ball>, Without<Struggle>)>,
+    struggles: Query<&Struggle>,
+    sprites: Query<(), With<SpriteDepth>>,
+) {
+    // Mashing pickup in a struggle shouldn't grab some other ball in reach.
+    let struggling = struggles
+        .iter()
+        .flat_map(|struggle| struggle.contenders.iter().copied())
+        .collect::<Vec<_>>();
+    let mut claims = Vec::new();
+    for (character, action_state, buffer, state, transform, reach, _) in &characters {
+        if !state.is_free()
+            || struggling.contains(&character)
+            || !buffer.pending(action_state, Action::Pickup)
+        {
+            continue;
+        }
+        let position = transform.translation();
+        for ball in &reach.0 {
+            // Live balls hit the character instead of landing in their hands.
+            if let Ok((ball_transform, BallState::Dead, _)) = balls.get(*ball) {
+                let ball_position = ball_transform.translation();
+                if ball_position.z <= PICKUP_HEIGHT {
+                    claims.push((ball_position.distance(position), character, *ball));
+                }
+            }
+        }
+    }
+    claims.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)).then(a.2.cmp(&b.2)));
+
+    let mut taken = Vec::new();
+    let mut holders = Vec::new();
+    for &(distance, character, ball) in &claims {
+        if taken.contains(&ball) || holders.contains(&character) {
+            continue;
+        }
+        taken.push(ball);
+        let mut contenders = vec![character];
+        for &(other_distance, other, other_ball) in &claims {
+            if other_ball == ball
+                && !contenders.contains(&other)
+                && !holders.contains(&other)
+                && other_distance - distance <= settings.contest_margin
+            {
+                contenders.push(other);
+            }
+        }
+        holders.extend(&contenders);
+        for contender in &contenders {
+            if let Ok((_, action_state, mut buffer, ..)) = characters.get_mut(*contender) {
+                buffer.take(action_state, Action::Pickup);
+            }
+        }
+
+        if contenders.len() > 1 {
+            info!("Characters {contenders:?} are struggling for ball {ball:?}");
+            commands.entity(ball).insert(Struggle {
+                presses: vec![0; contenders.len()],
+                contenders,
+                timer: TickTimer::from_seconds(settings.struggle_seconds),
+            });
+        } else if let Ok((.., children)) = characters.get(character) {
+            let kind = balls
+                .get(ball)
+                .map_or_else(|_| BallKind::default(), |(.., kind)| *kind);
+            grab(&mut commands, character, ball, kind, children, &sprites);
+        }
+    }
+}
+
+/// Counts pickup presses from everyone struggling for a ball, and hands it to whoever pressed
+/// most once the struggle is over. Contenders who wandered off, got hold of another ball or were
+/// knocked out of action forfeit.
+fn settle_struggles(
+    mut commands: Commands,
+    time: Res<GameTime>,
+    characters: Query<
+        (&ActionState<Action>, &CharacterState, &InReach, &Children),
+        Without<HoldingBall>,
+    >,
+    mut balls: Query<(Entity, &mut Struggle, &BallState, &BallKind), With<Ball>>,
+    sprites: Query<(), With<SpriteDepth>>,
+) {
+    for (ball, mut struggle, state, kind) in &mut balls {
+        let Struggle {
+            contenders,
+            presses,
+            timer,
+        } = &mut *struggle;
+        for (contender, presses) in contenders.iter().zip(presses.iter_mut()) {
+            if let Ok((action_state, ..)) = characters.get(*contender) {
+                if action_state.just_pressed(Action::Pickup) {
+                    *presses += 1;
+                }
+            }
+        }
+        if *state != BallState::Dead {
+            commands.entity(ball).remove::<Struggle>();
+            continue;
+        }
+        if !timer.tick(time.ticks()).finished() {
+            continue;
+        }
+
+        commands.entity(ball).remove::<Struggle>();
+        let winner = contenders
+            .iter()
+            .zip(presses.iter())
+            .filter_map(|(contender, presses)| {
+                let (_, state, reach, children) = characters.get(*contender).ok()?;
+                (state.is_free() && reach.0.contains(&ball))
+                    .then(|| (*contender, *presses, children))
+            })
+            // Ties go to whoever was closest, which comes first.
+            .reduce(|best, next| if next.1 > best.1 { next } else { best });
+        if let Some((character, presses, children)) = winner {
+            info!(
+                "Character {character:?} won the struggle for ball {ball:?} with {presses} presses"
+            );
+            grab(&mut commands, character, ball, *kind, children, &sprites);
+        }
+    }
+}
+
+fn grab(
+    commands: &mut Commands,
+    character: Entity,
+    ball: Entity,
+    kind: BallKind,
+    children: &Children,
+    sprites: &Query<(), With<SpriteDepth>>,
+) {
+    info!("Character {character:?} has picked up ball {ball:?}");
+    hold_ball(character, ball, kind, commands);
+    for sprite in children.iter().filter(|child| sprites.contains(**child)) {
+        commands.entity(*sprite).insert(
+            Tween::scale(Vec3::splat(PICKUP_POP_SCALE), Vec3::ONE, PICKUP_POP_SECONDS)
+                .ease(Ease::BackOut),
+        );
+    }
+}
+
+/// Draws loose balls towards a player reaching for one, so it can be picked up without lining
+/// up exactly.
+fn attract_loose_balls(
+    time: Res<GameTime>,
+    settings: Res<PickupSettings>,
+    characters: Query<
+        (
+            &GlobalTransform,
+            &CharacterState,
+            &ActionState<Action>,
+            &InputBuffer,
+        ),
+        (With<Player>, Without<HoldingBall>),
+    >,
+    mut balls: Query<(&GlobalTransform, &BallState, &mut Velocity), With<Ball>>,
+) {
+    let reaching = characters
+        .iter()
+        .filter(|(_, state, action_state, buffer)| {
+            state.is_free()
+                && (action_state.pressed(Action::Pickup)
+                    || buffer.pending(action_state, Action::Pickup))
+        })
+        .map(|(transform, ..)| transform.translation())
+        .collect::<Vec<_>>();
+    if reaching.is_empty() {
+        return;
+    }
+    for (ball_transform, state, mut velocity) in &mut balls {
+        if *state != BallState::Dead {
+            continue;
+        }
+        let ball_position = ball_transform.translation();
+        let nearest = reaching
+            .iter()
+            .map(|position| (*position - ball_position).truncate())
+            .filter(|offset| offset.length() <= settings.magnet_radius)
+            .min_by(|a, b| a.length_squared().total_cmp(&b.length_squared()));
+        if let Some(offset) = nearest {
+            let pull = offset.normalize_or_zero() * settings.magnet_pull;
+            velocity.linvel += (pull * time.delta_seconds()).extend(0.0);
+        }
+    }
+}
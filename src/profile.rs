@@ -0,0 +1,89 @@
+use std::{collections::BTreeMap, fs};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::keyboard_layout::KeyboardLayout;
+
+/// Where the profile is kept, relative to the working directory.
+const PROFILE_PATH: &str = "profile.ron";
+/// How many target practice scores are kept.
+const HIGH_SCORE_COUNT: usize = 5;
+
+pub struct ProfilePlugin;
+
+impl Plugin for ProfilePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Profile::load());
+    }
+}
+
+/// Progress that carries over between sessions.
+#[derive(Serialize, Deserialize, Default)]
+pub struct Profile {
+    /// The best medal earned in each challenge, by challenge id.
+    #[serde(default)]
+    pub medals: BTreeMap<String, Medal>,
+    /// The best target practice scores, highest first.
+    #[serde(default)]
+    pub target_scores: Vec<u32>,
+    /// The keyboard layout default bindings are laid out for, once it's been detected or picked.
+    #[serde(default)]
+    pub keyboard_layout: Option<KeyboardLayout>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Medal {
+    Bronze,
+    Silver,
+    Gold,
+}
+
+impl Profile {
+    fn load() -> Self {
+        let bytes = if let Ok(bytes) = fs::read(PROFILE_PATH) {
+            bytes
+        } else {
+            return Self::default();
+        };
+        ron::de::from_bytes(&bytes).unwrap_or_else(|error| {
+            warn!("Couldn't parse the profile, starting a new one: {error}");
+            Self::default()
+        })
+    }
+
+    pub fn save(&self) {
+        let result = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|error| error.to_string())
+            .and_then(|ron| fs::write(PROFILE_PATH, ron).map_err(|error| error.to_string()));
+        if let Err(error) = result {
+            warn!("Couldn't save the profile: {error}");
+        }
+    }
+
+    /// Keeps the medal if it beats the best one earned in the challenge so far, and returns
+    /// whether it did.
+    pub fn award(&mut self, challenge: &str, medal: Medal) -> bool {
+        if self
+            .medals
+            .get(challenge)
+            .map_or(false, |best| *best >= medal)
+        {
+            return false;
+        }
+        self.medals.insert(challenge.to_string(), medal);
+        true
+    }
+
+    /// Adds a target practice score to the high scores if it's good enough, and returns where it
+    /// placed.
+    pub fn record_target_score(&mut self, score: u32) -> Option<usize> {
+        let rank = self.target_scores.partition_point(|best| *best >= score);
+        if rank >= HIGH_SCORE_COUNT || score == 0 {
+            return None;
+        }
+        self.target_scores.insert(rank, score);
+        self.target_scores.truncate(HIGH_SCORE_COUNT);
+        Some(rank)
+    }
+}
@@ -0,0 +1,176 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::{
+    character::{CharacterState, StateChanged},
+    drop_held_ball,
+    game_time::GameTime,
+    graphics::SpriteDepth,
+    hit::{apply_hits, Health, Hit},
+    rules::{match_active, Ruleset},
+    stage::ActiveStage,
+    status::{StatusEffect, StatusEffects},
+    team::Team,
+    tick_timer::TickTimer,
+    HoldingBall, Player,
+};
+
+/// How long respawned players can't be hit, so they can't be picked off as they come back.
+const INVINCIBLE_SECONDS: f32 = 2.0;
+/// How long respawned players' sprites stay shown, then hidden, while they're invincible.
+const BLINK_SECONDS: f32 = 0.1;
+/// Height players respawn at, as when they are first spawned.
+const SPAWN_HEIGHT: f32 = 0.25;
+
+pub struct RespawnPlugin;
+
+impl Plugin for RespawnPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::new()
+                .with_run_criteria(match_active)
+                .with_system(flag_hit_players.after(apply_hits))
+                .with_system(send_off_hit_players)
+                .with_system(respawn)
+                .with_system(blink_invincible),
+        );
+    }
+}
+
+/// Marks a respawned player whose sprite blinks for as long as they can't be hit.
+#[derive(Component, Default)]
+struct Blinking {
+    elapsed: f32,
+}
+
+/// Marks a hit player with lives to spare, who is sent off once the hit's stun wears off.
+#[derive(Component)]
+struct SendingOff;
+
+/// With lives to spare, a hit stuns and knocks the player back as usual, then sends them off.
+fn flag_hit_players(
+    mut commands: Commands,
+    ruleset: Res<Ruleset>,
+    mut hits: EventReader<Hit>,
+    players: Query<&Health, With<Player>>,
+) {
+    if ruleset.lives <= 1 {
+        return;
+    }
+    for hit in hits.iter() {
+        if players
+            .get(hit.player)
+            .map_or(false, |health| health.current > 0)
+        {
+            commands.entity(hit.player).insert(SendingOff);
+        }
+    }
+}
+
+/// Takes hit players off the court until they respawn once their stun is over, dropping any
+/// ball they were holding where they stood.
+fn send_off_hit_players(
+    mut commands: Commands,
+    ruleset: Res<Ruleset>,
+    mut events: EventReader<StateChanged>,
+    mut players: Query<
+        (
+            &mut CharacterState,
+            &Transform,
+            &mut Velocity,
+            &mut Visibility,
+            Option<&HoldingBall>,
+        ),
+        (With<Player>, With<SendingOff>),
+    >,
+) {
+    for event in events.iter() {
+        if !matches!(event.from, CharacterState::Stunned(_)) {
+            continue;
+        }
+        let (mut state, transform, mut velocity, mut visibility, held) =
+            if let Ok(player) = players.get_mut(event.entity) {
+                player
+            } else {
+                continue;
+            };
+        commands.entity(event.entity).remove::<SendingOff>();
+        // Eliminated while still stunned, so they're out rather than respawning.
+        if matches!(
+            *state,
+            CharacterState::Eliminated | CharacterState::Respawning(_)
+        ) {
+            continue;
+        }
+        if let Some(held) = held {
+            drop_held_ball(event.entity, held, transform.translation, &mut commands);
+        }
+        *state = CharacterState::Respawning(TickTimer::from_seconds(ruleset.respawn_seconds));
+        *velocity = Velocity::zero();
+        visibility.is_visible = false;
+        commands
+            .entity(event.entity)
+            .insert_bundle((RigidBody::Fixed, CollisionGroups::new(0, 0)));
+    }
+}
+
+/// Brings players back at their team's spawn point once they've sat out long enough.
+fn respawn(
+    mut commands: Commands,
+    time: Res<GameTime>,
+    stage: Option<Res<ActiveStage>>,
+    mut players: Query<(
+        Entity,
+        &Team,
+        &mut CharacterState,
+        &mut Transform,
+        &mut Visibility,
+        &mut StatusEffects,
+    )>,
+) {
+    for (entity, team, mut state, mut transform, mut visibility, mut effects) in &mut players {
+        let mut timer = if let CharacterState::Respawning(timer) = *state {
+            timer
+        } else {
+            continue;
+        };
+        if !timer.tick(time.ticks()).finished() {
+            *state = CharacterState::Respawning(timer);
+            continue;
+        }
+        let spawn = stage
+            .as_ref()
+            .map_or(Vec2::ZERO, |stage| stage.spawn(team.0));
+        *state = CharacterState::Idle;
+        transform.translation = spawn.extend(SPAWN_HEIGHT);
+        visibility.is_visible = true;
+        effects.add(StatusEffect::Invulnerable, Some(INVINCIBLE_SECONDS));
+        commands
+            .entity(entity)
+            .remove::<CollisionGroups>()
+            .insert_bundle((RigidBody::Dynamic, Blinking::default()));
+    }
+}
+
+fn blink_invincible(
+    mut commands: Commands,
+    time: Res<GameTime>,
+    mut players: Query<(Entity, &mut Blinking, &StatusEffects, &Children)>,
+    mut sprites: Query<&mut Visibility, With<SpriteDepth>>,
+) {
+    for (entity, mut blinking, effects, children) in &mut players {
+        blinking.elapsed += time.delta_seconds();
+        let invincible = effects.contains(StatusEffect::Invulnerable);
+        let shown = !invincible || (blinking.elapsed / BLINK_SECONDS) as u32 % 2 == 0;
+        for child in children.iter() {
+            if let Ok(mut visibility) = sprites.get_mut(*child) {
+                if visibility.is_visible != shown {
+                    visibility.is_visible = shown;
+                }
+            }
+        }
+        if !invincible {
+            commands.entity(entity).remove::<Blinking>();
+        }
+    }
+}
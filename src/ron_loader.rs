@@ -0,0 +1,39 @@
+use std::marker::PhantomData;
+
+use bevy::{
+    asset::{Asset, AssetLoader, LoadContext, LoadedAsset},
+    utils::BoxedFuture,
+};
+use serde::de::DeserializeOwned;
+
+/// An asset read straight out of a RON file, told apart from other RON files by its extension.
+pub trait RonAsset: Asset + DeserializeOwned {
+    const EXTENSIONS: &'static [&'static str];
+}
+
+/// Loads any [`RonAsset`], registered once per asset type.
+pub struct RonLoader<A>(PhantomData<fn() -> A>);
+
+impl<A> Default for RonLoader<A> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<A: RonAsset> AssetLoader for RonLoader<A> {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let asset: A = ron::de::from_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(asset));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        A::EXTENSIONS
+    }
+}
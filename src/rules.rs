@@ -0,0 +1,277 @@
+use bevy::{ecs::schedule::ShouldRun, prelude::*, utils::HashMap};
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ball_spawner::BallSpawnPattern,
+    celebration::MatchOver,
+    character::CharacterState,
+    status::{StatusEffect, StatusEffects},
+    team::Team,
+    Ball, BallState, GameState, Player, ThrownBy,
+};
+
+/// How close an incoming ball has to be before a comeback slow takes hold of it.
+const SLOW_RADIUS: f32 = 2.0;
+
+pub struct RulesPlugin;
+
+impl Plugin for RulesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Ruleset>()
+            .add_system_set(SystemSet::on_enter(GameState::Ready).with_system(start_match))
+            .add_system_set(SystemSet::on_exit(GameState::Ready).with_system(end_match))
+            .add_system(decide_match)
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(match_active)
+                    .with_system(last_stand)
+                    .with_system(slow_incoming_balls.after(last_stand)),
+            );
+    }
+}
+
+/// Present while a match is being played, from when the court is set up until it is decided.
+pub struct MatchActive;
+
+/// Runs systems only while a match is being played.
+pub fn match_active(active: Option<Res<MatchActive>>) -> ShouldRun {
+    if active.is_some() {
+        ShouldRun::Yes
+    } else {
+        ShouldRun::No
+    }
+}
+
+fn start_match(mut commands: Commands) {
+    commands.insert_resource(MatchActive);
+}
+
+fn end_match(mut commands: Commands) {
+    commands.remove_resource::<MatchActive>();
+}
+
+fn decide_match(mut commands: Commands, mut events: EventReader<MatchOver>) {
+    if events.iter().last().is_some() {
+        commands.remove_resource::<MatchActive>();
+    }
+}
+
+/// Match rules that can be changed between matches.
+pub struct Ruleset {
+    pub mode: MatchMode,
+    /// The help given to a team that is down to its last player.
+    pub comeback: Option<Comeback>,
+    pub headshots: Headshots,
+    /// Ranked and tournament matches, where nothing may tip the balance between players.
+    pub competitive: bool,
+    /// Hits each player can take before they are eliminated.
+    pub lives: u32,
+    /// With more than one life, how long a hit player sits out before coming back at their
+    /// team's spawn point.
+    pub respawn_seconds: f32,
+    /// Balls in play throughout a match. Lost balls are replaced to keep it at this.
+    pub ball_count: usize,
+    pub ball_spawns: BallSpawnPattern,
+    /// Seeds where balls are placed, for replaying a match exactly. Random if unset.
+    pub ball_seed: Option<u64>,
+    pub ball_clashes: Clashes,
+    pub blocks: Blocks,
+    /// Whether a teammate's throw can hit, rather than passing harmlessly.
+    pub friendly_fire: bool,
+    /// Whether special balls are dropped in every so often.
+    pub power_ups: bool,
+    /// Wall bounces a throw can take and still be live, for bank shots around blockers.
+    pub ricochets: u32,
+    /// Start with balls on the center line and players at their back walls, racing for them at GO.
+    pub opening_rush: bool,
+    /// Eliminated players wait in a jail behind the other team's court, and each catch brings
+    /// back the catcher's teammate who has been in there longest.
+    pub jail: bool,
+}
+
+impl Default for Ruleset {
+    fn default() -> Self {
+        Self {
+            mode: MatchMode::Versus,
+            comeback: Some(Comeback::SlowBalls(0.85)),
+            headshots: Headshots::Knockback(2.0),
+            competitive: false,
+            lives: 1,
+            respawn_seconds: 2.0,
+            ball_count: 3,
+            ball_spawns: BallSpawnPattern::Mirrored,
+            ball_seed: None,
+            ball_clashes: Clashes::Deflect,
+            blocks: Blocks::KeepBall,
+            friendly_fire: false,
+            power_ups: true,
+            ricochets: 0,
+            opening_rush: true,
+            jail: false,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MatchMode {
+    Versus,
+    /// Fending off waves of bots, ending in a boss fight.
+    Survival,
+    /// Practising alone, with an overlay of the inputs being read.
+    Training,
+    /// Playing one of the authored challenges, by its index in the challenge list.
+    Challenge(usize),
+    /// Throwing at targets that pop up across the court against the clock, for a high score.
+    TargetPractice,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Comeback {
+    /// Scales the speed of balls thrown at the last player standing.
+    SlowBalls(f32),
+}
+
+impl Comeback {
+    fn effect(self) -> StatusEffect {
+        match self {
+            Comeback::SlowBalls(scale) => StatusEffect::SlowIncoming(scale),
+        }
+    }
+}
+
+/// What happens when a ball hits a player on the head.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Headshots {
+    /// Headshots are no different from other hits.
+    Off,
+    /// Multiplies the knockback of the hit.
+    Knockback(f32),
+    /// Takes the player out of the match on the spot.
+    Eliminate,
+}
+
+/// What happens when two thrown balls meet in the air.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Clashes {
+    /// Both balls bounce off each other at full speed.
+    Deflect,
+    /// The heavier ball carries on as if nothing happened and the other one drops dead. Balls
+    /// of the same weight both drop.
+    HeavierWins,
+    /// Both balls drop out of the air.
+    BothDead,
+}
+
+/// Whether a player holding a ball can block throws with it, by pressing catch.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Blocks {
+    Off,
+    /// The blocked ball drops dead and the blocker keeps hold of theirs.
+    KeepBall,
+    /// Both balls drop to the floor.
+    DropBall,
+}
+
+/// Marks a player who is the last one standing on their team.
+#[derive(Component)]
+pub struct LastStand(Comeback);
+
+fn last_stand(
+    mut commands: Commands,
+    ruleset: Res<Ruleset>,
+    mut team_sizes: Local<HashMap<Team, usize>>,
+    mut players: Query<
+        (
+            Entity,
+            &Team,
+            &CharacterState,
+            &mut StatusEffects,
+            Option<&LastStand>,
+        ),
+        With<Player>,
+    >,
+) {
+    let mut counts = HashMap::<Team, usize>::default();
+    for (_, team, state, _, _) in &players {
+        if state.is_active() {
+            *counts.entry(*team).or_default() += 1;
+        }
+    }
+    for (team, count) in &counts {
+        let size = team_sizes.entry(*team).or_default();
+        *size = (*size).max(*count);
+    }
+
+    for (entity, team, state, mut effects, last_stand) in &mut players {
+        if !state.is_active() {
+            continue;
+        }
+        let alone = counts.get(team) == Some(&1) && team_sizes.get(team) > Some(&1);
+        let comeback = ruleset.comeback.filter(|_| alone);
+        match (last_stand, comeback) {
+            (Some(LastStand(current)), Some(comeback)) if *current == comeback => {}
+            (current, comeback) => {
+                if let Some(LastStand(current)) = current {
+                    effects.remove(current.effect());
+                    commands.entity(entity).remove::<LastStand>();
+                }
+                if let Some(comeback) = comeback {
+                    info!("{team:?} is down to its last player");
+                    effects.add(comeback.effect(), None);
+                    commands.entity(entity).insert(LastStand(comeback));
+                }
+            }
+        }
+    }
+}
+
+/// Marks a live ball that has already been slowed down on its way to a player, during the throw
+/// of whoever it holds. The ball can be slowed again once it's someone else's throw.
+#[derive(Component)]
+pub struct Slowed(Entity);
+
+/// Slows opposing throws as they close in on a player with a comeback slow.
+fn slow_incoming_balls(
+    mut commands: Commands,
+    mut balls: Query<
+        (
+            Entity,
+            &GlobalTransform,
+            &mut Velocity,
+            &BallState,
+            Option<&ThrownBy>,
+            Option<&Slowed>,
+        ),
+        With<Ball>,
+    >,
+    players: Query<(&GlobalTransform, &Team, &StatusEffects), With<Player>>,
+    teams: Query<&Team>,
+) {
+    for (ball, ball_transform, mut velocity, state, thrown_by, slowed) in &mut balls {
+        let thrower = match (state, thrown_by) {
+            (BallState::Live, Some(ThrownBy(thrower))) => *thrower,
+            _ => continue,
+        };
+        match slowed {
+            Some(Slowed(slowed_throw)) if *slowed_throw == thrower => continue,
+            Some(_) => {
+                commands.entity(ball).remove::<Slowed>();
+            }
+            None => {}
+        }
+        let thrower_team = teams.get(thrower).ok();
+        for (player_transform, team, effects) in &players {
+            let scale = effects.incoming_ball_scale();
+            if (scale - 1.0).abs() < f32::EPSILON || thrower_team == Some(team) {
+                continue;
+            }
+            let offset = player_transform.translation() - ball_transform.translation();
+            if offset.length() <= SLOW_RADIUS && velocity.linvel.dot(offset) > 0.0 {
+                velocity.linvel *= scale;
+                commands.entity(ball).insert(Slowed(thrower));
+                break;
+            }
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{catch::Caught, hit::Hit, rules::match_active, team::Team};
+
+pub struct ScorePlugin;
+
+impl Plugin for ScorePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Score>()
+            .add_system(score_hits.with_run_criteria(match_active))
+            .add_system(score_catches.with_run_criteria(match_active));
+    }
+}
+
+/// Points each team has scored in the current match.
+#[derive(Default)]
+pub struct Score(pub HashMap<Team, u32>);
+
+impl Score {
+    pub fn get(&self, team: Team) -> u32 {
+        self.0.get(&team).copied().unwrap_or_default()
+    }
+}
+
+fn score_hits(mut score: ResMut<Score>, mut hits: EventReader<Hit>, teams: Query<&Team>) {
+    for hit in hits.iter() {
+        let thrower = hit.thrower.and_then(|thrower| teams.get(thrower).ok());
+        if let (Some(thrower), Ok(target)) = (thrower, teams.get(hit.player)) {
+            if thrower != target {
+                *score.0.entry(*thrower).or_default() += 1;
+            }
+        }
+    }
+}
+
+/// Catching a ball thrown by the other team is worth as much as hitting someone with it.
+fn score_catches(mut score: ResMut<Score>, mut caught: EventReader<Caught>, teams: Query<&Team>) {
+    for catch in caught.iter() {
+        let thrower = catch.thrower.and_then(|thrower| teams.get(thrower).ok());
+        if let (Some(thrower), Ok(catcher)) = (thrower, teams.get(catch.player)) {
+            if thrower != catcher {
+                *score.0.entry(*catcher).or_default() += 1;
+            }
+        }
+    }
+}
@@ -0,0 +1,64 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::{player_control, rules::match_active, Player};
+
+/// Solver group of players. They don't collide with each other physically, since the solver can
+/// launch a player standing on another one, so they're pushed apart by [`separate_players`]
+/// instead.
+pub const PLAYER_GROUP: u32 = 1 << 2;
+/// Solver group of balls that aren't live. Players walk through these rather than getting
+/// wedged against them.
+pub const DEAD_BALL_GROUP: u32 = 1 << 3;
+/// How close two players can get before they start pushing each other apart.
+const SEPARATION_DISTANCE: f32 = 0.5;
+/// How hard overlapping players push apart, in metres per second per metre of overlap.
+const SEPARATION_STIFFNESS: f32 = 12.0;
+
+pub struct SeparationPlugin;
+
+impl Plugin for SeparationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(
+            separate_players
+                .with_run_criteria(match_active)
+                .after(player_control),
+        );
+    }
+}
+
+/// The contact settings every player is spawned with.
+pub fn player_contacts() -> (SolverGroups, Restitution) {
+    (
+        SolverGroups::new(PLAYER_GROUP, !(PLAYER_GROUP | DEAD_BALL_GROUP)),
+        Restitution {
+            coefficient: 0.0,
+            combine_rule: CoefficientCombineRule::Min,
+        },
+    )
+}
+
+/// Pushes overlapping players apart along the floor, so neither can be stood on and launched.
+fn separate_players(mut players: Query<(Entity, &GlobalTransform, &mut Velocity), With<Player>>) {
+    let mut pairs = players.iter_combinations_mut();
+    while let Some([(a, a_transform, mut a_velocity), (b, b_transform, mut b_velocity)]) =
+        pairs.fetch_next()
+    {
+        let offset = (a_transform.translation() - b_transform.translation()).truncate();
+        let distance = offset.length();
+        if distance >= SEPARATION_DISTANCE {
+            continue;
+        }
+        // Players stacked right on top of each other split along the court's width.
+        let direction = if distance > f32::EPSILON {
+            offset / distance
+        } else if a < b {
+            Vec2::X
+        } else {
+            Vec2::NEG_X
+        };
+        let push = direction * (SEPARATION_DISTANCE - distance) * SEPARATION_STIFFNESS / 2.0;
+        a_velocity.linvel += push.extend(0.0);
+        b_velocity.linvel -= push.extend(0.0);
+    }
+}
@@ -0,0 +1,89 @@
+use std::collections::VecDeque;
+
+use bevy::{ecs::event::Event, prelude::*};
+
+use crate::{camera::FlyCamera, tween::Ease};
+
+pub struct SequencePlugin;
+
+impl Plugin for SequencePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(run_sequences);
+    }
+}
+
+/// A chain of timed steps for scripted moments, played back by inserting it on an entity.
+///
+/// Steps only queue commands, so sequences run alongside everything else. The entity is
+/// despawned once the last step has finished.
+#[derive(Component, Default)]
+pub struct Sequence {
+    steps: VecDeque<Step>,
+    elapsed: f32,
+}
+
+enum Step {
+    Wait(f32),
+    Run(Box<dyn FnOnce(&mut Commands) + Send + Sync>),
+}
+
+impl Sequence {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn wait(mut self, seconds: f32) -> Self {
+        self.steps.push_back(Step::Wait(seconds));
+        self
+    }
+
+    /// Runs `step` once, then moves straight on to the next step.
+    pub fn then(mut self, step: impl FnOnce(&mut Commands) + Send + Sync + 'static) -> Self {
+        self.steps.push_back(Step::Run(Box::new(step)));
+        self
+    }
+
+    pub fn send<E: Event>(self, event: E) -> Self {
+        self.then(move |commands| {
+            commands.add(move |world: &mut World| world.resource_mut::<Events<E>>().send(event));
+        })
+    }
+
+    /// Flies the game camera from wherever it is when the step starts to `to`, moving on once it
+    /// gets there.
+    pub fn move_camera(self, to: Transform, seconds: f32, ease: Ease) -> Self {
+        self.send(FlyCamera { to, seconds, ease }).wait(seconds)
+    }
+
+    /// Advances the sequence by `delta` seconds, returning whether it has finished.
+    fn advance(&mut self, commands: &mut Commands, mut delta: f32) -> bool {
+        while let Some(step) = self.steps.pop_front() {
+            match step {
+                Step::Wait(seconds) => {
+                    let remaining = seconds - self.elapsed;
+                    if delta < remaining {
+                        self.elapsed += delta;
+                        self.steps.push_front(step);
+                        return false;
+                    }
+                    delta -= remaining;
+                }
+                Step::Run(run) => run(commands),
+            }
+            self.elapsed = 0.0;
+        }
+        true
+    }
+}
+
+fn run_sequences(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut sequences: Query<(Entity, &mut Sequence)>,
+) {
+    for (entity, mut sequence) in &mut sequences {
+        if sequence.advance(&mut commands, time.delta_seconds()) {
+            commands.entity(entity).despawn();
+        }
+    }
+}
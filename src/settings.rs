@@ -0,0 +1,252 @@
+use std::fs;
+
+use bevy::{
+    prelude::*,
+    window::{PresentMode, WindowMode},
+};
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Where settings are kept, relative to the working directory.
+const SETTINGS_PATH: &str = "settings.ron";
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        let settings = Settings::load();
+        app.insert_resource(FirstRun(settings.is_none()))
+            .insert_resource(settings.unwrap_or_default())
+            .add_system(apply_settings);
+    }
+}
+
+/// Whether there was no settings file at launch, so the player still has to set the game up.
+pub struct FirstRun(pub bool);
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub language: Language,
+    /// The device the first player plays with.
+    pub controls: ControlPreset,
+    pub video_mode: VideoMode,
+    /// Volume of everything the game plays, from silent at zero to full at one.
+    pub volume: f32,
+    pub vsync: bool,
+    pub debug_physics: bool,
+    pub pixel_text: bool,
+    pub pixel_perfect: bool,
+    pub sprite_alpha_cutoff: bool,
+    pub minimap: bool,
+    /// How each local player's movement stick is read, by player slot.
+    pub sticks: Vec<StickSettings>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            language: Language::from_system().unwrap_or_default(),
+            controls: ControlPreset::Keyboard,
+            video_mode: VideoMode::Windowed,
+            volume: 1.0,
+            vsync: true,
+            debug_physics: true,
+            pixel_text: true,
+            pixel_perfect: false,
+            sprite_alpha_cutoff: false,
+            minimap: true,
+            sticks: Vec::new(),
+        }
+    }
+}
+
+impl Settings {
+    fn load() -> Option<Self> {
+        let bytes = fs::read(SETTINGS_PATH).ok()?;
+        Some(ron::de::from_bytes(&bytes).unwrap_or_else(|error| {
+            warn!("Couldn't parse the settings, using the defaults: {error}");
+            Self::default()
+        }))
+    }
+
+    pub fn save(&self) {
+        let result = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|error| error.to_string())
+            .and_then(|ron| fs::write(SETTINGS_PATH, ron).map_err(|error| error.to_string()));
+        if let Err(error) = result {
+            warn!("Couldn't save the settings: {error}");
+        }
+    }
+
+    pub fn stick(&self, slot: usize) -> StickSettings {
+        self.sticks.get(slot).copied().unwrap_or_default()
+    }
+
+    pub fn stick_mut(&mut self, slot: usize) -> &mut StickSettings {
+        if self.sticks.len() <= slot {
+            self.sticks.resize(slot + 1, StickSettings::default());
+        }
+        &mut self.sticks[slot]
+    }
+}
+
+/// Languages the game can be set to. Only English text exists so far, so the choice is kept for
+/// when translations arrive.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum Language {
+    #[default]
+    English,
+    Spanish,
+    French,
+    German,
+    Portuguese,
+}
+
+impl Language {
+    pub const ALL: [Self; 5] = [
+        Self::English,
+        Self::Spanish,
+        Self::French,
+        Self::German,
+        Self::Portuguese,
+    ];
+
+    /// The language's name in itself, so it can be found by someone who reads it.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::English => "English",
+            Self::Spanish => "Español",
+            Self::French => "Français",
+            Self::German => "Deutsch",
+            Self::Portuguese => "Português",
+        }
+    }
+
+    /// The language of the system, from a locale like `fr_CA.UTF-8`, if it's one on offer.
+    pub fn from_system() -> Option<Self> {
+        let locale = std::env::var("LC_ALL")
+            .or_else(|_| std::env::var("LANG"))
+            .ok()?;
+        match locale.get(..2)? {
+            "en" => Some(Self::English),
+            "es" => Some(Self::Spanish),
+            "fr" => Some(Self::French),
+            "de" => Some(Self::German),
+            "pt" => Some(Self::Portuguese),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ControlPreset {
+    /// Controllers that get connected go to the other players.
+    Keyboard,
+    /// The first player takes the first controller connected.
+    Gamepad,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum VideoMode {
+    Windowed,
+    Borderless,
+    Fullscreen,
+}
+
+impl VideoMode {
+    pub const ALL: [Self; 3] = [Self::Windowed, Self::Borderless, Self::Fullscreen];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Windowed => "Windowed",
+            Self::Borderless => "Borderless",
+            Self::Fullscreen => "Fullscreen",
+        }
+    }
+
+    fn window_mode(self) -> WindowMode {
+        match self {
+            Self::Windowed => WindowMode::Windowed,
+            Self::Borderless => WindowMode::BorderlessFullscreen,
+            Self::Fullscreen => WindowMode::Fullscreen,
+        }
+    }
+}
+
+/// How a player's movement stick is turned into movement, for controllers that drift or feel
+/// too twitchy.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct StickSettings {
+    /// How far the stick has to be pushed before the character moves at all.
+    pub deadzone: f32,
+    pub response: StickResponse,
+    pub invert_x: bool,
+    pub invert_y: bool,
+}
+
+impl Default for StickSettings {
+    fn default() -> Self {
+        Self {
+            deadzone: 0.15,
+            response: StickResponse::Linear,
+            invert_x: false,
+            invert_y: false,
+        }
+    }
+}
+
+/// How movement speed ramps up as the stick is pushed further.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum StickResponse {
+    Linear,
+    /// Slow near the center, for careful positioning.
+    Precise,
+    /// Quick to reach full speed.
+    Aggressive,
+}
+
+impl StickSettings {
+    /// Turns a raw stick position into a movement input no longer than 1. Movement ramps up from
+    /// nothing at the edge of the deadzone, so there's no jump in speed once it's passed.
+    pub fn apply(&self, raw: Vec2) -> Vec2 {
+        let mut stick = raw;
+        if self.invert_x {
+            stick.x = -stick.x;
+        }
+        if self.invert_y {
+            stick.y = -stick.y;
+        }
+        let length = stick.length();
+        if length <= self.deadzone {
+            return Vec2::ZERO;
+        }
+        let scaled = ((length - self.deadzone) / (1.0 - self.deadzone)).min(1.0);
+        let curved = match self.response {
+            StickResponse::Linear => scaled,
+            StickResponse::Precise => scaled * scaled,
+            StickResponse::Aggressive => scaled.sqrt(),
+        };
+        stick / length * curved
+    }
+}
+
+fn apply_settings(
+    settings: Res<Settings>,
+    mut windows: ResMut<Windows>,
+    mut debug_render: ResMut<DebugRenderContext>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+    if let Some(window) = windows.get_primary_mut() {
+        window.set_present_mode(if settings.vsync {
+            PresentMode::Fifo
+        } else {
+            PresentMode::Immediate
+        });
+        window.set_mode(settings.video_mode.window_mode());
+    }
+    debug_render.enabled = settings.debug_physics;
+}
@@ -0,0 +1,153 @@
+use bevy::prelude::*;
+use bevy_asset_loader::prelude::*;
+
+use crate::{
+    celebration::MatchOver,
+    clash::BallClash,
+    hit::Hit,
+    settings::Settings,
+    stage::{ActiveStage, AmbienceLayer},
+};
+
+/// How long one stage's ambience takes to fade into the next one's.
+const CROSSFADE_SECONDS: f32 = 1.5;
+
+pub struct SoundPlugin;
+
+impl Plugin for SoundPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AmbienceBus>()
+            .init_resource::<Ambience>()
+            .add_system(play_sounds)
+            .add_system(change_ambience)
+            .add_system(fade_ambience.after(change_ambience));
+    }
+}
+
+/// Mixes every ambience layer together, so background sound can be turned down as a whole
+/// without touching effects.
+pub struct AmbienceBus {
+    pub volume: f32,
+}
+
+impl Default for AmbienceBus {
+    fn default() -> Self {
+        Self { volume: 1.0 }
+    }
+}
+
+/// The ambience layers playing right now, including ones still fading out.
+#[derive(Default)]
+struct Ambience {
+    layers: Vec<PlayingLayer>,
+}
+
+struct PlayingLayer {
+    layer: AmbienceLayer,
+    sink: Handle<AudioSink>,
+    /// From silent at zero to full volume at one.
+    fade: f32,
+    fading_in: bool,
+}
+
+#[derive(AssetCollection)]
+pub struct AudioAssets {
+    #[asset(path = "audio/hit.ogg")]
+    pub hit: Handle<AudioSource>,
+    #[asset(path = "audio/clash.ogg")]
+    pub clash: Handle<AudioSource>,
+    #[asset(path = "audio/whistle.ogg")]
+    pub whistle: Handle<AudioSource>,
+}
+
+fn play_sounds(
+    audio: Res<Audio>,
+    settings: Res<Settings>,
+    sounds: Option<Res<AudioAssets>>,
+    mut hits: EventReader<Hit>,
+    mut clashes: EventReader<BallClash>,
+    mut match_over: EventReader<MatchOver>,
+) {
+    let sounds = if let Some(sounds) = sounds {
+        sounds
+    } else {
+        return;
+    };
+    let playback = PlaybackSettings::ONCE.with_volume(settings.volume);
+    for _ in hits.iter() {
+        audio.play_with_settings(sounds.hit.clone(), playback.clone());
+    }
+    for _ in clashes.iter() {
+        audio.play_with_settings(sounds.clash.clone(), playback.clone());
+    }
+    if match_over.iter().last().is_some() {
+        audio.play_with_settings(sounds.whistle.clone(), playback);
+    }
+}
+
+/// Starts the new stage's layers fading in and the old ones fading out whenever the stage
+/// changes. Layers both stages share carry on uninterrupted.
+fn change_ambience(
+    asset_server: Res<AssetServer>,
+    audio: Res<Audio>,
+    sinks: Res<Assets<AudioSink>>,
+    stage: Option<Res<ActiveStage>>,
+    mut ambience: ResMut<Ambience>,
+) {
+    let wanted: &[AmbienceLayer] = match &stage {
+        Some(stage) if stage.is_changed() => stage.ambience.as_slice(),
+        Some(_) => return,
+        None if ambience.layers.iter().any(|playing| playing.fading_in) => &[],
+        None => return,
+    };
+    for playing in &mut ambience.layers {
+        playing.fading_in = wanted.contains(&playing.layer);
+    }
+    for layer in wanted {
+        if ambience
+            .layers
+            .iter()
+            .any(|playing| playing.fading_in && playing.layer == *layer)
+        {
+            continue;
+        }
+        let sink = audio.play_with_settings(
+            asset_server.load(layer.sound.as_str()),
+            PlaybackSettings::LOOP.with_volume(0.0),
+        );
+        ambience.layers.push(PlayingLayer {
+            layer: layer.clone(),
+            sink: sinks.get_handle(sink),
+            fade: 0.0,
+            fading_in: true,
+        });
+    }
+}
+
+fn fade_ambience(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    bus: Res<AmbienceBus>,
+    sinks: Res<Assets<AudioSink>>,
+    mut ambience: ResMut<Ambience>,
+) {
+    let step = time.delta_seconds() / CROSSFADE_SECONDS;
+    ambience.layers.retain_mut(|playing| {
+        playing.fade = if playing.fading_in {
+            (playing.fade + step).min(1.0)
+        } else {
+            (playing.fade - step).max(0.0)
+        };
+        let sink = sinks.get(&playing.sink);
+        if let Some(sink) = sink {
+            sink.set_volume(playing.layer.volume * bus.volume * settings.volume * playing.fade);
+        }
+        let silent = !playing.fading_in && playing.fade <= 0.0;
+        if silent {
+            if let Some(sink) = sink {
+                sink.stop();
+            }
+        }
+        !silent
+    });
+}
@@ -0,0 +1,151 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::{rules::match_active, Ball, Player};
+
+/// Fastest a ball may travel. Rallies and curves stack up speed, and past this even continuous
+/// collision detection gets unreliable against thin obstacles.
+const MAX_BALL_SPEED: f32 = 32.0;
+/// Fastest a player may be knocked around.
+const MAX_PLAYER_SPEED: f32 = 20.0;
+
+pub struct SpeedLimitPlugin;
+
+/// Runs after every gameplay system has had its say, right before velocities go to physics.
+#[derive(StageLabel, Debug, Clone, PartialEq, Eq, Hash)]
+struct SpeedLimitStage;
+
+impl Plugin for SpeedLimitPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_stage_before(
+            PhysicsStages::SyncBackend,
+            SpeedLimitStage,
+            SystemStage::single(clamp_velocities.with_run_criteria(match_active)),
+        );
+    }
+}
+
+fn clamp_velocities(
+    mut balls: Query<&mut Velocity, (With<Ball>, Without<Player>)>,
+    mut players: Query<&mut Velocity, (With<Player>, Without<Ball>)>,
+) {
+    for mut velocity in &mut balls {
+        clamp(&mut velocity, MAX_BALL_SPEED);
+    }
+    for mut velocity in &mut players {
+        clamp(&mut velocity, MAX_PLAYER_SPEED);
+    }
+}
+
+fn clamp(velocity: &mut Velocity, max_speed: f32) {
+    if velocity.linvel.length_squared() > max_speed * max_speed {
+        velocity.linvel = velocity.linvel.clamp_length_max(max_speed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        asset::AssetPlugin, hierarchy::HierarchyPlugin, prelude::*, transform::TransformPlugin,
+    };
+    use bevy_rapier3d::prelude::*;
+
+    use super::MAX_BALL_SPEED;
+    use crate::{ball_kinds::BallKindTable, interactions::BallKind, throw::MAX_THROW_SPEED};
+
+    /// Generated arenas scatter obstacles no thinner than this, and authored stages have none
+    /// thinner either.
+    const THINNEST_WALL: f32 = 0.4;
+    const BALL_RADIUS: f32 = 0.1;
+    const STEP_SECONDS: f32 = 1.0 / 60.0;
+    /// How far from the wall the ball starts, and how many steps it gets to get through it.
+    const START_DISTANCE: f32 = 2.0;
+    const STEPS: usize = 60;
+    /// Far faster than any ball goes, covering 1.5 m a step. Starting where it does, the ball is
+    /// never touching the wall at the end of a step, so only CCD can stop it.
+    const CONTROL_SPEED: f32 = 90.0;
+
+    /// The most any kind of ball speeds up a throw, going by the default ball table.
+    fn fastest_kind() -> f32 {
+        let table: BallKindTable =
+            ron::de::from_str(include_str!("../assets/rules/default.balls.ron")).unwrap();
+        [
+            BallKind::Standard,
+            BallKind::Heavy,
+            BallKind::Speed,
+            BallKind::Split,
+            BallKind::Fragment,
+            BallKind::Barrage,
+        ]
+        .into_iter()
+        .map(|kind| table.get(kind).speed)
+        .fold(1.0, f32::max)
+    }
+
+    /// Fires a ball straight at the thinnest wall, stepping physics the way a match does, and
+    /// reports whether it ever made it past the wall's middle.
+    fn passes_through_thinnest_wall(speed: f32, ccd: bool) -> bool {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(AssetPlugin)
+            .add_asset::<Mesh>()
+            .add_plugin(TransformPlugin)
+            .add_plugin(HierarchyPlugin)
+            .insert_resource(RapierConfiguration {
+                gravity: Vect::ZERO,
+                timestep_mode: TimestepMode::Fixed {
+                    dt: STEP_SECONDS,
+                    substeps: 1,
+                },
+                ..default()
+            })
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+        app.world
+            .spawn()
+            .insert_bundle(TransformBundle::default())
+            .insert_bundle((
+                Collider::cuboid(THINNEST_WALL / 2.0, 5.0, 5.0),
+                RigidBody::Fixed,
+            ));
+        let ball = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from_transform(Transform::from_xyz(
+                -START_DISTANCE,
+                0.0,
+                0.0,
+            )))
+            .insert_bundle((
+                Collider::ball(BALL_RADIUS),
+                RigidBody::Dynamic,
+                Velocity::linear(Vec3::X * speed),
+                Ccd { enabled: ccd },
+            ))
+            .id();
+        (0..STEPS).any(|_| {
+            app.update();
+            app.world.get::<Transform>(ball).unwrap().translation.x > 0.0
+        })
+    }
+
+    #[test]
+    fn fastest_throw_does_not_tunnel() {
+        assert!(!passes_through_thinnest_wall(
+            MAX_THROW_SPEED * fastest_kind(),
+            true
+        ));
+    }
+
+    #[test]
+    fn fastest_ball_does_not_tunnel() {
+        assert!(!passes_through_thinnest_wall(MAX_BALL_SPEED, true));
+    }
+
+    /// Checks the setup can tell CCD apart from discrete collision detection, which catches the
+    /// game's own speeds on its own.
+    #[test]
+    fn only_ccd_stops_a_tunneling_ball() {
+        assert!(passes_through_thinnest_wall(CONTROL_SPEED, false));
+        assert!(!passes_through_thinnest_wall(CONTROL_SPEED, true));
+    }
+}
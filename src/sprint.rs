@@ -0,0 +1,70 @@
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::*;
+
+use crate::{
+    character::{CharacterState, CharacterStats},
+    game_time::GameTime,
+    rules::match_active,
+    status::{StatusEffect, StatusEffects},
+    tick_timer::TickTimer,
+    Action, Player,
+};
+
+pub struct SprintPlugin;
+
+impl Plugin for SprintPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::new()
+                .with_run_criteria(match_active)
+                .with_system(start_sprints)
+                .with_system(cool_down),
+        );
+    }
+}
+
+/// Counts down from the start of a sprint to when the next one can start.
+#[derive(Component)]
+pub struct SprintCooldown(TickTimer);
+
+/// Sprints are a burst of haste, so they stack with other speed effects and wear off the same way.
+fn start_sprints(
+    mut commands: Commands,
+    mut players: Query<
+        (
+            Entity,
+            &ActionState<Action>,
+            &CharacterState,
+            &CharacterStats,
+            &mut StatusEffects,
+        ),
+        (With<Player>, Without<SprintCooldown>),
+    >,
+) {
+    for (entity, action_state, state, stats, mut effects) in &mut players {
+        if !action_state.just_pressed(Action::Sprint) || !state.is_free() || !state.is_grounded() {
+            continue;
+        }
+        effects.add(
+            StatusEffect::Haste(stats.sprint_multiplier),
+            Some(stats.sprint_seconds),
+        );
+        commands
+            .entity(entity)
+            .insert(SprintCooldown(TickTimer::from_seconds(
+                stats.sprint_cooldown_seconds,
+            )));
+    }
+}
+
+fn cool_down(
+    mut commands: Commands,
+    time: Res<GameTime>,
+    mut players: Query<(Entity, &mut SprintCooldown)>,
+) {
+    for (entity, mut cooldown) in &mut players {
+        if cooldown.0.tick(time.ticks()).finished() {
+            commands.entity(entity).remove::<SprintCooldown>();
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use bevy::{prelude::*, reflect::TypeUuid, utils::HashMap};
+use bevy_sprite3d::AtlasSprite3dComponent;
+use serde::Deserialize;
+
+use crate::{
+    game_time::GameTime,
+    ron_loader::{RonAsset, RonLoader},
+};
+
+/// The clip sprites start out playing.
+pub const DEFAULT_CLIP: &str = "idle";
+
+pub struct SpriteAnimationPlugin;
+
+impl Plugin for SpriteAnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<ClipSet>()
+            .init_asset_loader::<RonLoader<ClipSet>>()
+            .add_system(play_sprite_animations);
+    }
+}
+
+/// A character's animations, as runs of frames in its sprite sheet.
+#[derive(Deserialize, TypeUuid)]
+#[uuid = "8e4c2b61-0f3a-4d97-b5e8-61a9d7c3f205"]
+pub struct ClipSet {
+    pub clips: HashMap<String, Clip>,
+}
+
+#[derive(Deserialize, Clone, Copy)]
+pub struct Clip {
+    /// The atlas index of the clip's first frame. The rest follow it in the sheet.
+    pub first: usize,
+    pub frames: usize,
+    pub fps: f32,
+    /// Whether the clip starts over once it reaches the end, or holds its last frame.
+    #[serde(default)]
+    pub looping: bool,
+}
+
+/// Steps an atlas sprite through the frames of one clip at a time.
+#[derive(Component)]
+pub struct SpriteAnimation {
+    clips: Handle<ClipSet>,
+    clip: String,
+    elapsed: f32,
+    finished: bool,
+}
+
+impl SpriteAnimation {
+    pub fn new(clips: Handle<ClipSet>) -> Self {
+        Self {
+            clips,
+            clip: DEFAULT_CLIP.to_string(),
+            elapsed: 0.0,
+            finished: false,
+        }
+    }
+
+    pub fn clips(&self) -> &Handle<ClipSet> {
+        &self.clips
+    }
+
+    pub fn clip(&self) -> &str {
+        &self.clip
+    }
+
+    /// Switches to a clip from its first frame, or carries on if it's already playing.
+    pub fn play(&mut self, clip: &str) {
+        if self.clip != clip {
+            self.restart(clip);
+        }
+    }
+
+    /// Plays a clip from its first frame, even if it's already playing.
+    pub fn restart(&mut self, clip: &str) {
+        self.clip = clip.to_string();
+        self.elapsed = 0.0;
+        self.finished = false;
+    }
+
+    /// Whether a clip that doesn't loop has reached its last frame.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+}
+
+fn play_sprite_animations(
+    time: Res<GameTime>,
+    clip_sets: Res<Assets<ClipSet>>,
+    mut sprites: Query<(&mut SpriteAnimation, &mut AtlasSprite3dComponent)>,
+) {
+    for (mut animation, mut sprite) in &mut sprites {
+        let clip = if let Some(clip) = clip_sets
+            .get(&animation.clips)
+            .and_then(|set| set.clips.get(&animation.clip))
+        {
+            *clip
+        } else {
+            continue;
+        };
+        if clip.frames == 0 {
+            continue;
+        }
+        animation.elapsed += time.delta_seconds();
+        if clip.looping {
+            animation.elapsed %= clip.frames as f32 / clip.fps;
+        }
+        let mut frame = (animation.elapsed * clip.fps) as usize;
+        if frame >= clip.frames {
+            frame = if clip.looping { 0 } else { clip.frames - 1 };
+            animation.finished = !clip.looping;
+        }
+        let index = (clip.first + frame).min(sprite.atlas.len().saturating_sub(1));
+        if sprite.index != index {
+            sprite.index = index;
+        }
+    }
+}
+
+impl RonAsset for ClipSet {
+    const EXTENSIONS: &'static [&'static str] = &["clips.ron"];
+}
@@ -0,0 +1,353 @@
+use std::collections::VecDeque;
+
+use bevy::{prelude::*, reflect::TypeUuid};
+use bevy_asset_loader::prelude::*;
+use bevy_rapier3d::prelude::*;
+use rand::Rng;
+use serde::Deserialize;
+
+use crate::{
+    cleanup::DespawnOnExit,
+    ron_loader::{RonAsset, RonLoader},
+    rules::{MatchMode, Ruleset},
+    GameState,
+};
+
+use self::{
+    generator::generate_arena,
+    markings::{spawn_markings, Markings},
+    props::{spawn_prop, Prop, PropsPlugin},
+    transition::TransitionPlugin,
+};
+
+mod generator;
+mod markings;
+mod props;
+pub mod transition;
+mod validation;
+
+const OBSTACLE_HEIGHT: f32 = 0.8;
+/// How far from the back wall players line up when a stage has no rush spawns.
+const RUSH_SPAWN_MARGIN: f32 = 1.0;
+/// How far behind the other team's back wall a jail goes when a stage has none.
+const JAIL_MARGIN: f32 = 1.0;
+/// How much darker obstacles are than the floor they stand on.
+const OBSTACLE_SHADE: f32 = 0.6;
+
+pub struct StagePlugin;
+
+impl Plugin for StagePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(TransitionPlugin)
+            .add_plugin(PropsPlugin)
+            .init_resource::<ProceduralArena>()
+            .add_asset::<StageAsset>()
+            .init_asset_loader::<RonLoader<StageAsset>>()
+            .add_system_set(SystemSet::on_enter(GameState::Ready).with_system(spawn_stage));
+    }
+}
+
+#[derive(AssetCollection)]
+pub struct StageAssets {
+    #[asset(path = "stages/court.stage.ron")]
+    pub court: Handle<StageAsset>,
+}
+
+#[derive(Deserialize, TypeUuid, Clone)]
+#[uuid = "6b1f3d2e-94c7-4a51-b8e3-2f0c7d9a5e16"]
+pub struct StageAsset {
+    /// Width and length of the court.
+    pub size: (f32, f32),
+    pub color: Color,
+    pub camera: StageCamera,
+    /// Where each team starts, indexed by team.
+    pub spawns: Vec<(f32, f32)>,
+    /// Where each team starts an opening rush, against its back wall.
+    #[serde(default)]
+    pub rush_spawns: Vec<(f32, f32)>,
+    /// Where each team's eliminated players are held when playing with jails, behind the other
+    /// team's court.
+    #[serde(default)]
+    pub jails: Vec<(f32, f32)>,
+    #[serde(default)]
+    pub obstacles: Vec<Obstacle>,
+    #[serde(default)]
+    pub markings: Markings,
+    /// Flags, screens and lights dressing the arena around the court.
+    #[serde(default)]
+    pub props: Vec<Prop>,
+    /// Looping background sounds, layered on top of each other.
+    #[serde(default)]
+    pub ambience: Vec<AmbienceLayer>,
+}
+
+/// A looping background sound, like a gym's echo or waves on a beach.
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct AmbienceLayer {
+    /// Asset path of the sound.
+    pub sound: String,
+    pub volume: f32,
+}
+
+impl StageAsset {
+    pub fn spawn(&self, team: usize) -> Vec2 {
+        self.spawns
+            .get(team)
+            .copied()
+            .map_or(Vec2::ZERO, Vec2::from)
+    }
+
+    /// Falls back to the middle of the team's back wall when the stage doesn't say.
+    pub fn rush_spawn(&self, team: usize) -> Vec2 {
+        self.rush_spawns.get(team).copied().map_or_else(
+            || {
+                let back_wall = self.size.1 / 2.0 - RUSH_SPAWN_MARGIN;
+                Vec2::new(0.0, self.spawn(team).y.signum() * back_wall)
+            },
+            Vec2::from,
+        )
+    }
+
+    /// Falls back to just behind the other team's back wall when the stage doesn't say.
+    pub fn jail(&self, team: usize) -> Vec2 {
+        self.jails.get(team).copied().map_or_else(
+            || {
+                let behind = self.size.1 / 2.0 + JAIL_MARGIN;
+                Vec2::new(0.0, -self.spawn(team).y.signum() * behind)
+            },
+            Vec2::from,
+        )
+    }
+}
+
+/// A solid block on the court that players have to move around and can take cover behind.
+#[derive(Deserialize, Clone)]
+pub struct Obstacle {
+    pub position: (f32, f32),
+    pub size: (f32, f32),
+}
+
+/// Settings for the arenas generated for survival mode.
+pub struct ProceduralArena {
+    /// Generates the same arena every time when set, otherwise a new one each match.
+    pub seed: Option<u64>,
+    /// The fraction of the court covered by obstacles.
+    pub obstacle_density: f32,
+}
+
+impl Default for ProceduralArena {
+    fn default() -> Self {
+        Self {
+            seed: None,
+            obstacle_density: 0.08,
+        }
+    }
+}
+
+/// How the camera frames a stage.
+#[derive(Deserialize, Clone, Copy)]
+pub struct StageCamera {
+    /// Angle of the camera above the court, in degrees.
+    pub pitch: f32,
+    pub distance: f32,
+    /// Narrowest and widest field of view in degrees, widening as the players spread out.
+    pub fov: (f32, f32),
+    /// How far along the length of the court the camera may pan to follow the players.
+    pub follow_bounds: (f32, f32),
+}
+
+impl StageCamera {
+    /// The camera transform when looking at `target` on the court.
+    pub fn transform(&self, target: Vec3) -> Transform {
+        let pitch = self.pitch.to_radians();
+        let offset = Vec3::new(pitch.cos(), 0.0, pitch.sin()) * self.distance;
+        Transform::from_translation(target + offset).looking_at(target, Vec3::NEG_X)
+    }
+}
+
+/// The stage currently being played on.
+pub struct ActiveStage {
+    pub size: Vec2,
+    pub camera: StageCamera,
+    pub ambience: Vec<AmbienceLayer>,
+    /// Where each team starts, indexed by team.
+    pub spawns: Vec<Vec2>,
+    /// Each team's jail, indexed by team.
+    pub jails: Vec<Vec2>,
+}
+
+impl ActiveStage {
+    pub fn spawn(&self, team: usize) -> Vec2 {
+        self.spawns.get(team).copied().unwrap_or_default()
+    }
+}
+
+impl RonAsset for StageAsset {
+    const EXTENSIONS: &'static [&'static str] = &["stage.ron"];
+}
+
+#[derive(Component)]
+pub struct Ground;
+
+/// Marks an obstacle's collider, which balls can be banked off.
+#[derive(Component)]
+pub struct Wall;
+
+fn spawn_stage(
+    mut commands: Commands,
+    stages: Res<StageAssets>,
+    stage_assets: Res<Assets<StageAsset>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    ruleset: Res<Ruleset>,
+    procedural: Res<ProceduralArena>,
+) {
+    let stage = if let Some(stage) = stage_assets.get(&stages.court) {
+        stage
+    } else {
+        return;
+    };
+    let mut builder = StageBuilder::new(prepare_stage(stage, &ruleset, &procedural));
+    builder.spawn(&mut commands, &mut meshes, &mut materials, usize::MAX);
+    commands.insert_resource(builder.active_stage());
+}
+
+/// The stage as it will be played, generating an arena from it for modes that call for one.
+fn prepare_stage(
+    stage: &StageAsset,
+    ruleset: &Ruleset,
+    procedural: &ProceduralArena,
+) -> StageAsset {
+    let stage = if ruleset.mode == MatchMode::Survival {
+        let seed = procedural.seed.unwrap_or_else(|| rand::thread_rng().gen());
+        info!("Generating arena from seed {seed}");
+        generate_arena(stage, seed, procedural.obstacle_density)
+    } else {
+        stage.clone()
+    };
+    if let Err(error) = stage.validate() {
+        warn!("The stage is not fit to play on: {error}");
+    }
+    stage
+}
+
+/// Marks everything spawned as part of a stage, so it can be cleared when the stage changes.
+#[derive(Component)]
+pub struct StageEntity;
+
+enum StagePiece {
+    Floor,
+    Obstacle(usize),
+    Prop(usize),
+}
+
+/// Spawns a stage piece by piece, so large stages can be spread over several frames.
+struct StageBuilder {
+    stage: StageAsset,
+    pieces: VecDeque<StagePiece>,
+    obstacle_material: Option<Handle<StandardMaterial>>,
+}
+
+impl StageBuilder {
+    fn new(stage: StageAsset) -> Self {
+        let pieces = std::iter::once(StagePiece::Floor)
+            .chain((0..stage.obstacles.len()).map(StagePiece::Obstacle))
+            .chain((0..stage.props.len()).map(StagePiece::Prop))
+            .collect();
+        Self {
+            stage,
+            pieces,
+            obstacle_material: None,
+        }
+    }
+
+    /// Spawns up to `budget` pieces, returning whether the whole stage has been spawned.
+    fn spawn(
+        &mut self,
+        commands: &mut Commands,
+        meshes: &mut Assets<Mesh>,
+        materials: &mut Assets<StandardMaterial>,
+        budget: usize,
+    ) -> bool {
+        let stage = &self.stage;
+        for _ in 0..budget {
+            let piece = if let Some(piece) = self.pieces.pop_front() {
+                piece
+            } else {
+                break;
+            };
+            match piece {
+                StagePiece::Floor => {
+                    let size = Vec2::from(stage.size);
+                    let mut mat: StandardMaterial = stage.color.into();
+                    mat.unlit = true;
+                    commands
+                        .spawn_bundle(PbrBundle {
+                            mesh: meshes.add(shape::Quad { size, flip: false }.into()),
+                            material: materials.add(mat),
+                            ..default()
+                        })
+                        .insert_bundle((
+                            Collider::cuboid(size.x / 2.0, size.y / 2.0, 0.01),
+                            RigidBody::Fixed,
+                            Ground,
+                            StageEntity,
+                            DespawnOnExit(GameState::Ready),
+                        ))
+                        .with_children(|parent| {
+                            spawn_markings(parent, &stage.markings, size, meshes, materials);
+                        });
+                }
+                StagePiece::Obstacle(index) => {
+                    let obstacle = &stage.obstacles[index];
+                    let material = self
+                        .obstacle_material
+                        .get_or_insert_with(|| {
+                            materials.add(StandardMaterial {
+                                base_color: stage.color * OBSTACLE_SHADE,
+                                unlit: true,
+                                ..default()
+                            })
+                        })
+                        .clone();
+                    let extents = Vec2::from(obstacle.size).extend(OBSTACLE_HEIGHT);
+                    commands
+                        .spawn_bundle(PbrBundle {
+                            mesh: meshes
+                                .add(shape::Box::new(extents.x, extents.y, extents.z).into()),
+                            material,
+                            transform: Transform::from_translation(
+                                Vec2::from(obstacle.position).extend(OBSTACLE_HEIGHT / 2.0),
+                            ),
+                            ..default()
+                        })
+                        .insert_bundle((
+                            Collider::cuboid(extents.x / 2.0, extents.y / 2.0, extents.z / 2.0),
+                            RigidBody::Fixed,
+                            Wall,
+                            StageEntity,
+                            DespawnOnExit(GameState::Ready),
+                        ));
+                }
+                StagePiece::Prop(index) => {
+                    spawn_prop(commands, &stage.props[index], index, meshes, materials);
+                }
+            }
+        }
+        self.pieces.is_empty()
+    }
+
+    fn active_stage(&self) -> ActiveStage {
+        ActiveStage {
+            size: Vec2::from(self.stage.size),
+            camera: self.stage.camera,
+            ambience: self.stage.ambience.clone(),
+            spawns: (0..self.stage.spawns.len())
+                .map(|team| self.stage.spawn(team))
+                .collect(),
+            jails: (0..self.stage.spawns.len())
+                .map(|team| self.stage.jail(team))
+                .collect(),
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use bevy::prelude::*;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use super::{Obstacle, StageAsset};
+
+/// Average area of a generated obstacle, used to turn a density into an obstacle count.
+const OBSTACLE_AREA: f32 = 0.6;
+const OBSTACLE_SIZE: (f32, f32) = (0.4, 1.2);
+/// How many spots to try for each obstacle before leaving it out.
+const MAX_ATTEMPTS: usize = 32;
+
+/// Generates a practice arena from `base`, scattering obstacles over one half of the court and
+/// mirroring them onto the other.
+///
+/// `obstacle_density` is the fraction of the court to cover. The same seed always produces the
+/// same arena, and every arena passes [`StageAsset::validate`].
+pub fn generate_arena(base: &StageAsset, seed: u64, obstacle_density: f32) -> StageAsset {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let half = Vec2::from(base.size) / 2.0;
+    let count = (half.x * half.y * 2.0 * obstacle_density / OBSTACLE_AREA).round() as usize;
+
+    let mut arena = base.clone();
+    arena.obstacles.clear();
+    if let Err(error) = arena.validate() {
+        warn!("Cannot generate an arena from an invalid stage: {error}");
+        return arena;
+    }
+
+    for _ in 0..count {
+        for _ in 0..MAX_ATTEMPTS {
+            let size = Vec2::new(
+                rng.gen_range(OBSTACLE_SIZE.0..=OBSTACLE_SIZE.1),
+                rng.gen_range(OBSTACLE_SIZE.0..=OBSTACLE_SIZE.1),
+            );
+            let limit = half - size / 2.0;
+            if limit.x <= 0.0 || limit.y <= 0.0 {
+                continue;
+            }
+            let position = Vec2::new(
+                rng.gen_range(-limit.x..=limit.x),
+                -rng.gen_range(0.0..=limit.y),
+            );
+            arena.obstacles.push(Obstacle {
+                position: position.into(),
+                size: size.into(),
+            });
+            arena.obstacles.push(Obstacle {
+                position: (position.x, -position.y),
+                size: size.into(),
+            });
+            if arena.validate().is_ok() {
+                break;
+            }
+            arena.obstacles.truncate(arena.obstacles.len() - 2);
+        }
+    }
+    arena
+}
@@ -0,0 +1,165 @@
+use std::f32::consts::TAU;
+
+use bevy::{
+    prelude::*,
+    render::mesh::{Indices, PrimitiveTopology},
+};
+use serde::Deserialize;
+
+use crate::team::{Team, TeamTint};
+
+/// Height of the markings above the floor, just enough to keep them from z-fighting with it.
+const MARKING_HEIGHT: f32 = 0.002;
+const CIRCLE_SEGMENTS: usize = 48;
+
+/// Court markings painted onto the floor of a stage.
+#[derive(Deserialize, Clone)]
+pub struct Markings {
+    pub color: Color,
+    /// Width of every line, in metres.
+    pub width: f32,
+    pub shapes: Vec<Marking>,
+}
+
+impl Default for Markings {
+    fn default() -> Self {
+        Self {
+            color: Color::WHITE,
+            width: 0.05,
+            shapes: Vec::new(),
+        }
+    }
+}
+
+#[derive(Deserialize, Clone)]
+pub enum Marking {
+    /// Outlines the edge of the court.
+    Boundary,
+    Line {
+        from: (f32, f32),
+        to: (f32, f32),
+    },
+    Circle {
+        center: (f32, f32),
+        radius: f32,
+        /// Paints the circle in a team's color, for spots that belong to one side.
+        #[serde(default)]
+        team: Option<usize>,
+    },
+}
+
+/// Spawns each marking as its own mesh on top of the floor, so lines stay crisp however large
+/// the court is.
+pub fn spawn_markings(
+    parent: &mut ChildBuilder,
+    markings: &Markings,
+    court: Vec2,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+) {
+    let material = materials.add(StandardMaterial {
+        base_color: markings.color,
+        unlit: true,
+        ..default()
+    });
+    let width = markings.width;
+    let half = court / 2.0 - Vec2::splat(width / 2.0);
+
+    for marking in &markings.shapes {
+        match marking {
+            Marking::Boundary => {
+                let corners = [
+                    Vec2::new(-half.x, -half.y),
+                    Vec2::new(half.x, -half.y),
+                    Vec2::new(half.x, half.y),
+                    Vec2::new(-half.x, half.y),
+                ];
+                for (index, from) in corners.iter().enumerate() {
+                    let to = corners[(index + 1) % corners.len()];
+                    spawn_line(parent, *from, to, width, &material, meshes);
+                }
+            }
+            Marking::Line { from, to } => {
+                spawn_line(
+                    parent,
+                    Vec2::from(*from),
+                    Vec2::from(*to),
+                    width,
+                    &material,
+                    meshes,
+                );
+            }
+            Marking::Circle {
+                center,
+                radius,
+                team,
+            } => {
+                let mut circle = parent.spawn_bundle(PbrBundle {
+                    mesh: meshes.add(ring(*radius, width)),
+                    material: material.clone(),
+                    transform: Transform::from_translation(
+                        Vec2::from(*center).extend(MARKING_HEIGHT),
+                    ),
+                    ..default()
+                });
+                if let Some(team) = team {
+                    circle.insert(TeamTint {
+                        team: Team(*team),
+                        strength: 1.0,
+                    });
+                }
+            }
+        }
+    }
+}
+
+fn spawn_line(
+    parent: &mut ChildBuilder,
+    from: Vec2,
+    to: Vec2,
+    width: f32,
+    material: &Handle<StandardMaterial>,
+    meshes: &mut Assets<Mesh>,
+) {
+    let offset = to - from;
+    // Extend lines by their width so they meet cleanly at corners.
+    let size = Vec2::new(offset.length() + width, width);
+    parent.spawn_bundle(PbrBundle {
+        mesh: meshes.add(shape::Quad { size, flip: false }.into()),
+        material: material.clone(),
+        transform: Transform::from_translation(((from + to) / 2.0).extend(MARKING_HEIGHT))
+            .with_rotation(Quat::from_rotation_z(offset.y.atan2(offset.x))),
+        ..default()
+    });
+}
+
+/// A flat ring facing up, centered on the origin.
+fn ring(radius: f32, width: f32) -> Mesh {
+    let inner = radius - width / 2.0;
+    let outer = radius + width / 2.0;
+    let mut positions = Vec::with_capacity(CIRCLE_SEGMENTS * 2);
+    let mut uvs = Vec::with_capacity(CIRCLE_SEGMENTS * 2);
+    let mut indices = Vec::with_capacity(CIRCLE_SEGMENTS * 6);
+    for segment in 0..CIRCLE_SEGMENTS {
+        let progress = segment as f32 / CIRCLE_SEGMENTS as f32;
+        let (sin, cos) = (progress * TAU).sin_cos();
+        positions.push([cos * inner, sin * inner, 0.0]);
+        positions.push([cos * outer, sin * outer, 0.0]);
+        uvs.push([progress, 0.0]);
+        uvs.push([progress, 1.0]);
+
+        let i = (segment * 2) as u32;
+        let next = ((segment + 1) % CIRCLE_SEGMENTS * 2) as u32;
+        indices.extend_from_slice(&[i, i + 1, next + 1, i, next + 1, next]);
+    }
+
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+    mesh.insert_attribute(
+        Mesh::ATTRIBUTE_NORMAL,
+        vec![[0.0, 0.0, 1.0]; positions.len()],
+    );
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+    mesh.set_indices(Some(Indices::U32(indices)));
+    mesh
+}
@@ -0,0 +1,307 @@
+use std::f32::consts::TAU;
+
+use bevy::{
+    prelude::*,
+    render::{
+        camera::RenderTarget,
+        render_resource::{
+            Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
+        },
+        view::RenderLayers,
+    },
+};
+use serde::Deserialize;
+
+use crate::{cleanup::DespawnOnExit, score::Score, team::Team, ui::text::StyledText, GameState};
+
+use super::StageEntity;
+
+/// Render layer the score shown on jumbotrons is drawn on, away from the court and the
+/// pixel-perfect upscale.
+const SCORE_SCREEN_LAYER: u8 = 2;
+/// Resolution of the score shown on jumbotrons, in pixels per metre of screen.
+const SCORE_SCREEN_DENSITY: f32 = 64.0;
+const POLE_WIDTH: f32 = 0.05;
+/// How far a flag swings either way on its pole, in radians, and how many times a second.
+const FLAG_SWING: f32 = 0.35;
+const FLAG_WAVES_PER_SECOND: f32 = 0.6;
+/// How much of its brightness a blinking light keeps while off.
+const LIGHT_OFF_BRIGHTNESS: f32 = 0.2;
+const LIGHT_RADIUS: f32 = 0.12;
+
+pub struct PropsPlugin;
+
+impl Plugin for PropsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(set_up_jumbotrons)
+            .add_system(show_score_on_jumbotrons.after(set_up_jumbotrons))
+            .add_system(wave_flags)
+            .add_system(blink_lights);
+    }
+}
+
+/// Dressing around a stage. Props are purely for show, so nothing collides with them.
+#[derive(Deserialize, Clone)]
+pub struct Prop {
+    /// The top of a flag's pole, or the middle of anything else.
+    pub position: (f32, f32, f32),
+    /// Turns the prop around the vertical axis, in degrees. Screens and flags face the camera
+    /// side of the court unturned.
+    #[serde(default)]
+    pub turn: f32,
+    pub kind: PropKind,
+}
+
+#[derive(Deserialize, Clone)]
+pub enum PropKind {
+    /// A flag on a pole, waving in the breeze.
+    Flag { color: Color, size: (f32, f32) },
+    /// A screen showing the live score.
+    Jumbotron { size: (f32, f32) },
+    /// A light that blinks on and off, once every `period` seconds.
+    Light { color: Color, period: f32 },
+}
+
+/// Swings a flag back and forth around its pole.
+#[derive(Component)]
+struct Wave {
+    phase: f32,
+}
+
+#[derive(Component)]
+struct Blink {
+    period: f32,
+    color: Color,
+}
+
+/// A screen the score is shown on.
+#[derive(Component)]
+struct Jumbotron {
+    size: Vec2,
+}
+
+/// The text the score is drawn with, off screen, for jumbotrons to show.
+#[derive(Component)]
+struct ScoreScreen(Handle<Image>);
+
+/// Rotates a quad, which faces up, to stand upright facing the camera side of the court.
+fn upright() -> Quat {
+    Quat::from_mat3(&Mat3::from_cols(Vec3::Y, Vec3::Z, Vec3::X))
+}
+
+pub fn spawn_prop(
+    commands: &mut Commands,
+    prop: &Prop,
+    index: usize,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+) {
+    let position = Vec3::from(prop.position);
+    let mut entity = commands.spawn_bundle(SpatialBundle {
+        transform: Transform::from_translation(position)
+            .with_rotation(Quat::from_rotation_z(prop.turn.to_radians())),
+        ..default()
+    });
+    entity.insert_bundle((StageEntity, DespawnOnExit(GameState::Ready)));
+    match prop.kind {
+        PropKind::Flag { color, size } => {
+            let size = Vec2::from(size);
+            let pole_material = materials.add(StandardMaterial {
+                base_color: Color::GRAY,
+                unlit: true,
+                ..default()
+            });
+            let flag_material = materials.add(StandardMaterial {
+                base_color: color,
+                unlit: true,
+                double_sided: true,
+                cull_mode: None,
+                ..default()
+            });
+            let pole = meshes.add(shape::Box::new(POLE_WIDTH, POLE_WIDTH, position.z).into());
+            let flag = meshes.add(shape::Quad { size, flip: false }.into());
+            entity.with_children(|parent| {
+                parent.spawn_bundle(PbrBundle {
+                    mesh: pole,
+                    material: pole_material,
+                    transform: Transform::from_xyz(0.0, 0.0, -position.z / 2.0),
+                    ..default()
+                });
+                // Flags hang off the top of the pole, so they swing around it.
+                parent
+                    .spawn_bundle(SpatialBundle::default())
+                    .insert(Wave {
+                        phase: index as f32,
+                    })
+                    .with_children(|pivot| {
+                        pivot.spawn_bundle(PbrBundle {
+                            mesh: flag,
+                            material: flag_material,
+                            transform: Transform::from_xyz(0.0, size.x / 2.0, -size.y / 2.0)
+                                .with_rotation(upright()),
+                            ..default()
+                        });
+                    });
+            });
+        }
+        PropKind::Jumbotron { size } => {
+            entity.insert(Jumbotron {
+                size: Vec2::from(size),
+            });
+        }
+        PropKind::Light { color, period } => {
+            entity.insert_bundle((
+                meshes.add(
+                    shape::UVSphere {
+                        radius: LIGHT_RADIUS,
+                        sectors: 12,
+                        stacks: 6,
+                    }
+                    .into(),
+                ),
+                materials.add(StandardMaterial {
+                    base_color: color,
+                    unlit: true,
+                    ..default()
+                }),
+                Blink { period, color },
+            ));
+        }
+    }
+}
+
+/// Gives new jumbotrons their screens, drawing the score off screen for them to show the first
+/// time one is needed.
+fn set_up_jumbotrons(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    jumbotrons: Query<(Entity, &Jumbotron), Added<Jumbotron>>,
+    screens: Query<&ScoreScreen>,
+) {
+    let mut screen = screens.iter().next().map(|screen| screen.0.clone());
+    for (entity, jumbotron) in &jumbotrons {
+        let image = screen
+            .get_or_insert_with(|| spawn_score_screen(&mut commands, &mut images, jumbotron.size))
+            .clone();
+        let material = materials.add(StandardMaterial {
+            base_color_texture: Some(image),
+            unlit: true,
+            ..default()
+        });
+        commands.entity(entity).with_children(|parent| {
+            parent.spawn_bundle(PbrBundle {
+                mesh: meshes.add(
+                    shape::Quad {
+                        size: jumbotron.size,
+                        flip: false,
+                    }
+                    .into(),
+                ),
+                material,
+                transform: Transform::from_rotation(upright()),
+                ..default()
+            });
+        });
+    }
+}
+
+/// Renders the score into an image of its own, sized for a screen of `size` metres.
+fn spawn_score_screen(
+    commands: &mut Commands,
+    images: &mut Assets<Image>,
+    size: Vec2,
+) -> Handle<Image> {
+    let size = Extent3d {
+        width: (size.x * SCORE_SCREEN_DENSITY) as u32,
+        height: (size.y * SCORE_SCREEN_DENSITY) as u32,
+        ..default()
+    };
+    let mut image = Image {
+        texture_descriptor: TextureDescriptor {
+            label: None,
+            size,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::Bgra8UnormSrgb,
+            mip_level_count: 1,
+            sample_count: 1,
+            usage: TextureUsages::TEXTURE_BINDING
+                | TextureUsages::COPY_DST
+                | TextureUsages::RENDER_ATTACHMENT,
+        },
+        ..default()
+    };
+    image.resize(size);
+    let image = images.add(image);
+    let layer = RenderLayers::layer(SCORE_SCREEN_LAYER);
+    commands
+        .spawn_bundle(Camera2dBundle {
+            camera: Camera {
+                target: RenderTarget::Image(image.clone()),
+                priority: -1,
+                ..default()
+            },
+            ..default()
+        })
+        .insert_bundle((layer, StageEntity, DespawnOnExit(GameState::Ready)));
+    commands
+        .spawn_bundle(Text2dBundle {
+            text: Text::from_section("", TextStyle::default())
+                .with_alignment(TextAlignment::CENTER),
+            ..default()
+        })
+        .insert_bundle((
+            layer,
+            StyledText("title"),
+            ScoreScreen(image.clone()),
+            StageEntity,
+            DespawnOnExit(GameState::Ready),
+        ));
+    image
+}
+
+fn show_score_on_jumbotrons(
+    score: Res<Score>,
+    mut screens: Query<(&mut Text, ChangeTrackers<ScoreScreen>)>,
+) {
+    for (mut text, tracker) in &mut screens {
+        if !score.is_changed() && !tracker.is_added() {
+            continue;
+        }
+        text.sections[0].value = format!("{} - {}", score.get(Team(0)), score.get(Team(1)));
+    }
+}
+
+fn wave_flags(time: Res<Time>, mut flags: Query<(&Wave, &mut Transform)>) {
+    let elapsed = time.seconds_since_startup() as f32;
+    for (wave, mut transform) in &mut flags {
+        let swing = (elapsed * FLAG_WAVES_PER_SECOND * TAU + wave.phase).sin() * FLAG_SWING;
+        transform.rotation = Quat::from_rotation_z(swing);
+    }
+}
+
+fn blink_lights(
+    time: Res<Time>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    lights: Query<(&Blink, &Handle<StandardMaterial>)>,
+) {
+    let elapsed = time.seconds_since_startup() as f32;
+    for (blink, handle) in &lights {
+        let on = blink.period <= 0.0 || elapsed % blink.period < blink.period / 2.0;
+        let color = if on {
+            blink.color
+        } else {
+            blink.color * LIGHT_OFF_BRIGHTNESS
+        };
+        // Only touch the material when it changes, since that re-uploads it.
+        if materials
+            .get(handle)
+            .map_or(false, |material| material.base_color != color)
+        {
+            if let Some(material) = materials.get_mut(handle) {
+                material.base_color = color;
+            }
+        }
+    }
+}
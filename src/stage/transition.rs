@@ -0,0 +1,177 @@
+use bevy::{asset::LoadState, prelude::*};
+use bevy_rapier3d::prelude::*;
+
+use crate::{
+    cleanup::DespawnOnExit,
+    game_time::{HoldPhysics, PhysicsHold},
+    input::{InputContext, InputLayer},
+    rules::Ruleset,
+    team::Team,
+    tween::{Ease, Tween},
+    GameState, Player, CLEAR,
+};
+
+use super::{prepare_stage, ProceduralArena, StageAsset, StageBuilder, StageEntity};
+
+const WIPE_SECONDS: f32 = 0.4;
+/// How many stage pieces are spawned each frame while the wipe covers the screen.
+const PIECES_PER_FRAME: usize = 8;
+
+pub struct TransitionPlugin;
+
+impl Plugin for TransitionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<LoadStage>().add_system_set(
+            SystemSet::on_update(GameState::Ready)
+                .with_system(start_transition)
+                .with_system(advance_transition.after(start_transition)),
+        );
+    }
+}
+
+/// Swaps the current stage for the one at this asset path, behind a screen wipe.
+pub struct LoadStage(pub String);
+
+/// A stage change in progress.
+struct StageTransition {
+    wipe: Entity,
+    phase: TransitionPhase,
+}
+
+enum TransitionPhase {
+    /// The wipe is sliding in while the new stage loads in the background.
+    Covering(Timer, Handle<StageAsset>),
+    Loading(Handle<StageAsset>),
+    Spawning(StageBuilder),
+    Revealing(Timer),
+}
+
+fn start_transition(
+    mut commands: Commands,
+    mut events: EventReader<LoadStage>,
+    asset_server: Res<AssetServer>,
+    mut holds: EventWriter<HoldPhysics>,
+    transition: Option<Res<StageTransition>>,
+) {
+    let path = if let Some(LoadStage(path)) = events.iter().last() {
+        path
+    } else {
+        return;
+    };
+    if transition.is_some() {
+        warn!("Already changing stage, ignoring {path}");
+        return;
+    }
+
+    info!("Loading stage {path}");
+    holds.send(HoldPhysics {
+        hold: PhysicsHold::StageTransition,
+        held: true,
+    });
+    let wipe = commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    left: Val::Percent(-100.0),
+                    ..default()
+                },
+                ..default()
+            },
+            color: CLEAR.into(),
+            ..default()
+        })
+        .insert_bundle((
+            Tween::slide_left(-100.0, 0.0, WIPE_SECONDS).ease(Ease::QuadIn),
+            // Nobody plays on while the stage is hidden.
+            InputLayer(InputContext::Menu),
+            DespawnOnExit(GameState::Ready),
+        ))
+        .id();
+    commands.insert_resource(StageTransition {
+        wipe,
+        phase: TransitionPhase::Covering(
+            Timer::from_seconds(WIPE_SECONDS, false),
+            asset_server.load(path.as_str()),
+        ),
+    });
+}
+
+fn advance_transition(
+    mut commands: Commands,
+    time: Res<Time>,
+    transition: Option<ResMut<StageTransition>>,
+    asset_server: Res<AssetServer>,
+    stages: Res<Assets<StageAsset>>,
+    ruleset: Res<Ruleset>,
+    procedural: Res<ProceduralArena>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut holds: EventWriter<HoldPhysics>,
+    old_stage: Query<Entity, With<StageEntity>>,
+    mut players: Query<(&mut Transform, &mut Velocity, &Team), With<Player>>,
+) {
+    let mut transition = if let Some(transition) = transition {
+        transition
+    } else {
+        return;
+    };
+    let wipe = transition.wipe;
+
+    let next = match &mut transition.phase {
+        TransitionPhase::Covering(timer, handle) => timer
+            .tick(time.delta())
+            .finished()
+            .then(|| TransitionPhase::Loading(handle.clone())),
+        TransitionPhase::Loading(handle) => match asset_server.get_load_state(&*handle) {
+            LoadState::Loaded => stages.get(&*handle).map(|stage| {
+                for entity in &old_stage {
+                    commands.entity(entity).despawn_recursive();
+                }
+                TransitionPhase::Spawning(StageBuilder::new(prepare_stage(
+                    stage,
+                    &ruleset,
+                    &procedural,
+                )))
+            }),
+            LoadState::Failed => {
+                warn!("Could not load the stage, staying on the current one");
+                Some(reveal(&mut commands, wipe))
+            }
+            _ => None,
+        },
+        TransitionPhase::Spawning(builder) => builder
+            .spawn(&mut commands, &mut meshes, &mut materials, PIECES_PER_FRAME)
+            .then(|| {
+                for (mut transform, mut velocity, team) in &mut players {
+                    let spawn = builder.stage.spawn(team.0);
+                    transform.translation = spawn.extend(transform.translation.z);
+                    *velocity = Velocity::zero();
+                }
+                commands.insert_resource(builder.active_stage());
+                reveal(&mut commands, wipe)
+            }),
+        TransitionPhase::Revealing(timer) => {
+            if timer.tick(time.delta()).finished() {
+                commands.entity(wipe).despawn_recursive();
+                commands.remove_resource::<StageTransition>();
+                holds.send(HoldPhysics {
+                    hold: PhysicsHold::StageTransition,
+                    held: false,
+                });
+            }
+            None
+        }
+    };
+    if let Some(next) = next {
+        transition.phase = next;
+    }
+}
+
+fn reveal(commands: &mut Commands, wipe: Entity) -> TransitionPhase {
+    commands
+        .entity(wipe)
+        .insert(Tween::slide_left(0.0, 100.0, WIPE_SECONDS).ease(Ease::QuadOut));
+    TransitionPhase::Revealing(Timer::from_seconds(WIPE_SECONDS, false))
+}
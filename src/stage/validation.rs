@@ -0,0 +1,179 @@
+use std::{collections::VecDeque, fmt};
+
+use bevy::prelude::*;
+
+use super::{Obstacle, StageAsset};
+
+/// How much room a spawn point needs around it, in metres.
+const SPAWN_CLEARANCE: f32 = 0.6;
+/// How far obstacles have to stay from the center line, so throws across it are never blocked
+/// point-blank.
+const CENTER_CLEARANCE: f32 = 1.0;
+/// Half the width of a player, used to check whether they fit between obstacles.
+const PLAYER_RADIUS: f32 = 0.25;
+/// Size of the cells the court is split into when checking whether spawns can be reached.
+const CELL_SIZE: f32 = 0.25;
+const MIRROR_TOLERANCE: f32 = 0.01;
+
+/// A reason a stage cannot be played on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StageError {
+    /// The stage has fewer spawn points than there are teams.
+    MissingSpawns,
+    /// An obstacle pokes out of the court.
+    ObstacleOutOfBounds(usize),
+    /// An obstacle is too close to the center line.
+    ObstacleOnCenterLine(usize),
+    /// An obstacle has no mirror image on the other half of the court.
+    Asymmetric(usize),
+    /// A spawn point is outside the court or too close to an obstacle.
+    BlockedSpawn(usize),
+    /// A spawn point is walled off from the center line.
+    UnreachableSpawn(usize),
+    /// A team's opening rush spawn is outside the court or too close to an obstacle.
+    BlockedRushSpawn(usize),
+}
+
+impl fmt::Display for StageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StageError::MissingSpawns => write!(f, "the stage needs a spawn point for each team"),
+            StageError::ObstacleOutOfBounds(index) => {
+                write!(f, "obstacle {index} is outside the court")
+            }
+            StageError::ObstacleOnCenterLine(index) => {
+                write!(f, "obstacle {index} is too close to the center line")
+            }
+            StageError::Asymmetric(index) => {
+                write!(f, "obstacle {index} is not mirrored on the other half")
+            }
+            StageError::BlockedSpawn(index) => write!(f, "spawn point {index} is blocked"),
+            StageError::UnreachableSpawn(index) => {
+                write!(f, "spawn point {index} cannot reach the center line")
+            }
+            StageError::BlockedRushSpawn(team) => {
+                write!(f, "the rush spawn for team {team} is blocked")
+            }
+        }
+    }
+}
+
+impl StageAsset {
+    /// Checks that the stage is fair and playable.
+    ///
+    /// Anything that produces stages should run them through this before they are played on.
+    pub fn validate(&self) -> Result<(), StageError> {
+        let half = Vec2::from(self.size) / 2.0;
+        if self.spawns.len() < 2 {
+            return Err(StageError::MissingSpawns);
+        }
+
+        for (index, obstacle) in self.obstacles.iter().enumerate() {
+            let (min, max) = obstacle.bounds();
+            if min.x < -half.x || min.y < -half.y || max.x > half.x || max.y > half.y {
+                return Err(StageError::ObstacleOutOfBounds(index));
+            }
+            if min.y < CENTER_CLEARANCE && max.y > -CENTER_CLEARANCE {
+                return Err(StageError::ObstacleOnCenterLine(index));
+            }
+            if !self.obstacles.iter().any(|other| other.mirrors(obstacle)) {
+                return Err(StageError::Asymmetric(index));
+            }
+        }
+
+        for (index, spawn) in self.spawns.iter().enumerate() {
+            let spawn = Vec2::from(*spawn);
+            if self.blocks(spawn) {
+                return Err(StageError::BlockedSpawn(index));
+            }
+            if !self.reaches_center_line(spawn) {
+                return Err(StageError::UnreachableSpawn(index));
+            }
+        }
+
+        for team in 0..self.spawns.len() {
+            let spawn = self.rush_spawn(team);
+            if self.blocks(spawn) || !self.reaches_center_line(spawn) {
+                return Err(StageError::BlockedRushSpawn(team));
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether a player standing at `spawn` would be outside the court or inside an obstacle.
+    fn blocks(&self, spawn: Vec2) -> bool {
+        let half = Vec2::from(self.size) / 2.0;
+        spawn.x.abs() > half.x - SPAWN_CLEARANCE
+            || spawn.y.abs() > half.y - SPAWN_CLEARANCE
+            || self
+                .obstacles
+                .iter()
+                .any(|obstacle| obstacle.distance_to(spawn) < SPAWN_CLEARANCE)
+    }
+
+    /// Flood fills the court from `from` to see whether a player can walk up to the center line.
+    fn reaches_center_line(&self, from: Vec2) -> bool {
+        let size = Vec2::from(self.size);
+        let columns = (size.x / CELL_SIZE).ceil() as usize;
+        let rows = (size.y / CELL_SIZE).ceil() as usize;
+        let center = |column: usize, row: usize| {
+            Vec2::new(column as f32 + 0.5, row as f32 + 0.5) * CELL_SIZE - size / 2.0
+        };
+        let walkable = |column: usize, row: usize| {
+            let point = center(column, row);
+            self.obstacles
+                .iter()
+                .all(|obstacle| obstacle.distance_to(point) >= PLAYER_RADIUS)
+        };
+
+        let start = (from + size / 2.0) / CELL_SIZE;
+        let start = (
+            (start.x.max(0.0) as usize).min(columns - 1),
+            (start.y.max(0.0) as usize).min(rows - 1),
+        );
+        let mut visited = vec![false; columns * rows];
+        let mut queue = VecDeque::from([start]);
+        visited[start.1 * columns + start.0] = true;
+        while let Some((column, row)) = queue.pop_front() {
+            if center(column, row).y.abs() <= CELL_SIZE {
+                return true;
+            }
+            let neighbours = [
+                (column.wrapping_sub(1), row),
+                (column + 1, row),
+                (column, row.wrapping_sub(1)),
+                (column, row + 1),
+            ];
+            for (column, row) in neighbours {
+                if column >= columns || row >= rows || visited[row * columns + column] {
+                    continue;
+                }
+                visited[row * columns + column] = true;
+                if walkable(column, row) {
+                    queue.push_back((column, row));
+                }
+            }
+        }
+        false
+    }
+}
+
+impl Obstacle {
+    fn bounds(&self) -> (Vec2, Vec2) {
+        let position = Vec2::from(self.position);
+        let half = Vec2::from(self.size) / 2.0;
+        (position - half, position + half)
+    }
+
+    fn distance_to(&self, point: Vec2) -> f32 {
+        let (min, max) = self.bounds();
+        (point.clamp(min, max) - point).length()
+    }
+
+    /// Whether this obstacle is `other` reflected across the center line.
+    fn mirrors(&self, other: &Obstacle) -> bool {
+        let mirrored = Vec2::new(other.position.0, -other.position.1);
+        Vec2::from(self.position).distance(mirrored) < MIRROR_TOLERANCE
+            && Vec2::from(self.size).distance(Vec2::from(other.size)) < MIRROR_TOLERANCE
+    }
+}
@@ -0,0 +1,123 @@
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{
+    catch::Caught, game_time::GameTime, hit::Hit, rules::match_active, team::Team,
+    tick_timer::TickTimer, GameState, HoldingBall,
+};
+
+/// Gameplay ticks between telemetry samples, five seconds at the tick rate.
+const SAMPLE_TICKS: u32 = 300;
+
+pub struct StatsPlugin;
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MatchStats>()
+            .init_resource::<Telemetry>()
+            .add_system_set(SystemSet::on_enter(GameState::Ready).with_system(reset_stats))
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(match_active)
+                    .with_system(count_stats)
+                    .with_system(record_telemetry.after(count_stats)),
+            );
+    }
+}
+
+/// What each player has done in the current match.
+#[derive(Default)]
+pub struct MatchStats(pub HashMap<Entity, PlayerStats>);
+
+#[derive(Default, Clone, Copy)]
+pub struct PlayerStats {
+    /// Balls thrown that struck an opponent.
+    pub hits: u32,
+    pub catches: u32,
+}
+
+impl MatchStats {
+    pub fn get(&self, player: Entity) -> PlayerStats {
+        self.0.get(&player).copied().unwrap_or_default()
+    }
+}
+
+/// How the match went over time, sampled at a fixed rate for the results screen.
+pub struct Telemetry {
+    pub samples: Vec<TelemetrySample>,
+    timer: TickTimer,
+    /// Ticks each team has held a ball for since the last sample.
+    possession: HashMap<Team, u32>,
+}
+
+impl Default for Telemetry {
+    fn default() -> Self {
+        Self {
+            samples: Vec::new(),
+            timer: TickTimer::from_ticks(SAMPLE_TICKS),
+            possession: HashMap::default(),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct TelemetrySample {
+    /// Hits each team has landed since the match started.
+    pub hits: HashMap<Team, u32>,
+    /// The share of the time since the last sample each team held a ball for, from zero to one.
+    pub possession: HashMap<Team, f32>,
+}
+
+fn reset_stats(mut stats: ResMut<MatchStats>, mut telemetry: ResMut<Telemetry>) {
+    stats.0.clear();
+    *telemetry = Telemetry::default();
+}
+
+fn count_stats(
+    mut stats: ResMut<MatchStats>,
+    mut hits: EventReader<Hit>,
+    mut catches: EventReader<Caught>,
+    teams: Query<&Team>,
+) {
+    for hit in hits.iter() {
+        let thrower = if let Some(thrower) = hit.thrower {
+            thrower
+        } else {
+            continue;
+        };
+        if teams.get(thrower).ok() != teams.get(hit.player).ok() {
+            stats.0.entry(thrower).or_default().hits += 1;
+        }
+    }
+    for caught in catches.iter() {
+        stats.0.entry(caught.player).or_default().catches += 1;
+    }
+}
+
+fn record_telemetry(
+    time: Res<GameTime>,
+    stats: Res<MatchStats>,
+    mut telemetry: ResMut<Telemetry>,
+    holders: Query<&Team, With<HoldingBall>>,
+    teams: Query<&Team>,
+) {
+    for team in &holders {
+        *telemetry.possession.entry(*team).or_default() += time.ticks();
+    }
+    if !telemetry.timer.tick(time.ticks()).just_finished() {
+        return;
+    }
+    telemetry.timer.reset();
+
+    let mut hits = HashMap::default();
+    for (player, player_stats) in &stats.0 {
+        if let Ok(team) = teams.get(*player) {
+            *hits.entry(*team).or_default() += player_stats.hits;
+        }
+    }
+    let possession = telemetry
+        .possession
+        .drain()
+        .map(|(team, ticks)| (team, (ticks as f32 / SAMPLE_TICKS as f32).min(1.0)))
+        .collect();
+    telemetry.samples.push(TelemetrySample { hits, possession });
+}
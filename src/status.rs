@@ -0,0 +1,92 @@
+use bevy::prelude::*;
+
+use crate::{game_time::GameTime, rules::match_active, tick_timer::TickTimer};
+
+pub struct StatusPlugin;
+
+impl Plugin for StatusPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(tick_status_effects.with_run_criteria(match_active));
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum StatusEffect {
+    /// Multiplies movement speed.
+    Haste(f32),
+    /// Scales the speed of balls flying at the player.
+    SlowIncoming(f32),
+    /// Can't be hit, so balls pass by harmlessly.
+    Invulnerable,
+}
+
+/// The status effects currently applied to a character.
+#[derive(Component, Default)]
+pub struct StatusEffects(Vec<ActiveEffect>);
+
+struct ActiveEffect {
+    effect: StatusEffect,
+    /// Effects without a timer last until they are removed.
+    timer: Option<TickTimer>,
+}
+
+impl StatusEffects {
+    pub fn add(&mut self, effect: StatusEffect, seconds: Option<f32>) {
+        self.0.push(ActiveEffect {
+            effect,
+            timer: seconds.map(TickTimer::from_seconds),
+        });
+    }
+
+    pub fn remove(&mut self, effect: StatusEffect) {
+        self.0.retain(|active| active.effect != effect);
+    }
+
+    pub fn contains(&self, effect: StatusEffect) -> bool {
+        self.0.iter().any(|active| active.effect == effect)
+    }
+
+    /// The fraction of time left on a timed effect.
+    pub fn percent_left(&self, effect: StatusEffect) -> Option<f32> {
+        self.0
+            .iter()
+            .filter(|active| active.effect == effect)
+            .find_map(|active| active.timer.as_ref().map(TickTimer::percent_left))
+    }
+
+    pub fn speed_multiplier(&self) -> f32 {
+        self.0
+            .iter()
+            .filter_map(|active| match active.effect {
+                StatusEffect::Haste(multiplier) => Some(multiplier),
+                StatusEffect::SlowIncoming(_) | StatusEffect::Invulnerable => None,
+            })
+            .product()
+    }
+
+    pub fn incoming_ball_scale(&self) -> f32 {
+        self.0
+            .iter()
+            .filter_map(|active| match active.effect {
+                StatusEffect::SlowIncoming(scale) => Some(scale),
+                StatusEffect::Haste(_) | StatusEffect::Invulnerable => None,
+            })
+            .product()
+    }
+}
+
+fn tick_status_effects(time: Res<GameTime>, mut characters: Query<&mut StatusEffects>) {
+    for mut effects in &mut characters {
+        if effects.0.iter().all(|active| active.timer.is_none()) {
+            continue;
+        }
+        for active in &mut effects.0 {
+            if let Some(timer) = &mut active.timer {
+                timer.tick(time.ticks());
+            }
+        }
+        effects
+            .0
+            .retain(|active| !active.timer.as_ref().map_or(false, TickTimer::finished));
+    }
+}
@@ -0,0 +1,131 @@
+use std::{f32::consts::TAU, time::Duration};
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use rand::Rng;
+
+use crate::{spawn_ball, stage::ActiveStage, GameState, BALL_DROP_HEIGHT};
+
+/// Frames timed per run, about ten seconds' worth.
+const STRESS_FRAMES: usize = 600;
+/// Frames left out at the start, so spawning everything doesn't count against the run.
+const WARMUP_FRAMES: usize = 30;
+const STRESS_BALL_SPEED: f32 = 8.0;
+
+/// A dev tool that fills the court with balls bouncing around and times the frames that
+/// follow. Started from the console with `stress <balls>`. Only in debug builds.
+///
+/// It only measures the build it runs in. To see what a scheduling or physics change did, run the
+/// same ball count on a build from before the change and one from after, and compare the logs.
+pub struct StressPlugin;
+
+impl Plugin for StressPlugin {
+    fn build(&self, app: &mut App) {
+        if cfg!(debug_assertions) {
+            app.add_event::<StartStressTest>().add_system_set(
+                SystemSet::on_update(GameState::Ready)
+                    .with_system(start_stress_test)
+                    .with_system(time_stress_test.after(start_stress_test)),
+            );
+        }
+    }
+}
+
+/// Starts a stress test with this many extra balls.
+pub struct StartStressTest(pub usize);
+
+/// A stress test in progress.
+struct StressTest {
+    balls: usize,
+    warmup: usize,
+    frames: Vec<Duration>,
+}
+
+#[derive(Component)]
+struct StressBall;
+
+fn start_stress_test(
+    mut commands: Commands,
+    mut events: EventReader<StartStressTest>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    stage: Option<Res<ActiveStage>>,
+    test: Option<Res<StressTest>>,
+) {
+    let balls = if let Some(StartStressTest(balls)) = events.iter().last() {
+        *balls
+    } else {
+        return;
+    };
+    let stage = if let Some(stage) = stage {
+        stage
+    } else {
+        return;
+    };
+    if test.is_some() {
+        warn!("A stress test is already running");
+        return;
+    }
+
+    let half = stage.size / 2.0;
+    let mut rng = rand::thread_rng();
+    for _ in 0..balls {
+        let position = Vec2::new(
+            rng.gen_range(-half.x..=half.x),
+            rng.gen_range(-half.y..=half.y),
+        );
+        let angle = rng.gen_range(0.0..TAU);
+        let ball = spawn_ball(
+            position.extend(BALL_DROP_HEIGHT),
+            &mut commands,
+            &mut meshes,
+        );
+        commands.entity(ball).insert_bundle((
+            Velocity::linear(Vec3::new(angle.cos(), angle.sin(), 0.0) * STRESS_BALL_SPEED),
+            StressBall,
+        ));
+    }
+    info!("Stress testing with {balls} balls over {STRESS_FRAMES} frames");
+    commands.insert_resource(StressTest {
+        balls,
+        warmup: WARMUP_FRAMES,
+        frames: Vec::with_capacity(STRESS_FRAMES),
+    });
+}
+
+/// Records real frame times until the run is over, then logs how they came out and clears the
+/// extra balls away.
+fn time_stress_test(
+    mut commands: Commands,
+    time: Res<Time>,
+    test: Option<ResMut<StressTest>>,
+    balls: Query<Entity, With<StressBall>>,
+) {
+    let mut test = if let Some(test) = test {
+        test
+    } else {
+        return;
+    };
+    if test.warmup > 0 {
+        test.warmup -= 1;
+        return;
+    }
+    test.frames.push(time.delta());
+    if test.frames.len() < STRESS_FRAMES {
+        return;
+    }
+
+    let mut frames = std::mem::take(&mut test.frames);
+    frames.sort();
+    let mean = frames.iter().sum::<Duration>() / frames.len() as u32;
+    let slowest = frames[frames.len() * 99 / 100];
+    info!(
+        "Stress test with {} balls: {:.2} ms mean frame, {:.2} ms 99th percentile",
+        test.balls,
+        mean.as_secs_f64() * 1000.0,
+        slowest.as_secs_f64() * 1000.0
+    );
+    for ball in &balls {
+        commands.entity(ball).despawn_recursive();
+    }
+    commands.remove_resource::<StressTest>();
+}
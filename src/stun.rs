@@ -0,0 +1,155 @@
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::*;
+
+use crate::{
+    character::{CharacterState, StateChanged},
+    cleanup::DespawnOnExit,
+    rules::match_active,
+    tween::{Ease, Tween},
+    ui::text::styled_text,
+    Action, GameState,
+};
+
+/// Stun ticks taken off by the first press; each press after that takes off this percentage of
+/// the one before, rounded down.
+const MASH_TICKS: u32 = 9;
+const MASH_FALLOFF_PERCENT: u32 = 85;
+/// How far above the character's center the mash prompt floats.
+const INDICATOR_HEIGHT: f32 = 0.6;
+const INDICATOR_POP_SCALE: f32 = 1.4;
+const INDICATOR_POP_SECONDS: f32 = 0.15;
+
+pub struct StunPlugin;
+
+impl Plugin for StunPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::new()
+                .with_run_criteria(match_active)
+                .with_system(track_stuns)
+                .with_system(mash_recovery.after(track_stuns)),
+        )
+        .add_system(position_indicators.after(mash_recovery));
+    }
+}
+
+/// Counts the presses a stunned character has mashed so far.
+///
+/// Recovery depends only on this count and the inputs, so it replays the same under rollback.
+#[derive(Component, Default)]
+pub struct MashRecovery {
+    pub presses: u32,
+}
+
+#[derive(Component)]
+struct StunIndicator(Entity);
+
+fn track_stuns(
+    mut commands: Commands,
+    mut events: EventReader<StateChanged>,
+    indicators: Query<(Entity, &StunIndicator)>,
+) {
+    for event in events.iter() {
+        match (event.from, event.to) {
+            (_, CharacterState::Stunned(_)) => {
+                commands
+                    .entity(event.entity)
+                    .insert(MashRecovery::default());
+                commands
+                    .spawn_bundle(styled_text("Mash!", "hud"))
+                    .insert_bundle((StunIndicator(event.entity), DespawnOnExit(GameState::Ready)))
+                    .insert(Style {
+                        position_type: PositionType::Absolute,
+                        ..default()
+                    });
+            }
+            (CharacterState::Stunned(_), _) => {
+                commands.entity(event.entity).remove::<MashRecovery>();
+                for (indicator, StunIndicator(character)) in &indicators {
+                    if *character == event.entity {
+                        commands.entity(indicator).despawn_recursive();
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn mash_recovery(
+    mut commands: Commands,
+    mut characters: Query<(
+        Entity,
+        &ActionState<Action>,
+        &mut CharacterState,
+        &mut MashRecovery,
+    )>,
+    indicators: Query<(Entity, &StunIndicator)>,
+) {
+    for (entity, action_state, mut state, mut mash) in &mut characters {
+        let mut timer = if let CharacterState::Stunned(timer) = *state {
+            timer
+        } else {
+            continue;
+        };
+        let mashed = [Action::Jump, Action::Throw, Action::Catch]
+            .into_iter()
+            .filter(|action| action_state.just_pressed(*action))
+            .count();
+        if mashed == 0 {
+            continue;
+        }
+
+        let mut recovered = 0;
+        for _ in 0..mashed {
+            recovered += mash_ticks(mash.presses);
+            mash.presses += 1;
+        }
+        timer.tick(recovered);
+        *state = CharacterState::Stunned(timer);
+
+        for (indicator, StunIndicator(character)) in &indicators {
+            if *character == entity {
+                commands.entity(indicator).insert(
+                    Tween::scale(
+                        Vec3::splat(INDICATOR_POP_SCALE),
+                        Vec3::ONE,
+                        INDICATOR_POP_SECONDS,
+                    )
+                    .ease(Ease::QuadOut),
+                );
+            }
+        }
+    }
+}
+
+/// The stun ticks a press takes off after `presses` earlier ones, in whole ticks so recovery
+/// comes out the same on every machine.
+fn mash_ticks(presses: u32) -> u32 {
+    (0..presses).fold(MASH_TICKS, |ticks, _| ticks * MASH_FALLOFF_PERCENT / 100)
+}
+
+/// Keeps each mash prompt floating above its character on screen.
+fn position_indicators(
+    cameras: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    characters: Query<&GlobalTransform>,
+    mut indicators: Query<(&StunIndicator, &mut Style)>,
+) {
+    let (camera, camera_transform) = if let Some(camera) = cameras.iter().next() {
+        camera
+    } else {
+        return;
+    };
+    for (StunIndicator(character), mut style) in &mut indicators {
+        let position = characters.get(*character).ok().and_then(|transform| {
+            camera.world_to_viewport(
+                camera_transform,
+                transform.translation() + Vec3::Z * INDICATOR_HEIGHT,
+            )
+        });
+        if let Some(position) = position {
+            style.position.left = Val::Px(position.x);
+            style.position.bottom = Val::Px(position.y);
+        }
+    }
+}
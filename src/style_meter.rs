@@ -0,0 +1,92 @@
+use bevy::{prelude::*, utils::HashSet};
+use bevy_rapier3d::prelude::*;
+
+use crate::{
+    hit::LIVE_BALL_SPEED,
+    rules::match_active,
+    status::{StatusEffect, StatusEffects},
+    Ball, HeldBy, Player,
+};
+
+const NEAR_MISS_RADIUS: f32 = 0.75;
+const NEAR_MISS_POINTS: f32 = 25.0;
+pub const STYLE_METER_MAX: f32 = 100.0;
+const BUFF_SECONDS: f32 = 5.0;
+/// Granted for filling the style meter.
+pub const STYLE_BUFF: StatusEffect = StatusEffect::Haste(1.25);
+
+pub struct StyleMeterPlugin;
+
+impl Plugin for StyleMeterPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<NearMiss>().add_system_set(
+            SystemSet::new()
+                .with_run_criteria(match_active)
+                .with_system(detect_near_misses)
+                .with_system(award_style.after(detect_near_misses)),
+        );
+    }
+}
+
+/// Style points earned by dodging. Filling the meter grants a short [`StatusEffect::Haste`].
+#[derive(Component, Default)]
+pub struct StyleMeter {
+    pub points: f32,
+}
+
+/// Sent when a live ball flies past a player without hitting them.
+pub struct NearMiss {
+    pub player: Entity,
+    pub ball: Entity,
+}
+
+/// Tracks balls flying at players while they are within the near-miss radius, and reports a
+/// near miss once a ball leaves the radius again without having touched the player.
+fn detect_near_misses(
+    mut passing: Local<HashSet<(Entity, Entity)>>,
+    mut collisions: EventReader<CollisionEvent>,
+    mut near_misses: EventWriter<NearMiss>,
+    balls: Query<(Entity, &GlobalTransform, &Velocity), (With<Ball>, Without<HeldBy>)>,
+    players: Query<(Entity, &GlobalTransform), With<Player>>,
+) {
+    for event in collisions.iter() {
+        if let CollisionEvent::Started(e1, e2, _) = event {
+            passing.remove(&(*e1, *e2));
+            passing.remove(&(*e2, *e1));
+        }
+    }
+
+    let mut still_passing = HashSet::default();
+    for (ball, ball_transform, velocity) in &balls {
+        for (player, player_transform) in &players {
+            let pair = (ball, player);
+            let offset = player_transform.translation() - ball_transform.translation();
+            if offset.length() <= NEAR_MISS_RADIUS {
+                let incoming = velocity.linvel.length() >= LIVE_BALL_SPEED
+                    && velocity.linvel.dot(offset) > 0.0;
+                if passing.contains(&pair) || incoming {
+                    still_passing.insert(pair);
+                }
+            } else if passing.contains(&pair) {
+                near_misses.send(NearMiss { player, ball });
+            }
+        }
+    }
+    *passing = still_passing;
+}
+
+fn award_style(
+    mut near_misses: EventReader<NearMiss>,
+    mut meters: Query<(&mut StyleMeter, &mut StatusEffects)>,
+) {
+    for near_miss in near_misses.iter() {
+        if let Ok((mut meter, mut effects)) = meters.get_mut(near_miss.player) {
+            meter.points += NEAR_MISS_POINTS;
+            if meter.points >= STYLE_METER_MAX {
+                meter.points = 0.0;
+                effects.remove(STYLE_BUFF);
+                effects.add(STYLE_BUFF, Some(BUFF_SECONDS));
+            }
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use bevy::{prelude::*, utils::HashSet};
+use leafwing_input_manager::prelude::*;
+
+use crate::{
+    ai::{drive_bots, Bot},
+    devices::PlayerSlot,
+    rules::match_active,
+    Action, PlayerName, BOT_SKILL,
+};
+
+pub struct TakeoverPlugin;
+
+impl Plugin for TakeoverPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(hand_over.with_run_criteria(match_active).before(drive_bots));
+    }
+}
+
+/// Hands a local player's character to a bot when they press takeover, and back again when they
+/// press it once more. The player's input keeps feeding their [`ActionState`] all along, and the
+/// bot overwrites it each frame while it's in control.
+///
+/// Runs before the bots do, so it still sees the player's own input.
+fn hand_over(
+    mut commands: Commands,
+    mut held: Local<HashSet<Entity>>,
+    players: Query<(Entity, &ActionState<Action>, &PlayerName, Option<&Bot>), With<PlayerSlot>>,
+) {
+    for (player, action_state, name, bot) in &players {
+        // Bots release every action each frame, so a held button is tracked here rather than
+        // trusting just_pressed.
+        if !action_state.pressed(Action::Takeover) {
+            held.remove(&player);
+            continue;
+        }
+        if !held.insert(player) {
+            continue;
+        }
+        if bot.is_some() {
+            info!("{} has taken back control", name.0);
+            commands.entity(player).remove::<Bot>();
+        } else {
+            info!("{} has handed control to a bot", name.0);
+            commands.entity(player).insert(Bot::new(BOT_SKILL));
+        }
+    }
+}
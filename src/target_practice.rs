@@ -0,0 +1,279 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    cleanup::DespawnOnExit,
+    devices::PlayerSlot,
+    game_time::GameTime,
+    profile::Profile,
+    rules::{match_active, MatchMode, Ruleset},
+    stage::ActiveStage,
+    tick_timer::{TickTimer, TICK_RATE},
+    Ball, BallState, GameState, ThrownBy,
+};
+
+/// How long a round of target practice lasts.
+const ROUND_SECONDS: f32 = 60.0;
+/// How often a new target pops up.
+const SPAWN_SECONDS: f32 = 1.5;
+/// How long a target stays up before it drops out unhit.
+const TARGET_SECONDS: f32 = 4.0;
+const TARGET_RADIUS: f32 = 0.4;
+/// How likely a new target is to sway from side to side, which makes it worth more.
+const MOVING_CHANCE: f64 = 0.35;
+const MOVING_POINTS: u32 = 3;
+/// How far a moving target sways either way, and how many sways it makes a second.
+const SWAY_DISTANCE: f32 = 1.5;
+const SWAY_RATE: f32 = 0.4;
+/// Targets stay this far from the center line and the sidelines, so each one is a real throw.
+const CENTER_MARGIN: f32 = 3.0;
+const EDGE_MARGIN: f32 = 0.6;
+
+pub struct TargetPracticePlugin;
+
+impl Plugin for TargetPracticePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_enter(GameState::Ready).with_system(start_target_practice),
+        )
+        .add_system_set(SystemSet::on_exit(GameState::Ready).with_system(end_target_practice))
+        .add_system_set(
+            SystemSet::new()
+                .with_run_criteria(match_active)
+                .with_system(spawn_targets)
+                .with_system(sway_targets)
+                .with_system(score_targets)
+                .with_system(expire_targets)
+                .with_system(finish_round.after(score_targets)),
+        );
+    }
+}
+
+/// The target practice round being played, and how it's going.
+pub struct TargetPracticeRun {
+    pub timer: TickTimer,
+    pub score: u32,
+    /// Where the score placed in the high scores once the round is over, if it made them.
+    pub rank: Option<usize>,
+    pub finished: bool,
+    spawn_timer: TickTimer,
+    rng: StdRng,
+    material: Handle<StandardMaterial>,
+    moving_material: Handle<StandardMaterial>,
+    mesh: Handle<Mesh>,
+}
+
+impl TargetPracticeRun {
+    pub fn seconds_left(&self) -> f32 {
+        self.timer.remaining_ticks() as f32 / TICK_RATE as f32
+    }
+}
+
+#[derive(Component)]
+struct Target {
+    points: u32,
+    lifetime: TickTimer,
+    sway: Option<Sway>,
+}
+
+struct Sway {
+    origin: Vec3,
+    elapsed: f32,
+}
+
+fn start_target_practice(
+    mut commands: Commands,
+    ruleset: Res<Ruleset>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    if ruleset.mode != MatchMode::TargetPractice {
+        return;
+    }
+    info!("Starting target practice");
+    commands.insert_resource(TargetPracticeRun {
+        timer: TickTimer::from_seconds(ROUND_SECONDS),
+        score: 0,
+        rank: None,
+        finished: false,
+        spawn_timer: TickTimer::from_seconds(SPAWN_SECONDS),
+        rng: StdRng::from_entropy(),
+        material: materials.add(StandardMaterial {
+            base_color: Color::rgb(0.95, 0.95, 0.95),
+            unlit: true,
+            ..default()
+        }),
+        moving_material: materials.add(StandardMaterial {
+            base_color: Color::rgb(0.95, 0.75, 0.1),
+            unlit: true,
+            ..default()
+        }),
+        mesh: meshes.add(
+            shape::Icosphere {
+                radius: TARGET_RADIUS,
+                ..default()
+            }
+            .into(),
+        ),
+    });
+}
+
+fn end_target_practice(mut commands: Commands) {
+    commands.remove_resource::<TargetPracticeRun>();
+}
+
+/// Pops targets up around the half of the court across from the player.
+fn spawn_targets(
+    mut commands: Commands,
+    time: Res<GameTime>,
+    run: Option<ResMut<TargetPracticeRun>>,
+    stage: Option<Res<ActiveStage>>,
+    players: Query<&GlobalTransform, With<PlayerSlot>>,
+) {
+    let (mut run, stage) = if let (Some(run), Some(stage)) = (run, stage) {
+        (run, stage)
+    } else {
+        return;
+    };
+    if run.finished || !run.spawn_timer.tick(time.ticks()).finished() {
+        return;
+    }
+    run.spawn_timer.reset();
+
+    let side = players
+        .iter()
+        .next()
+        .map_or(1.0, |transform| -transform.translation().y.signum());
+    let half_width = (stage.size.x / 2.0 - EDGE_MARGIN).max(0.0);
+    let half_length = (stage.size.y / 2.0 - EDGE_MARGIN).max(CENTER_MARGIN);
+    let position = Vec3::new(
+        run.rng.gen_range(-half_width..=half_width),
+        side * run.rng.gen_range(CENTER_MARGIN..=half_length),
+        run.rng.gen_range(0.5..=1.5),
+    );
+    let moving = run.rng.gen_bool(MOVING_CHANCE);
+    let (points, material, sway) = if moving {
+        let sway = Sway {
+            origin: position,
+            elapsed: 0.0,
+        };
+        (MOVING_POINTS, run.moving_material.clone(), Some(sway))
+    } else {
+        (1, run.material.clone(), None)
+    };
+    commands
+        .spawn_bundle(PbrBundle {
+            mesh: run.mesh.clone(),
+            material,
+            transform: Transform::from_translation(position),
+            ..default()
+        })
+        .insert_bundle((
+            Collider::ball(TARGET_RADIUS),
+            Sensor,
+            Target {
+                points,
+                lifetime: TickTimer::from_seconds(TARGET_SECONDS),
+                sway,
+            },
+            DespawnOnExit(GameState::Ready),
+        ));
+}
+
+fn sway_targets(
+    time: Res<GameTime>,
+    stage: Option<Res<ActiveStage>>,
+    mut targets: Query<(&mut Target, &mut Transform)>,
+) {
+    let half_width = stage.map_or(f32::MAX, |stage| stage.size.x / 2.0 - EDGE_MARGIN);
+    for (mut target, mut transform) in &mut targets {
+        if let Some(sway) = &mut target.sway {
+            sway.elapsed += time.delta_seconds();
+            let offset = (sway.elapsed * SWAY_RATE * std::f32::consts::TAU).sin() * SWAY_DISTANCE;
+            transform.translation.x = (sway.origin.x + offset).clamp(-half_width, half_width);
+        }
+    }
+}
+
+/// Scores a target for the player when a ball they threw passes through it.
+fn score_targets(
+    mut commands: Commands,
+    mut collisions: EventReader<CollisionEvent>,
+    run: Option<ResMut<TargetPracticeRun>>,
+    targets: Query<&Target>,
+    balls: Query<(&BallState, Option<&ThrownBy>), With<Ball>>,
+    players: Query<(), With<PlayerSlot>>,
+) {
+    let mut run = if let Some(run) = run {
+        run
+    } else {
+        return;
+    };
+    for event in collisions.iter() {
+        let (e1, e2) = if let CollisionEvent::Started(e1, e2, _) = event {
+            (*e1, *e2)
+        } else {
+            continue;
+        };
+        let (target, ball) = if targets.contains(e1) {
+            (e1, e2)
+        } else if targets.contains(e2) {
+            (e2, e1)
+        } else {
+            continue;
+        };
+        let thrown_by_player = matches!(
+            balls.get(ball),
+            Ok((BallState::Live, Some(ThrownBy(thrower)))) if players.contains(*thrower)
+        );
+        if run.finished || !thrown_by_player {
+            continue;
+        }
+        if let Ok(Target { points, .. }) = targets.get(target) {
+            run.score += points;
+            info!("Hit a target for {points}, score {}", run.score);
+            commands.entity(target).despawn_recursive();
+        }
+    }
+}
+
+fn expire_targets(
+    mut commands: Commands,
+    time: Res<GameTime>,
+    mut targets: Query<(Entity, &mut Target)>,
+) {
+    for (entity, mut target) in &mut targets {
+        if target.lifetime.tick(time.ticks()).finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+/// Ends the round once time is up, clearing the targets and keeping the score if it's a high
+/// score.
+fn finish_round(
+    mut commands: Commands,
+    time: Res<GameTime>,
+    run: Option<ResMut<TargetPracticeRun>>,
+    mut profile: ResMut<Profile>,
+    targets: Query<Entity, With<Target>>,
+) {
+    let mut run = if let Some(run) = run {
+        run
+    } else {
+        return;
+    };
+    if run.finished || !run.timer.tick(time.ticks()).finished() {
+        return;
+    }
+    run.finished = true;
+    for target in &targets {
+        commands.entity(target).despawn_recursive();
+    }
+    info!("Target practice over with {} points", run.score);
+    run.rank = profile.record_target_score(run.score);
+    if run.rank.is_some() {
+        profile.save();
+    }
+}
@@ -0,0 +1,113 @@
+use bevy::prelude::*;
+
+/// Colors on offer for teams, in the order they are handed out.
+pub const TEAM_PALETTE: [(&str, Color); 8] = [
+    ("Red", Color::rgb(0.9, 0.2, 0.2)),
+    ("Blue", Color::rgb(0.2, 0.4, 0.95)),
+    ("Yellow", Color::rgb(0.95, 0.85, 0.2)),
+    ("Green", Color::rgb(0.2, 0.8, 0.3)),
+    ("Purple", Color::rgb(0.6, 0.3, 0.9)),
+    ("Orange", Color::rgb(0.95, 0.55, 0.1)),
+    ("Cyan", Color::rgb(0.2, 0.85, 0.9)),
+    ("Pink", Color::rgb(0.95, 0.45, 0.75)),
+];
+/// Colors closer than this are too easy to mistake for each other to give to two teams.
+const CLASH_DISTANCE: f32 = 0.3;
+
+pub struct TeamPlugin;
+
+impl Plugin for TeamPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TeamColors>()
+            .add_system(apply_team_tints);
+    }
+}
+
+/// The side a character plays for.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Team(pub usize);
+
+/// The color each team is shown in, chosen in match setup.
+pub struct TeamColors(Vec<Color>);
+
+impl Default for TeamColors {
+    fn default() -> Self {
+        Self(vec![TEAM_PALETTE[0].1, TEAM_PALETTE[1].1])
+    }
+}
+
+impl TeamColors {
+    pub fn get(&self, team: Team) -> Color {
+        self.0
+            .get(team.0)
+            .copied()
+            .unwrap_or(TEAM_PALETTE[team.0 % TEAM_PALETTE.len()].1)
+    }
+
+    /// Gives a team its color, moving any other team with a color too close to it to the next
+    /// free one in the palette.
+    pub fn set(&mut self, team: Team, color: Color) {
+        if self.0.len() <= team.0 {
+            let len = self.0.len();
+            self.0
+                .extend((len..=team.0).map(|index| self.get(Team(index))));
+        }
+        self.0[team.0] = color;
+        for other in 0..self.0.len() {
+            if other == team.0 || !self.clashes(other, self.0[other]) {
+                continue;
+            }
+            if let Some((_, free)) = TEAM_PALETTE
+                .iter()
+                .find(|(_, candidate)| !self.clashes(other, *candidate))
+            {
+                self.0[other] = *free;
+            }
+        }
+    }
+
+    /// Whether a color is too close to one already used by a team other than this one.
+    fn clashes(&self, team: usize, color: Color) -> bool {
+        self.0
+            .iter()
+            .enumerate()
+            .any(|(other, used)| other != team && color_distance(color, *used) < CLASH_DISTANCE)
+    }
+}
+
+fn color_distance(a: Color, b: Color) -> f32 {
+    Vec3::new(a.r() - b.r(), a.g() - b.g(), a.b() - b.b()).length()
+}
+
+/// Recolors an entity's material to match a team, blending from white by `strength`.
+#[derive(Component)]
+pub struct TeamTint {
+    pub team: Team,
+    pub strength: f32,
+}
+
+fn apply_team_tints(
+    colors: Res<TeamColors>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut tinted: Query<(
+        &TeamTint,
+        ChangeTrackers<TeamTint>,
+        &mut Handle<StandardMaterial>,
+    )>,
+) {
+    for (tint, tracker, mut handle) in &mut tinted {
+        if !colors.is_changed() && !tracker.is_changed() {
+            continue;
+        }
+        let color = colors.get(tint.team);
+        let base = Vec4::ONE.lerp(Vec4::from(color.as_rgba_f32()), tint.strength);
+        // Each entity gets a material of its own, since sprites share theirs with every other
+        // sprite from the same atlas.
+        if let Some(material) = materials.get(&*handle).cloned() {
+            *handle = materials.add(StandardMaterial {
+                base_color: Color::rgba(base.x, base.y, base.z, material.base_color.a()),
+                ..material
+            });
+        }
+    }
+}
@@ -0,0 +1,312 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use leafwing_input_manager::prelude::*;
+
+use crate::{
+    ball_kinds::BallKindTable,
+    ball_physics::BallPhysicsConfig,
+    block::Blocking,
+    character::{CharacterState, CharacterStats, StateChanged, ThrowPhase},
+    curve::{Curve, CurveSettings},
+    game_time::GameTime,
+    graphics::SpriteDepth,
+    input_buffer::InputBuffer,
+    release_held_ball,
+    rules::match_active,
+    tick_timer::{seconds_to_ticks, TickTimer},
+    tween::{Ease, Tween},
+    Action, BallState, DataAssets, Facing, HoldingBall, Player, ThrownBy,
+};
+
+const FEINT_RECOVERY_SECONDS: f32 = 0.2;
+/// The sprite leans back into this squash while winding up a throw.
+const WIND_UP_SCALE: Vec3 = Vec3::new(0.85, 1.1, 1.0);
+/// How far in front of the thrower the ball is released, and how high.
+const THROW_REACH: f32 = 0.4;
+const THROW_HEIGHT: f32 = 0.1;
+/// Holding throw for this long charges it fully.
+pub const FULL_CHARGE_SECONDS: f32 = 1.0;
+/// Launch speeds of a throw released straight away and of a fully charged one.
+const MIN_THROW_SPEED: f32 = 8.0;
+pub const MAX_THROW_SPEED: f32 = 14.0;
+/// How far above the release point a throw peaks.
+const THROW_ARC_HEIGHT: f32 = 0.8;
+/// A lob peaks high enough to clear anyone in the way, landing this far off when released
+/// straight away and when fully charged.
+const LOB_ARC_HEIGHT: f32 = 3.0;
+const MIN_LOB_DISTANCE: f32 = 9.0;
+const MAX_LOB_DISTANCE: f32 = 13.0;
+/// The least a throw heads towards the other half, so aiming sideways still sends it across.
+const MIN_FORWARD_AIM: f32 = 0.5;
+
+/// Recovering from a throw, during which the player can't throw or jump and moves slowly.
+#[derive(Component)]
+pub struct ThrowRecovery(TickTimer);
+
+/// Marks a player winding up a lob rather than a straight throw.
+#[derive(Component)]
+pub struct Lobbing;
+
+pub struct ThrowPlugin;
+
+impl Plugin for ThrowPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::new()
+                .with_run_criteria(match_active)
+                .with_system(start_throw)
+                .with_system(advance_throws.after(start_throw))
+                .with_system(recover_from_throws),
+        )
+        .add_system(throw_poses);
+    }
+}
+
+/// Starts winding up a throw. Blocking players have their ball raised, so they can't throw it
+/// until they lower it again.
+pub fn start_throw(
+    mut commands: Commands,
+    mut players: Query<
+        (
+            Entity,
+            &ActionState<Action>,
+            &mut InputBuffer,
+            &mut CharacterState,
+        ),
+        (
+            With<Player>,
+            With<HoldingBall>,
+            Without<ThrowRecovery>,
+            Without<Blocking>,
+        ),
+    >,
+) {
+    for (entity, action_state, mut buffer, mut state) in &mut players {
+        if !state.is_free() {
+            continue;
+        }
+        // A lob cut short by a hit leaves its marker behind, so a straight throw clears it.
+        if buffer.take(action_state, Action::Throw) {
+            *state = CharacterState::Throwing(ThrowPhase::WindUp(0));
+            commands.entity(entity).remove::<Lobbing>();
+        } else if buffer.take(action_state, Action::Lob) {
+            *state = CharacterState::Throwing(ThrowPhase::WindUp(0));
+            commands.entity(entity).insert(Lobbing);
+        }
+    }
+}
+
+fn advance_throws(
+    mut commands: Commands,
+    time: Res<GameTime>,
+    rapier_config: Res<RapierConfiguration>,
+    ball_physics: Res<BallPhysicsConfig>,
+    curve_settings: Res<CurveSettings>,
+    data: Res<DataAssets>,
+    tables: Res<Assets<BallKindTable>>,
+    mut players: Query<(
+        Entity,
+        &mut CharacterState,
+        &ActionState<Action>,
+        &GlobalTransform,
+        &Facing,
+        &CharacterStats,
+        &Children,
+        Option<&HoldingBall>,
+        Option<&Lobbing>,
+    )>,
+    sprites: Query<(), With<SpriteDepth>>,
+) {
+    let ticks = time.ticks();
+    let gravity = -rapier_config.gravity.z * ball_physics.gravity_scale;
+    for (entity, mut state, action_state, transform, facing, stats, children, held, lobbing) in
+        &mut players
+    {
+        let phase = if let CharacterState::Throwing(phase) = *state {
+            phase
+        } else {
+            continue;
+        };
+        // The ball can be lost while winding up, dropped by a block under the drop ball rule,
+        // which leaves nothing to throw.
+        if held.is_none() && matches!(phase, ThrowPhase::WindUp(_)) {
+            *state = CharacterState::Idle;
+            commands.entity(entity).remove::<Lobbing>();
+            continue;
+        }
+        let button = if lobbing.is_some() {
+            Action::Lob
+        } else {
+            Action::Throw
+        };
+        *state = match phase {
+            ThrowPhase::WindUp(_) if action_state.just_pressed(Action::Feint) => {
+                pose(
+                    &mut commands,
+                    children,
+                    &sprites,
+                    WIND_UP_SCALE,
+                    Vec3::ONE,
+                    stats.wind_up_seconds,
+                );
+                commands.entity(entity).remove::<Lobbing>();
+                CharacterState::Throwing(ThrowPhase::Feint(TickTimer::from_seconds(
+                    FEINT_RECOVERY_SECONDS,
+                )))
+            }
+            ThrowPhase::WindUp(elapsed)
+                if elapsed + ticks >= seconds_to_ticks(stats.wind_up_seconds)
+                    && !action_state.pressed(button) =>
+            {
+                // Checked for above, as there's nothing to wind up without a ball.
+                let held = if let Some(held) = held {
+                    held
+                } else {
+                    continue;
+                };
+                let charge = state.throw_charge().unwrap_or_default();
+                let kind = held.kind;
+                let properties = tables
+                    .get(&data.ball_kinds)
+                    .map(|table| table.get(kind))
+                    .unwrap_or_default();
+                let aim = aim(facing.0, transform.translation().y);
+                let (release, launch) = planned_throw(
+                    transform,
+                    facing.0,
+                    charge,
+                    lobbing.is_some(),
+                    properties.speed,
+                    gravity,
+                );
+                let ball = release_held_ball(entity, held, release, &mut commands);
+                let mut velocity = Velocity::linear(launch);
+                if action_state.pressed(Action::Curve) {
+                    let curve = Curve::new(&curve_settings, aim, transform.translation());
+                    velocity.angvel = curve.spin(&curve_settings);
+                    commands.entity(ball).insert(curve);
+                }
+                commands.entity(ball).insert_bundle((
+                    velocity,
+                    ThrownBy(entity),
+                    BallState::Live,
+                    kind,
+                ));
+                commands
+                    .entity(entity)
+                    .remove::<Lobbing>()
+                    .insert(ThrowRecovery(TickTimer::from_seconds(
+                        stats.throw_recovery_seconds,
+                    )));
+                CharacterState::Idle
+            }
+            ThrowPhase::WindUp(elapsed) => {
+                CharacterState::Throwing(ThrowPhase::WindUp(elapsed + ticks))
+            }
+            ThrowPhase::Feint(mut timer) => {
+                if timer.tick(ticks).finished() {
+                    CharacterState::Idle
+                } else {
+                    CharacterState::Throwing(ThrowPhase::Feint(timer))
+                }
+            }
+        };
+    }
+}
+
+/// Where a throw with this much charge would be released from, and the velocity it would be
+/// launched at. `speed` scales straight throws, for balls that fly faster or slower than usual.
+pub fn planned_throw(
+    transform: &GlobalTransform,
+    facing: Vec2,
+    charge: f32,
+    lobbing: bool,
+    speed: f32,
+    gravity: f32,
+) -> (Vec3, Vec3) {
+    let aim = aim(facing, transform.translation().y);
+    let release = transform.translation() + (aim * THROW_REACH).extend(THROW_HEIGHT);
+    let launch = if lobbing {
+        let distance = MIN_LOB_DISTANCE + (MAX_LOB_DISTANCE - MIN_LOB_DISTANCE) * charge;
+        lob(aim, distance, gravity)
+    } else {
+        let throw_speed = MIN_THROW_SPEED + (MAX_THROW_SPEED - MIN_THROW_SPEED) * charge;
+        (aim * throw_speed * speed).extend(launch_speed(THROW_ARC_HEIGHT, gravity))
+    };
+    (release, launch)
+}
+
+/// Throws go the way the player is facing, but always towards the other half of the court, so
+/// backing off while throwing doesn't lob the ball at a teammate.
+fn aim(facing: Vec2, court_y: f32) -> Vec2 {
+    let forward = if court_y > 0.0 { -1.0 } else { 1.0 };
+    Vec2::new(facing.x, facing.y.abs().max(MIN_FORWARD_AIM) * forward).normalize()
+}
+
+fn recover_from_throws(
+    mut commands: Commands,
+    time: Res<GameTime>,
+    mut players: Query<(Entity, &mut ThrowRecovery)>,
+) {
+    for (entity, mut recovery) in &mut players {
+        if recovery.0.tick(time.ticks()).finished() {
+            commands.entity(entity).remove::<ThrowRecovery>();
+        }
+    }
+}
+
+/// The upwards speed a ball needs to peak `height` above where it was released.
+fn launch_speed(height: f32, gravity: f32) -> f32 {
+    (2.0 * gravity * height).sqrt()
+}
+
+/// Launches a ball so that it peaks at [`LOB_ARC_HEIGHT`] and comes back down to the height it
+/// was released at `distance` away.
+fn lob(aim: Vec2, distance: f32, gravity: f32) -> Vec3 {
+    let lift = launch_speed(LOB_ARC_HEIGHT, gravity);
+    let flight_seconds = 2.0 * lift / gravity;
+    (aim * distance / flight_seconds).extend(lift)
+}
+
+/// Leans the sprite back when a throw starts, and straightens it up again once the ball is
+/// released.
+fn throw_poses(
+    mut commands: Commands,
+    mut events: EventReader<StateChanged>,
+    characters: Query<(&Children, &CharacterStats)>,
+    sprites: Query<(), With<SpriteDepth>>,
+) {
+    for event in events.iter() {
+        let (from, to) = match (event.from, event.to) {
+            (_, CharacterState::Throwing(_)) => (Vec3::ONE, WIND_UP_SCALE),
+            // A feint has already straightened up by the time it ends.
+            (CharacterState::Throwing(ThrowPhase::WindUp(_)), _) => (WIND_UP_SCALE, Vec3::ONE),
+            _ => continue,
+        };
+        if let Ok((children, stats)) = characters.get(event.entity) {
+            pose(
+                &mut commands,
+                children,
+                &sprites,
+                from,
+                to,
+                stats.wind_up_seconds,
+            );
+        }
+    }
+}
+
+fn pose(
+    commands: &mut Commands,
+    children: &Children,
+    sprites: &Query<(), With<SpriteDepth>>,
+    from: Vec3,
+    to: Vec3,
+    seconds: f32,
+) {
+    for sprite in children.iter().filter(|child| sprites.contains(**child)) {
+        commands
+            .entity(*sprite)
+            .insert(Tween::scale(from, to, seconds).ease(Ease::QuadOut));
+    }
+}
@@ -0,0 +1,131 @@
+use serde::{Deserialize, Serialize};
+
+/// Gameplay ticks per second of [`GameTime`](crate::game_time::GameTime).
+pub const TICK_RATE: u32 = 60;
+
+/// Rounds `seconds` to the nearest whole tick, for durations tuned in seconds.
+pub fn seconds_to_ticks(seconds: f32) -> u32 {
+    (seconds.max(0.0) * TICK_RATE as f32).round() as u32
+}
+
+/// A countdown measured in whole gameplay ticks rather than wall-clock time, so it comes out the
+/// same on every machine and can be saved and restored as plain data for rollback.
+///
+/// Cooldowns, invulnerability, timed character states, round timers and power-up durations all
+/// count down with this.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub struct TickTimer {
+    duration: u32,
+    elapsed: u32,
+    just_finished: bool,
+    /// Whether a tick has already seen the timer finished, so it only just finishes once.
+    done: bool,
+}
+
+impl TickTimer {
+    pub fn from_ticks(duration: u32) -> Self {
+        Self {
+            duration,
+            elapsed: 0,
+            just_finished: false,
+            done: false,
+        }
+    }
+
+    /// Rounds `seconds` to the nearest whole tick.
+    pub fn from_seconds(seconds: f32) -> Self {
+        Self::from_ticks(seconds_to_ticks(seconds))
+    }
+
+    /// Advances the timer by this many ticks, usually
+    /// [`GameTime::ticks`](crate::game_time::GameTime::ticks).
+    pub fn tick(&mut self, ticks: u32) -> &Self {
+        self.elapsed = self.elapsed.saturating_add(ticks).min(self.duration);
+        self.just_finished = self.finished() && !self.done;
+        self.done = self.finished();
+        self
+    }
+
+    pub fn finished(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+
+    /// Whether the timer finished during the last call to [`TickTimer::tick`].
+    pub fn just_finished(&self) -> bool {
+        self.just_finished
+    }
+
+    pub fn elapsed_ticks(&self) -> u32 {
+        self.elapsed
+    }
+
+    pub fn remaining_ticks(&self) -> u32 {
+        self.duration - self.elapsed
+    }
+
+    /// The fraction of the timer left to run, for meters and fading effects.
+    pub fn percent_left(&self) -> f32 {
+        if self.duration == 0 {
+            0.0
+        } else {
+            self.remaining_ticks() as f32 / self.duration as f32
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.elapsed = 0;
+        self.just_finished = false;
+        self.done = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TickTimer;
+
+    #[test]
+    fn just_finishes_once() {
+        let mut timer = TickTimer::from_ticks(3);
+        assert!(!timer.tick(2).just_finished());
+        assert!(timer.tick(1).just_finished());
+        assert!(!timer.tick(1).just_finished());
+        assert!(!timer.tick(0).just_finished());
+        assert!(timer.finished());
+    }
+
+    #[test]
+    fn finishes_partway_through_a_tick() {
+        let mut timer = TickTimer::from_ticks(3);
+        assert!(timer.tick(5).just_finished());
+        assert!(!timer.tick(5).just_finished());
+    }
+
+    #[test]
+    fn zero_duration_finishes_on_first_tick() {
+        let mut timer = TickTimer::from_ticks(0);
+        assert!(timer.finished());
+        assert!(timer.tick(0).just_finished());
+        assert!(!timer.tick(1).just_finished());
+        assert_eq!(TickTimer::from_seconds(0.0), TickTimer::from_ticks(0));
+    }
+
+    #[test]
+    fn tick_saturates_once_finished() {
+        let mut timer = TickTimer::from_ticks(4);
+        timer.tick(3);
+        timer.tick(u32::MAX);
+        assert_eq!(timer.elapsed_ticks(), 4);
+        assert_eq!(timer.remaining_ticks(), 0);
+        timer.tick(u32::MAX);
+        assert_eq!(timer.elapsed_ticks(), 4);
+    }
+
+    #[test]
+    fn reset_finishes_again() {
+        let mut timer = TickTimer::from_ticks(2);
+        assert!(timer.tick(2).just_finished());
+        timer.reset();
+        assert!(!timer.finished());
+        assert!(timer.tick(2).just_finished());
+    }
+}
@@ -0,0 +1,277 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use leafwing_input_manager::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    character::CharacterState,
+    elimination::{Reinstate, Sidelined},
+    hold_ball,
+    interactions::BallKind,
+    rules::{match_active, MatchMode, Ruleset},
+    spawn_ball,
+    ui::widgets::MenuAction,
+    Ball, BallState, HeldBy, HoldingBall, Player, ThrownBy,
+};
+
+/// Where saved scenarios are kept, relative to the working directory.
+const SCENARIO_DIR: &str = "scenarios";
+const SCENARIO_EXTENSION: &str = "scenario.ron";
+
+pub struct TrainingPlugin;
+
+impl Plugin for TrainingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ActiveScenario>()
+            .add_event::<LoadScenario>()
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(match_active)
+                    .with_system(save_scenario)
+                    .with_system(quick_reset)
+                    .with_system(load_scenario.after(quick_reset)),
+            );
+    }
+}
+
+/// A frozen training situation: where everyone and every ball is and how they are moving.
+///
+/// Players are matched up by spawn order, so a scenario fits any match with the same lineup.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Scenario {
+    pub name: String,
+    players: Vec<PlayerSnapshot>,
+    balls: Vec<BallSnapshot>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct PlayerSnapshot {
+    position: [f32; 3],
+    velocity: [f32; 3],
+    state: CharacterState,
+    holding_ball: bool,
+    /// The kind of ball held, if holding one.
+    #[serde(default)]
+    held_kind: BallKind,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct BallSnapshot {
+    position: [f32; 3],
+    velocity: [f32; 3],
+    spin: [f32; 3],
+    #[serde(default)]
+    kind: BallKind,
+    /// Index of the player whose throw the ball is.
+    thrown_by: Option<usize>,
+}
+
+/// What a snapshot reads from each player.
+pub type SnapshotPlayers<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static GlobalTransform,
+        Option<&'static Velocity>,
+        &'static CharacterState,
+        Option<&'static HoldingBall>,
+    ),
+    With<Player>,
+>;
+
+/// What a snapshot reads from each loose ball. Held balls are saved with their holder.
+pub type SnapshotBalls<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static GlobalTransform,
+        Option<&'static Velocity>,
+        &'static BallKind,
+        Option<&'static ThrownBy>,
+    ),
+    (With<Ball>, Without<HeldBy>),
+>;
+
+impl Scenario {
+    /// Freezes the court as it is right now.
+    pub fn capture(name: String, players: &SnapshotPlayers, balls: &SnapshotBalls) -> Self {
+        let mut players = players.iter().collect::<Vec<_>>();
+        players.sort_by_key(|(entity, ..)| *entity);
+        Self {
+            name,
+            players: players
+                .iter()
+                .map(
+                    |(_, transform, velocity, state, holding_ball)| PlayerSnapshot {
+                        position: transform.translation().to_array(),
+                        velocity: velocity.map_or([0.0; 3], |velocity| velocity.linvel.to_array()),
+                        state: **state,
+                        holding_ball: holding_ball.is_some(),
+                        held_kind: holding_ball.map_or_else(BallKind::default, |held| held.kind),
+                    },
+                )
+                .collect(),
+            balls: balls
+                .iter()
+                .map(|(transform, velocity, kind, thrown_by)| BallSnapshot {
+                    position: transform.translation().to_array(),
+                    velocity: velocity.map_or([0.0; 3], |velocity| velocity.linvel.to_array()),
+                    spin: velocity.map_or([0.0; 3], |velocity| velocity.angvel.to_array()),
+                    kind: *kind,
+                    thrown_by: thrown_by.and_then(|ThrownBy(thrower)| {
+                        players.iter().position(|(player, ..)| player == thrower)
+                    }),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// The scenario quick reset goes back to.
+#[derive(Default)]
+pub struct ActiveScenario(pub Option<Scenario>);
+
+/// Puts the court back into a scenario and binds it to quick reset.
+pub struct LoadScenario(pub Scenario);
+
+/// Scenarios saved to disk, oldest first.
+pub fn saved_scenarios() -> Vec<PathBuf> {
+    let mut paths = fs::read_dir(SCENARIO_DIR)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| path.to_string_lossy().ends_with(SCENARIO_EXTENSION))
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    paths.sort();
+    paths
+}
+
+pub fn read_scenario(path: &Path) -> Option<Scenario> {
+    let bytes = fs::read(path)
+        .map_err(|error| warn!("Couldn't read scenario {path:?}: {error}"))
+        .ok()?;
+    ron::de::from_bytes(&bytes)
+        .map_err(|error| warn!("Couldn't parse scenario {path:?}: {error}"))
+        .ok()
+}
+
+fn write_scenario(scenario: &Scenario, path: &Path) -> std::io::Result<()> {
+    let ron = ron::ser::to_string_pretty(scenario, ron::ser::PrettyConfig::default())
+        .map_err(|error| std::io::Error::new(std::io::ErrorKind::Other, error))?;
+    fs::create_dir_all(SCENARIO_DIR)?;
+    fs::write(path, ron)
+}
+
+fn save_scenario(
+    ruleset: Res<Ruleset>,
+    action_state: Res<ActionState<MenuAction>>,
+    mut active: ResMut<ActiveScenario>,
+    players: SnapshotPlayers,
+    balls: SnapshotBalls,
+) {
+    if ruleset.mode != MatchMode::Training || !action_state.just_pressed(MenuAction::SaveScenario) {
+        return;
+    }
+    let number = saved_scenarios().len() + 1;
+    let scenario = Scenario::capture(format!("Scenario {number}"), &players, &balls);
+    let path = Path::new(SCENARIO_DIR).join(format!("scenario-{number}.{SCENARIO_EXTENSION}"));
+    match write_scenario(&scenario, &path) {
+        Ok(()) => info!("Saved {} to {path:?}", scenario.name),
+        Err(error) => warn!("Couldn't save scenario to {path:?}: {error}"),
+    }
+    active.0 = Some(scenario);
+}
+
+fn quick_reset(
+    ruleset: Res<Ruleset>,
+    action_state: Res<ActionState<MenuAction>>,
+    active: Res<ActiveScenario>,
+    mut loads: EventWriter<LoadScenario>,
+) {
+    if ruleset.mode != MatchMode::Training || !action_state.just_pressed(MenuAction::ResetScenario)
+    {
+        return;
+    }
+    if let Some(scenario) = &active.0 {
+        loads.send(LoadScenario(scenario.clone()));
+    }
+}
+
+fn load_scenario(
+    mut commands: Commands,
+    mut events: EventReader<LoadScenario>,
+    mut active: ResMut<ActiveScenario>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut reinstate: EventWriter<Reinstate>,
+    mut players: Query<
+        (
+            Entity,
+            &mut Transform,
+            &mut Velocity,
+            &mut CharacterState,
+            Option<&Sidelined>,
+        ),
+        With<Player>,
+    >,
+    balls: Query<Entity, With<Ball>>,
+) {
+    let scenario = if let Some(LoadScenario(scenario)) = events.iter().last() {
+        scenario
+    } else {
+        return;
+    };
+    let mut players = players.iter_mut().collect::<Vec<_>>();
+    players.sort_by_key(|(entity, ..)| *entity);
+    for ((entity, transform, velocity, state, sidelined), snapshot) in
+        players.iter_mut().zip(&scenario.players)
+    {
+        transform.translation = Vec3::from(snapshot.position);
+        **velocity = Velocity::linear(Vec3::from(snapshot.velocity));
+        // Players going out are sidelined as their state changes, like any elimination, but those
+        // coming back have to be reinstated to get their body back and leave jail.
+        if sidelined.is_some() && snapshot.state != CharacterState::Eliminated {
+            reinstate.send(Reinstate {
+                player: *entity,
+                position: transform.translation.truncate(),
+            });
+        } else {
+            **state = snapshot.state;
+        }
+        if snapshot.holding_ball {
+            let ball = spawn_ball(transform.translation, &mut commands, &mut meshes);
+            commands.entity(ball).insert(snapshot.held_kind);
+            hold_ball(*entity, ball, snapshot.held_kind, &mut commands);
+        } else {
+            commands.entity(*entity).remove::<HoldingBall>();
+        }
+    }
+
+    for ball in &balls {
+        commands.entity(ball).despawn_recursive();
+    }
+    for snapshot in &scenario.balls {
+        let ball = spawn_ball(Vec3::from(snapshot.position), &mut commands, &mut meshes);
+        commands.entity(ball).insert_bundle((
+            Velocity {
+                linvel: Vec3::from(snapshot.velocity),
+                angvel: Vec3::from(snapshot.spin),
+            },
+            snapshot.kind,
+        ));
+        if let Some((thrower, ..)) = snapshot.thrown_by.and_then(|index| players.get(index)) {
+            commands
+                .entity(ball)
+                .insert_bundle((ThrownBy(*thrower), BallState::Live));
+        }
+    }
+    info!("Loaded {}", scenario.name);
+    active.0 = Some(scenario.clone());
+}
@@ -0,0 +1,161 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::{
+    ball_kinds::BallKindTable,
+    ball_physics::BallPhysicsConfig,
+    character::CharacterState,
+    cleanup::DespawnOnExit,
+    devices::PlayerSlot,
+    rules::match_active,
+    throw::{planned_throw, Lobbing},
+    DataAssets, Facing, GameState, HoldingBall,
+};
+
+/// How many dots trace out the arc of a throw being charged.
+const ARC_DOTS: usize = 12;
+const DOT_RADIUS: f32 = 0.04;
+const MARKER_RADIUS: f32 = 0.25;
+/// How flat the landing marker is squashed, so it lies on the floor.
+const MARKER_THICKNESS: f32 = 0.1;
+const PREVIEW_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.35);
+/// Balls rest this high above the floor, so the arc ends where they touch down.
+const BALL_RADIUS: f32 = 0.1;
+/// Arcs are cut short after this long, for throws that would sail off the court.
+const MAX_FLIGHT_SECONDS: f32 = 3.0;
+
+pub struct TrajectoryPlugin;
+
+impl Plugin for TrajectoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(preview_trajectories.with_run_criteria(match_active));
+    }
+}
+
+/// The dots and landing marker showing a local player where their throw will go.
+#[derive(Component)]
+struct TrajectoryPreview {
+    dots: Vec<Entity>,
+    marker: Entity,
+}
+
+/// Traces the path of a throw while a local player charges it, using the same launch math as
+/// the throw itself. Curving spin isn't traced, since the curve only kicks in mid-flight.
+fn preview_trajectories(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut assets: Local<Option<(Handle<Mesh>, Handle<Mesh>, Handle<StandardMaterial>)>>,
+    rapier_config: Res<RapierConfiguration>,
+    ball_physics: Res<BallPhysicsConfig>,
+    data: Res<DataAssets>,
+    tables: Res<Assets<BallKindTable>>,
+    players: Query<
+        (
+            Entity,
+            &CharacterState,
+            &GlobalTransform,
+            &Facing,
+            Option<&HoldingBall>,
+            Option<&Lobbing>,
+            Option<&TrajectoryPreview>,
+        ),
+        With<PlayerSlot>,
+    >,
+    mut parts: Query<(&mut Transform, &mut Visibility), Without<PlayerSlot>>,
+) {
+    let (dot_mesh, marker_mesh, material) = assets
+        .get_or_insert_with(|| {
+            let sphere = |radius| {
+                meshes.add(
+                    shape::Icosphere {
+                        radius,
+                        subdivisions: 1,
+                    }
+                    .into(),
+                )
+            };
+            let material = materials.add(StandardMaterial {
+                base_color: PREVIEW_COLOR,
+                alpha_mode: AlphaMode::Blend,
+                unlit: true,
+                ..default()
+            });
+            (sphere(DOT_RADIUS), sphere(MARKER_RADIUS), material)
+        })
+        .clone();
+    let gravity = -rapier_config.gravity.z * ball_physics.gravity_scale;
+
+    for (entity, state, transform, facing, held, lobbing, preview) in &players {
+        let charge = state.throw_charge().filter(|_| held.is_some());
+        let preview = if let Some(preview) = preview {
+            preview
+        } else {
+            if charge.is_some() {
+                let mut spawn = |mesh: &Handle<Mesh>, scale| {
+                    commands
+                        .spawn_bundle(PbrBundle {
+                            mesh: mesh.clone(),
+                            material: material.clone(),
+                            transform: Transform::from_scale(scale),
+                            visibility: Visibility { is_visible: false },
+                            ..default()
+                        })
+                        .insert(DespawnOnExit(GameState::Ready))
+                        .id()
+                };
+                let dots = (0..ARC_DOTS).map(|_| spawn(&dot_mesh, Vec3::ONE)).collect();
+                let marker = spawn(&marker_mesh, Vec3::new(1.0, 1.0, MARKER_THICKNESS));
+                commands
+                    .entity(entity)
+                    .insert(TrajectoryPreview { dots, marker });
+            }
+            continue;
+        };
+
+        let charge = if let Some(charge) = charge {
+            charge
+        } else {
+            for part in preview.dots.iter().chain([&preview.marker]) {
+                if let Ok((_, mut visibility)) = parts.get_mut(*part) {
+                    visibility.is_visible = false;
+                }
+            }
+            continue;
+        };
+        let kind = held.map(|held| held.kind).unwrap_or_default();
+        let speed = tables
+            .get(&data.ball_kinds)
+            .map(|table| table.get(kind))
+            .unwrap_or_default()
+            .speed;
+        let (release, launch) = planned_throw(
+            transform,
+            facing.0,
+            charge,
+            lobbing.is_some(),
+            speed,
+            gravity,
+        );
+        let position_at =
+            |seconds: f32| release + launch * seconds - Vec3::Z * 0.5 * gravity * seconds * seconds;
+        // When the ball comes back down to the floor, from solving its height for time.
+        let drop = (release.z - BALL_RADIUS).max(0.0);
+        let landing_seconds = ((launch.z + (launch.z * launch.z + 2.0 * gravity * drop).sqrt())
+            / gravity)
+            .min(MAX_FLIGHT_SECONDS);
+
+        for (index, dot) in preview.dots.iter().enumerate() {
+            if let Ok((mut dot_transform, mut visibility)) = parts.get_mut(*dot) {
+                let seconds = landing_seconds * (index + 1) as f32 / (ARC_DOTS + 1) as f32;
+                dot_transform.translation = position_at(seconds);
+                visibility.is_visible = true;
+            }
+        }
+        if let Ok((mut marker_transform, mut visibility)) = parts.get_mut(preview.marker) {
+            let landing = position_at(landing_seconds);
+            marker_transform.translation = landing.truncate().extend(landing.z - BALL_RADIUS);
+            visibility.is_visible = true;
+        }
+    }
+}
@@ -0,0 +1,218 @@
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+
+pub struct TweenPlugin;
+
+impl Plugin for TweenPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<TweenCompleted>()
+            .add_system(animate_tweens::<Transform>)
+            .add_system(animate_tweens::<Style>)
+            .add_system(animate_tweens::<UiColor>)
+            .add_system(animate_tweens::<Text>);
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum Ease {
+    Linear,
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    CubicOut,
+    SineInOut,
+    BackOut,
+    ElasticOut,
+}
+
+impl Ease {
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Ease::Linear => t,
+            Ease::QuadIn => t * t,
+            Ease::QuadOut => 1.0 - (1.0 - t) * (1.0 - t),
+            Ease::QuadInOut => {
+                if t < 0.5 {
+                    2.0 * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(2) / 2.0
+                }
+            }
+            Ease::CubicOut => 1.0 - (1.0 - t).powi(3),
+            Ease::SineInOut => -((PI * t).cos() - 1.0) / 2.0,
+            Ease::BackOut => {
+                const OVERSHOOT: f32 = 1.701_58;
+                1.0 + (OVERSHOOT + 1.0) * (t - 1.0).powi(3) + OVERSHOOT * (t - 1.0).powi(2)
+            }
+            Ease::ElasticOut => {
+                if t <= 0.0 || t >= 1.0 {
+                    t
+                } else {
+                    2.0_f32.powf(-10.0 * t) * ((t * 10.0 - 0.75) * (2.0 * PI / 3.0)).sin() + 1.0
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Repeat {
+    /// Plays once, then removes the tween and sends [`TweenCompleted`].
+    Once,
+    Loop,
+    PingPong,
+}
+
+/// Sent when a [`Repeat::Once`] tween has finished.
+pub struct TweenCompleted {
+    pub entity: Entity,
+}
+
+/// Animates a component of type `T` by feeding eased progress into a lens.
+///
+/// ```ignore
+/// commands.entity(hud_element).insert(Tween::scale(Vec3::ZERO, Vec3::ONE, 0.25).ease(Ease::BackOut));
+/// ```
+#[derive(Component)]
+pub struct Tween<T: Component> {
+    lens: Box<dyn Fn(&mut T, f32) + Send + Sync>,
+    seconds: f32,
+    elapsed: f32,
+    ease: Ease,
+    repeat: Repeat,
+    reversed: bool,
+}
+
+impl<T: Component> Tween<T> {
+    pub fn new(seconds: f32, lens: impl Fn(&mut T, f32) + Send + Sync + 'static) -> Self {
+        Self {
+            lens: Box::new(lens),
+            seconds,
+            elapsed: 0.0,
+            ease: Ease::Linear,
+            repeat: Repeat::Once,
+            reversed: false,
+        }
+    }
+
+    pub fn ease(mut self, ease: Ease) -> Self {
+        self.ease = ease;
+        self
+    }
+
+    pub fn repeat(mut self, repeat: Repeat) -> Self {
+        self.repeat = repeat;
+        self
+    }
+}
+
+impl Tween<Transform> {
+    pub fn transform(from: Transform, to: Transform, seconds: f32) -> Self {
+        Self::new(seconds, move |transform, t| {
+            transform.translation = from.translation.lerp(to.translation, t);
+            transform.rotation = from.rotation.slerp(to.rotation, t);
+            transform.scale = from.scale.lerp(to.scale, t);
+        })
+    }
+
+    pub fn translation(from: Vec3, to: Vec3, seconds: f32) -> Self {
+        Self::new(seconds, move |transform, t| {
+            transform.translation = from.lerp(to, t);
+        })
+    }
+
+    pub fn rotation(from: Quat, to: Quat, seconds: f32) -> Self {
+        Self::new(seconds, move |transform, t| {
+            transform.rotation = from.slerp(to, t);
+        })
+    }
+
+    pub fn scale(from: Vec3, to: Vec3, seconds: f32) -> Self {
+        Self::new(seconds, move |transform, t| {
+            transform.scale = from.lerp(to, t)
+        })
+    }
+}
+
+impl Tween<Style> {
+    /// Slides a node between two offsets from the top of its parent, in percent.
+    pub fn slide_top(from: f32, to: f32, seconds: f32) -> Self {
+        Self::new(seconds, move |style, t| {
+            style.position.top = Val::Percent(from + (to - from) * t);
+        })
+    }
+
+    /// Slides a node between two offsets from the left of its parent, in percent.
+    pub fn slide_left(from: f32, to: f32, seconds: f32) -> Self {
+        Self::new(seconds, move |style, t| {
+            style.position.left = Val::Percent(from + (to - from) * t);
+        })
+    }
+}
+
+impl Tween<UiColor> {
+    pub fn color(from: Color, to: Color, seconds: f32) -> Self {
+        Self::new(seconds, move |color, t| color.0 = lerp_color(from, to, t))
+    }
+}
+
+impl Tween<Text> {
+    pub fn color(from: Color, to: Color, seconds: f32) -> Self {
+        Self::new(seconds, move |text, t| {
+            for section in &mut text.sections {
+                section.style.color = lerp_color(from, to, t);
+            }
+        })
+    }
+}
+
+fn lerp_color(from: Color, to: Color, t: f32) -> Color {
+    let from = Vec4::from(from.as_rgba_f32());
+    let to = Vec4::from(to.as_rgba_f32());
+    let color = from.lerp(to, t);
+    Color::rgba(color.x, color.y, color.z, color.w)
+}
+
+fn animate_tweens<T: Component>(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut completed: EventWriter<TweenCompleted>,
+    mut tweens: Query<(Entity, &mut Tween<T>, &mut T)>,
+) {
+    for (entity, mut tween, mut target) in &mut tweens {
+        tween.elapsed += time.delta_seconds();
+        let mut finished = false;
+        if tween.elapsed >= tween.seconds {
+            match tween.repeat {
+                Repeat::Once => {
+                    tween.elapsed = tween.seconds;
+                    finished = true;
+                }
+                Repeat::Loop => tween.elapsed -= tween.seconds,
+                Repeat::PingPong => {
+                    tween.elapsed -= tween.seconds;
+                    tween.reversed = !tween.reversed;
+                }
+            }
+        }
+
+        let progress = if tween.seconds > 0.0 {
+            tween.elapsed / tween.seconds
+        } else {
+            1.0
+        };
+        let progress = if tween.reversed {
+            1.0 - progress
+        } else {
+            progress
+        };
+        (tween.lens)(&mut target, tween.ease.apply(progress));
+
+        if finished {
+            commands.entity(entity).remove::<Tween<T>>();
+            completed.send(TweenCompleted { entity });
+        }
+    }
+}
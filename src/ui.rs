@@ -0,0 +1,43 @@
+use bevy::prelude::*;
+
+mod challenge_hud;
+mod console;
+mod damage_indicators;
+mod frame_data;
+mod hud;
+mod input_overlay;
+mod minimap;
+mod name_tags;
+mod results;
+mod scenario_browser;
+mod scoreboard;
+mod settings_menu;
+mod setup_wizard;
+mod target_practice_hud;
+pub mod text;
+pub mod theme;
+pub mod widgets;
+
+pub struct UiPlugin;
+
+impl Plugin for UiPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(widgets::WidgetPlugin)
+            .add_plugin(theme::ThemePlugin)
+            .add_plugin(text::TextStylePlugin)
+            .add_plugin(settings_menu::SettingsMenuPlugin)
+            .add_plugin(setup_wizard::SetupWizardPlugin)
+            .add_plugin(minimap::MinimapPlugin)
+            .add_plugin(hud::HudPlugin)
+            .add_plugin(challenge_hud::ChallengeHudPlugin)
+            .add_plugin(target_practice_hud::TargetPracticeHudPlugin)
+            .add_plugin(input_overlay::InputOverlayPlugin)
+            .add_plugin(frame_data::FrameDataPlugin)
+            .add_plugin(console::ConsolePlugin)
+            .add_plugin(damage_indicators::DamageIndicatorPlugin)
+            .add_plugin(scoreboard::ScoreboardPlugin)
+            .add_plugin(results::ResultsPlugin)
+            .add_plugin(scenario_browser::ScenarioBrowserPlugin)
+            .add_plugin(name_tags::NameTagPlugin);
+    }
+}
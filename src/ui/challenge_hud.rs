@@ -0,0 +1,88 @@
+use bevy::prelude::*;
+
+use crate::{
+    challenge::{ChallengeRun, Outcome},
+    cleanup::DespawnOnExit,
+    profile::Medal,
+    GameState,
+};
+
+use super::text::styled_text;
+
+const HUD_MARGIN: f32 = 16.0;
+
+pub struct ChallengeHudPlugin;
+
+impl Plugin for ChallengeHudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(spawn_challenge_hud)
+            .add_system(update_challenge_hud.after(spawn_challenge_hud));
+    }
+}
+
+#[derive(Component)]
+struct ChallengeHud;
+
+#[derive(Component)]
+struct ChallengeStatus;
+
+fn spawn_challenge_hud(mut commands: Commands, run: Option<Res<ChallengeRun>>) {
+    let run = if let Some(run) = run {
+        run
+    } else {
+        return;
+    };
+    if !run.is_added() {
+        return;
+    }
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    top: Val::Px(HUD_MARGIN),
+                    ..default()
+                },
+                size: Size::new(Val::Percent(100.0), Val::Auto),
+                flex_direction: FlexDirection::ColumnReverse,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            color: Color::NONE.into(),
+            ..default()
+        })
+        .insert_bundle((ChallengeHud, DespawnOnExit(GameState::Ready)))
+        .with_children(|hud| {
+            hud.spawn_bundle(styled_text(run.challenge.name.clone(), "hud"));
+            hud.spawn_bundle(styled_text("", "title"))
+                .insert(ChallengeStatus);
+        });
+}
+
+fn update_challenge_hud(
+    run: Option<Res<ChallengeRun>>,
+    mut statuses: Query<&mut Text, With<ChallengeStatus>>,
+) {
+    let run = if let Some(run) = run {
+        run
+    } else {
+        return;
+    };
+    let status = match run.outcome {
+        Some(Outcome::Passed(Medal::Gold)) => "Gold!".to_string(),
+        Some(Outcome::Passed(Medal::Silver)) => "Silver!".to_string(),
+        Some(Outcome::Passed(Medal::Bronze)) => "Bronze!".to_string(),
+        Some(Outcome::Failed) => "Failed".to_string(),
+        None => format!(
+            "{}/{}  {:.1}",
+            run.progress,
+            run.challenge.count,
+            run.seconds_left()
+        ),
+    };
+    for mut text in &mut statuses {
+        if text.sections[0].value != status {
+            text.sections[0].value = status.clone();
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use bevy::prelude::*;
+
+use crate::{
+    game_time::GameTime,
+    input::{InputContext, InputLayer},
+    stage::transition::LoadStage,
+    stress::StartStressTest,
+};
+
+use super::text::styled_text;
+
+const CONSOLE_LEFT: f32 = 16.0;
+const CONSOLE_TOP: f32 = 16.0;
+
+/// A debug console for tweaking the game while it runs, opened and closed with the key left
+/// of 1. Only in debug builds.
+pub struct ConsolePlugin;
+
+impl Plugin for ConsolePlugin {
+    fn build(&self, app: &mut App) {
+        if cfg!(debug_assertions) {
+            app.add_system(toggle_console)
+                .add_system(edit_console.after(toggle_console));
+        }
+    }
+}
+
+#[derive(Component, Default)]
+struct Console {
+    input: String,
+    /// What the last command run replied with.
+    output: String,
+}
+
+fn toggle_console(
+    mut commands: Commands,
+    keys: Res<Input<KeyCode>>,
+    consoles: Query<Entity, With<Console>>,
+) {
+    if !keys.just_pressed(KeyCode::Grave) {
+        return;
+    }
+    if let Some(console) = consoles.iter().next() {
+        commands.entity(console).despawn_recursive();
+        return;
+    }
+    commands
+        .spawn_bundle(styled_text("", "hud"))
+        .insert_bundle((Console::default(), InputLayer(InputContext::Console)))
+        .insert(Style {
+            position_type: PositionType::Absolute,
+            position: UiRect {
+                left: Val::Px(CONSOLE_LEFT),
+                top: Val::Px(CONSOLE_TOP),
+                ..default()
+            },
+            ..default()
+        });
+}
+
+fn edit_console(
+    mut typed: EventReader<ReceivedCharacter>,
+    keys: Res<Input<KeyCode>>,
+    mut game_time: ResMut<GameTime>,
+    mut load_stage: EventWriter<LoadStage>,
+    mut stress_tests: EventWriter<StartStressTest>,
+    mut consoles: Query<(&mut Console, &mut Text)>,
+) {
+    // Read even while closed, so what was typed then doesn't show up once it opens.
+    let typed = typed
+        .iter()
+        .map(|event| event.char)
+        .filter(|character| !character.is_control() && *character != '`')
+        .collect::<String>();
+    let (mut console, mut text) = if let Some(console) = consoles.iter_mut().next() {
+        console
+    } else {
+        return;
+    };
+    if !typed.is_empty() {
+        console.input.push_str(&typed);
+    }
+    if keys.just_pressed(KeyCode::Back) {
+        console.input.pop();
+    }
+    if keys.just_pressed(KeyCode::Return) {
+        let line = std::mem::take(&mut console.input);
+        console.output = run_command(&line, &mut game_time, &mut load_stage, &mut stress_tests);
+        info!("> {line}: {}", console.output);
+    }
+    if console.is_changed() {
+        text.sections[0].value = format!("{}\n> {}", console.output, console.input);
+    }
+}
+
+/// Runs a line typed into the console, returning the reply to show for it.
+fn run_command(
+    line: &str,
+    game_time: &mut GameTime,
+    load_stage: &mut EventWriter<LoadStage>,
+    stress_tests: &mut EventWriter<StartStressTest>,
+) -> String {
+    let mut words = line.split_whitespace();
+    match (words.next(), words.next()) {
+        (Some("timescale"), Some(scale)) => match scale.parse::<f32>() {
+            Ok(scale) => {
+                game_time.set_scale(scale);
+                format!("Gameplay runs at {}x", scale.max(0.0))
+            }
+            Err(_) => format!("Not a time scale: {scale}"),
+        },
+        (Some("timescale"), None) => "Usage: timescale <scale>".to_string(),
+        (Some("stage"), Some(path)) => {
+            load_stage.send(LoadStage(path.to_string()));
+            format!("Changing stage to {path}")
+        }
+        (Some("stage"), None) => "Usage: stage <path>, such as stages/court.stage.ron".to_string(),
+        (Some("stress"), Some(balls)) => match balls.parse::<usize>() {
+            Ok(balls) => {
+                stress_tests.send(StartStressTest(balls));
+                format!("Stress testing with {balls} balls")
+            }
+            Err(_) => format!("Not a ball count: {balls}"),
+        },
+        (Some("stress"), None) => "Usage: stress <balls>".to_string(),
+        (Some(command), _) => format!("Unknown command: {command}"),
+        (None, _) => String::new(),
+    }
+}
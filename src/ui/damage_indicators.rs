@@ -0,0 +1,112 @@
+use bevy::prelude::*;
+
+use crate::{
+    cleanup::DespawnOnExit,
+    devices::PlayerSlot,
+    hit::Hit,
+    tween::{Ease, Tween, TweenCompleted},
+    GameState,
+};
+
+use super::widgets::WidgetStyle;
+
+const INDICATOR_SIZE: Vec2 = Vec2::new(10.0, 64.0);
+/// Distance from the indicator to the edge of the screen.
+const INDICATOR_MARGIN: f32 = 24.0;
+const FLASH_SECONDS: f32 = 0.8;
+
+pub struct DamageIndicatorPlugin;
+
+impl Plugin for DamageIndicatorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(show_damage_indicators)
+            .add_system(remove_damage_indicators);
+    }
+}
+
+#[derive(Component)]
+struct DamageIndicator;
+
+/// Flashes a marker at the edge of the screen when a local player is hit by a ball thrown from
+/// somewhere they can't see, pointing towards where it came from.
+fn show_damage_indicators(
+    mut commands: Commands,
+    mut hits: EventReader<Hit>,
+    style: Res<WidgetStyle>,
+    cameras: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    players: Query<&GlobalTransform, With<PlayerSlot>>,
+) {
+    let (camera, camera_transform) = if let Some(camera) = cameras.iter().next() {
+        camera
+    } else {
+        return;
+    };
+    let screen = if let Some(screen) = camera.logical_viewport_size() {
+        screen
+    } else {
+        return;
+    };
+
+    for hit in hits.iter() {
+        let player = if let Ok(player) = players.get(hit.player) {
+            player.translation()
+        } else {
+            continue;
+        };
+        let on_screen = camera
+            .world_to_viewport(camera_transform, hit.source)
+            .map_or(false, |position| {
+                position.cmpge(Vec2::ZERO).all() && position.cmple(screen).all()
+            });
+        if on_screen {
+            continue;
+        }
+
+        // The court runs left to right on screen, with its far side at the top.
+        let offset = hit.source - player;
+        let direction = Vec2::new(offset.y, -offset.x).normalize_or_zero();
+        if direction == Vec2::ZERO {
+            continue;
+        }
+        let half = screen / 2.0 - Vec2::splat(INDICATOR_MARGIN);
+        let reach = (half.x / direction.x.abs()).min(half.y / direction.y.abs());
+        let position = screen / 2.0 + direction * reach - INDICATOR_SIZE / 2.0;
+
+        commands
+            .spawn_bundle(NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        left: Val::Px(position.x),
+                        bottom: Val::Px(position.y),
+                        ..default()
+                    },
+                    size: Size::new(Val::Px(INDICATOR_SIZE.x), Val::Px(INDICATOR_SIZE.y)),
+                    ..default()
+                },
+                color: style.accent_color.into(),
+                // Lies along the edge of the screen, facing the way the ball came from.
+                transform: Transform::from_rotation(Quat::from_rotation_z(
+                    direction.y.atan2(direction.x),
+                )),
+                ..default()
+            })
+            .insert_bundle((
+                DamageIndicator,
+                Tween::color(style.accent_color, Color::NONE, FLASH_SECONDS).ease(Ease::QuadIn),
+                DespawnOnExit(GameState::Ready),
+            ));
+    }
+}
+
+fn remove_damage_indicators(
+    mut commands: Commands,
+    mut completed: EventReader<TweenCompleted>,
+    indicators: Query<(), With<DamageIndicator>>,
+) {
+    for TweenCompleted { entity } in completed.iter() {
+        if indicators.contains(*entity) {
+            commands.entity(*entity).despawn_recursive();
+        }
+    }
+}
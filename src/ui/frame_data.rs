@@ -0,0 +1,181 @@
+use bevy::prelude::*;
+
+use crate::{
+    catch::CatchCooldown,
+    character::{CharacterState, ThrowPhase},
+    cleanup::DespawnOnExit,
+    devices::PlayerSlot,
+    game_time::GameTime,
+    rules::{match_active, MatchMode, Ruleset},
+    status::{StatusEffect, StatusEffects},
+    team::TeamTint,
+    throw::ThrowRecovery,
+    GameState, Player,
+};
+
+use super::text::styled_text;
+
+const FRAME_DATA_LEFT: f32 = 16.0;
+/// Sits just above the input overlay.
+const FRAME_DATA_BOTTOM: f32 = 56.0;
+/// What invulnerable characters are tinted, so their i-frames can be seen while tuning them.
+const IFRAME_COLOR: Color = Color::rgb(0.3, 1.0, 1.0);
+
+pub struct FrameDataPlugin;
+
+impl Plugin for FrameDataPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FrameCount>()
+            .add_system_set(SystemSet::on_enter(GameState::Ready).with_system(spawn_frame_data))
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(match_active)
+                    .with_system(count_frames)
+                    .with_system(update_frame_data.after(count_frames))
+                    .with_system(show_iframes),
+            );
+    }
+}
+
+/// Ticks spent in each part of the local player's latest move, counted as it plays out.
+#[derive(Default)]
+struct FrameCount {
+    name: &'static str,
+    startup: u32,
+    active: u32,
+    recovery: u32,
+    previous: Option<CharacterState>,
+}
+
+impl FrameCount {
+    fn start(&mut self, name: &'static str) {
+        *self = Self {
+            name,
+            previous: self.previous,
+            ..default()
+        };
+    }
+}
+
+#[derive(Component)]
+struct FrameDataText;
+
+/// Marks a character sprite tinted to show its i-frames.
+#[derive(Component)]
+struct IFrameTint;
+
+fn spawn_frame_data(mut commands: Commands, ruleset: Res<Ruleset>) {
+    if ruleset.mode != MatchMode::Training {
+        return;
+    }
+    commands
+        .spawn_bundle(styled_text("", "hud"))
+        .insert_bundle((FrameDataText, DespawnOnExit(GameState::Ready)))
+        .insert(Style {
+            position_type: PositionType::Absolute,
+            position: UiRect {
+                left: Val::Px(FRAME_DATA_LEFT),
+                bottom: Val::Px(FRAME_DATA_BOTTOM),
+                ..default()
+            },
+            ..default()
+        });
+}
+
+/// Reads the character state machine each tick. Throws start up while winding up, are active on
+/// the tick the ball leaves and recover afterwards, or from a feint. Catches are active from the
+/// press, for as long as the window is open, and recover through the cooldown after a whiff.
+fn count_frames(
+    time: Res<GameTime>,
+    mut count: ResMut<FrameCount>,
+    players: Query<
+        (
+            &CharacterState,
+            Option<&CatchCooldown>,
+            Option<&ThrowRecovery>,
+        ),
+        With<PlayerSlot>,
+    >,
+) {
+    let (state, cooldown, throw_recovery) = if let Some(player) = players.iter().next() {
+        player
+    } else {
+        return;
+    };
+    let previous = count.previous.replace(*state);
+    let was_winding_up = matches!(
+        previous,
+        Some(CharacterState::Throwing(ThrowPhase::WindUp(_)))
+    );
+    let ticks = time.ticks();
+    match *state {
+        CharacterState::Throwing(ThrowPhase::WindUp(_)) => {
+            if !was_winding_up {
+                count.start("Throw");
+            }
+            count.startup += ticks;
+        }
+        CharacterState::Throwing(ThrowPhase::Feint(_)) => count.recovery += ticks,
+        CharacterState::Catching(_) => {
+            if !matches!(previous, Some(CharacterState::Catching(_))) {
+                count.start("Catch");
+            }
+            count.active += ticks;
+        }
+        _ if was_winding_up => count.active = 1,
+        _ if throw_recovery.is_some() && count.name == "Throw" => count.recovery += ticks,
+        _ if cooldown.is_some() && count.name == "Catch" => count.recovery += ticks,
+        _ => {}
+    }
+}
+
+fn update_frame_data(count: Res<FrameCount>, mut texts: Query<&mut Text, With<FrameDataText>>) {
+    if !count.is_changed() || count.name.is_empty() {
+        return;
+    }
+    for mut text in &mut texts {
+        text.sections[0].value = format!(
+            "{}  startup {}  active {}  recovery {}",
+            count.name, count.startup, count.active, count.recovery
+        );
+    }
+}
+
+/// Tints characters while they are [invulnerable](StatusEffect::Invulnerable), in training and
+/// in development builds, then hands their color back to their team tint.
+fn show_iframes(
+    mut commands: Commands,
+    ruleset: Res<Ruleset>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    characters: Query<(&StatusEffects, &Children), With<Player>>,
+    mut sprites: Query<(
+        &mut TeamTint,
+        &Handle<StandardMaterial>,
+        Option<&IFrameTint>,
+    )>,
+) {
+    if ruleset.mode != MatchMode::Training && !cfg!(debug_assertions) {
+        return;
+    }
+    for (effects, children) in &characters {
+        let invulnerable = effects.contains(StatusEffect::Invulnerable);
+        for child in children.iter() {
+            let (mut tint, handle, tinted) = if let Ok(sprite) = sprites.get_mut(*child) {
+                sprite
+            } else {
+                continue;
+            };
+            if invulnerable && tinted.is_none() {
+                if let Some(material) = materials.get_mut(handle) {
+                    let alpha = material.base_color.a();
+                    material.base_color = IFRAME_COLOR;
+                    material.base_color.set_a(alpha);
+                }
+                commands.entity(*child).insert(IFrameTint);
+            } else if !invulnerable && tinted.is_some() {
+                tint.set_changed();
+                commands.entity(*child).remove::<IFrameTint>();
+            }
+        }
+    }
+}
@@ -0,0 +1,111 @@
+use bevy::prelude::*;
+
+use crate::{
+    cleanup::DespawnOnExit,
+    devices::PlayerSlot,
+    rules::match_active,
+    status::StatusEffects,
+    style_meter::{NearMiss, StyleMeter, STYLE_BUFF, STYLE_METER_MAX},
+    tween::{Ease, Tween},
+    GameState,
+};
+
+use super::{text::styled_text, widgets::WidgetStyle};
+
+const HUD_MARGIN: f32 = 16.0;
+const METER_SIZE: Vec2 = Vec2::new(160.0, 12.0);
+const POP_SCALE: f32 = 1.3;
+const POP_SECONDS: f32 = 0.3;
+
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_enter(GameState::Ready).with_system(spawn_style_meter))
+            .add_system(update_style_meter.with_run_criteria(match_active));
+    }
+}
+
+#[derive(Component)]
+struct StyleMeterBar;
+
+#[derive(Component)]
+struct StyleMeterFill;
+
+fn spawn_style_meter(mut commands: Commands, style: Res<WidgetStyle>) {
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    left: Val::Px(HUD_MARGIN),
+                    top: Val::Px(HUD_MARGIN),
+                    ..default()
+                },
+                flex_direction: FlexDirection::ColumnReverse,
+                ..default()
+            },
+            color: Color::NONE.into(),
+            ..default()
+        })
+        .insert(DespawnOnExit(GameState::Ready))
+        .with_children(|hud| {
+            hud.spawn_bundle(styled_text("Style", "hud"));
+            hud.spawn_bundle(NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Px(METER_SIZE.x), Val::Px(METER_SIZE.y)),
+                    ..default()
+                },
+                color: style.panel_color.into(),
+                ..default()
+            })
+            .insert(StyleMeterBar)
+            .with_children(|bar| {
+                bar.spawn_bundle(NodeBundle {
+                    style: Style {
+                        size: Size::new(Val::Percent(0.0), Val::Percent(100.0)),
+                        ..default()
+                    },
+                    color: style.accent_color.into(),
+                    ..default()
+                })
+                .insert(StyleMeterFill);
+            });
+        });
+}
+
+fn update_style_meter(
+    mut commands: Commands,
+    style: Res<WidgetStyle>,
+    mut near_misses: EventReader<NearMiss>,
+    players: Query<(Entity, &StyleMeter, &StatusEffects), With<PlayerSlot>>,
+    bars: Query<Entity, With<StyleMeterBar>>,
+    mut fills: Query<(&mut Style, &mut UiColor), With<StyleMeterFill>>,
+) {
+    let (player, meter, effects) = if let Some(player) = players.iter().next() {
+        player
+    } else {
+        return;
+    };
+
+    for (mut fill_style, mut color) in &mut fills {
+        // While the buff it granted lasts, the meter drains with the time it has left.
+        let (fill, fill_color) = effects.percent_left(STYLE_BUFF).map_or(
+            (meter.points / STYLE_METER_MAX, style.accent_color),
+            |left| (left, style.focused_color),
+        );
+        fill_style.size.width = Val::Percent(fill * 100.0);
+        color.0 = fill_color;
+    }
+
+    if near_misses
+        .iter()
+        .any(|near_miss| near_miss.player == player)
+    {
+        for bar in &bars {
+            commands.entity(bar).insert(
+                Tween::scale(Vec3::splat(POP_SCALE), Vec3::ONE, POP_SECONDS).ease(Ease::BackOut),
+            );
+        }
+    }
+}
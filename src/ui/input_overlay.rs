@@ -0,0 +1,155 @@
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::*;
+
+use crate::{
+    cleanup::DespawnOnExit,
+    devices::PlayerSlot,
+    rules::{MatchMode, Ruleset},
+    Action, GameState,
+};
+
+use super::{text::styled_text, widgets::WidgetStyle};
+
+const OVERLAY_MARGIN: f32 = 16.0;
+const STICK_CELL_SIZE: f32 = 14.0;
+const BUTTON_SIZE: Vec2 = Vec2::new(72.0, 24.0);
+const BUTTONS: [(Action, &str); 9] = [
+    (Action::Jump, "Jump"),
+    (Action::Throw, "Throw"),
+    (Action::Feint, "Feint"),
+    (Action::Catch, "Catch"),
+    (Action::Pickup, "Pick up"),
+    (Action::Curve, "Curve"),
+    (Action::Lob, "Lob"),
+    (Action::Dodge, "Dodge"),
+    (Action::Sprint, "Sprint"),
+];
+
+pub struct InputOverlayPlugin;
+
+impl Plugin for InputOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_enter(GameState::Ready).with_system(spawn_input_overlay))
+            .add_system(update_input_overlay);
+    }
+}
+
+/// One cell of the 3x3 stick display, by its screen direction from the middle.
+#[derive(Component)]
+struct StickCell(IVec2);
+
+#[derive(Component)]
+struct ButtonCell(Action);
+
+/// Shows the local player's inputs as they are read each frame, for learning timing in training.
+fn spawn_input_overlay(mut commands: Commands, ruleset: Res<Ruleset>, style: Res<WidgetStyle>) {
+    if ruleset.mode != MatchMode::Training {
+        return;
+    }
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    left: Val::Px(OVERLAY_MARGIN),
+                    bottom: Val::Px(OVERLAY_MARGIN),
+                    ..default()
+                },
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            color: Color::NONE.into(),
+            ..default()
+        })
+        .insert(DespawnOnExit(GameState::Ready))
+        .with_children(|overlay| {
+            overlay
+                .spawn_bundle(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::ColumnReverse,
+                        margin: UiRect::all(Val::Px(4.0)),
+                        ..default()
+                    },
+                    color: Color::NONE.into(),
+                    ..default()
+                })
+                .with_children(|stick| {
+                    for y in -1..=1 {
+                        stick
+                            .spawn_bundle(NodeBundle {
+                                color: Color::NONE.into(),
+                                ..default()
+                            })
+                            .with_children(|row| {
+                                for x in -1..=1 {
+                                    row.spawn_bundle(NodeBundle {
+                                        style: Style {
+                                            size: Size::new(
+                                                Val::Px(STICK_CELL_SIZE),
+                                                Val::Px(STICK_CELL_SIZE),
+                                            ),
+                                            margin: UiRect::all(Val::Px(1.0)),
+                                            ..default()
+                                        },
+                                        color: style.panel_color.into(),
+                                        ..default()
+                                    })
+                                    .insert(StickCell(IVec2::new(x, y)));
+                                }
+                            });
+                    }
+                });
+            for (action, label) in BUTTONS {
+                overlay
+                    .spawn_bundle(NodeBundle {
+                        style: Style {
+                            size: Size::new(Val::Px(BUTTON_SIZE.x), Val::Px(BUTTON_SIZE.y)),
+                            margin: UiRect::all(Val::Px(4.0)),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        color: style.panel_color.into(),
+                        ..default()
+                    })
+                    .insert(ButtonCell(action))
+                    .with_children(|button| {
+                        button.spawn_bundle(styled_text(label, "hud"));
+                    });
+            }
+        });
+}
+
+fn update_input_overlay(
+    style: Res<WidgetStyle>,
+    players: Query<&ActionState<Action>, With<PlayerSlot>>,
+    mut sticks: Query<(&StickCell, &mut UiColor), Without<ButtonCell>>,
+    mut buttons: Query<(&ButtonCell, &mut UiColor), Without<StickCell>>,
+) {
+    let action_state = if let Some(action_state) = players.iter().next() {
+        action_state
+    } else {
+        return;
+    };
+    // Screen directions, so the stick reads the way it was pushed.
+    let stick = IVec2::new(
+        i32::from(action_state.pressed(Action::MoveRight))
+            - i32::from(action_state.pressed(Action::MoveLeft)),
+        i32::from(action_state.pressed(Action::MoveTowards))
+            - i32::from(action_state.pressed(Action::MoveAway)),
+    );
+    for (StickCell(direction), mut color) in &mut sticks {
+        color.0 = if *direction == stick {
+            style.accent_color
+        } else {
+            style.panel_color
+        };
+    }
+    for (ButtonCell(action), mut color) in &mut buttons {
+        color.0 = if action_state.pressed(*action) {
+            style.accent_color
+        } else {
+            style.panel_color
+        };
+    }
+}
@@ -0,0 +1,137 @@
+use bevy::prelude::*;
+
+use crate::{
+    cleanup::DespawnOnExit,
+    settings::Settings,
+    stage::ActiveStage,
+    team::{Team, TeamColors},
+    Ball, GameState, HeldBy, Player,
+};
+
+use super::widgets::WidgetStyle;
+
+/// Stages shorter than this are small enough to take in at a glance, so they get no minimap.
+const MIN_STAGE_LENGTH: f32 = 20.0;
+const MINIMAP_WIDTH: f32 = 192.0;
+const MINIMAP_MARGIN: f32 = 16.0;
+const DOT_SIZE: f32 = 6.0;
+
+pub struct MinimapPlugin;
+
+impl Plugin for MinimapPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(spawn_minimap)
+            .add_system(update_minimap_dots.after(spawn_minimap));
+    }
+}
+
+#[derive(Component)]
+struct Minimap;
+
+/// A dot on the minimap following the entity it points to.
+#[derive(Component)]
+struct MinimapDot(Entity);
+
+fn spawn_minimap(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    stage: Option<Res<ActiveStage>>,
+    style: Res<WidgetStyle>,
+    minimaps: Query<Entity, With<Minimap>>,
+) {
+    let stage = if let Some(stage) = stage {
+        stage
+    } else {
+        return;
+    };
+    if !settings.is_changed() && !stage.is_changed() && !style.is_changed() {
+        return;
+    }
+    for minimap in &minimaps {
+        commands.entity(minimap).despawn_recursive();
+    }
+    if !settings.minimap || stage.size.y < MIN_STAGE_LENGTH {
+        return;
+    }
+
+    // The court runs left to right on screen, with its far side at the top.
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    right: Val::Px(MINIMAP_MARGIN),
+                    bottom: Val::Px(MINIMAP_MARGIN),
+                    ..default()
+                },
+                size: Size::new(
+                    Val::Px(MINIMAP_WIDTH),
+                    Val::Px(MINIMAP_WIDTH * stage.size.x / stage.size.y),
+                ),
+                ..default()
+            },
+            color: style.panel_color.into(),
+            ..default()
+        })
+        .insert_bundle((Minimap, DespawnOnExit(GameState::Ready)));
+}
+
+fn update_minimap_dots(
+    mut commands: Commands,
+    style: Res<WidgetStyle>,
+    team_colors: Res<TeamColors>,
+    stage: Option<Res<ActiveStage>>,
+    minimaps: Query<Entity, With<Minimap>>,
+    tracked: Query<
+        (Entity, &GlobalTransform, Option<&Team>),
+        (Or<(With<Player>, With<Ball>)>, Without<HeldBy>),
+    >,
+    mut dots: Query<(Entity, &MinimapDot, &mut Style, &mut UiColor)>,
+) {
+    let (minimap, stage) = if let (Some(minimap), Some(stage)) = (minimaps.iter().next(), stage) {
+        (minimap, stage)
+    } else {
+        return;
+    };
+
+    let dot_color =
+        |team: Option<&Team>| team.map_or(style.text_color, |team| team_colors.get(*team));
+
+    for (dot, MinimapDot(target), mut dot_style, mut color) in &mut dots {
+        if let Ok((_, transform, team)) = tracked.get(*target) {
+            let position = transform.translation();
+            dot_style.position.left = Val::Percent((position.y / stage.size.y + 0.5) * 100.0);
+            dot_style.position.top = Val::Percent((position.x / stage.size.x + 0.5) * 100.0);
+            color.0 = dot_color(team);
+        } else {
+            commands.entity(dot).despawn_recursive();
+        }
+    }
+
+    for (entity, _, team) in &tracked {
+        if dots
+            .iter()
+            .any(|(_, MinimapDot(target), _, _)| *target == entity)
+        {
+            continue;
+        }
+        let dot = commands
+            .spawn_bundle(NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    size: Size::new(Val::Px(DOT_SIZE), Val::Px(DOT_SIZE)),
+                    margin: UiRect {
+                        left: Val::Px(-DOT_SIZE / 2.0),
+                        top: Val::Px(-DOT_SIZE / 2.0),
+                        ..default()
+                    },
+                    ..default()
+                },
+                color: dot_color(team).into(),
+                ..default()
+            })
+            .insert(MinimapDot(entity))
+            .id();
+        commands.entity(minimap).add_child(dot);
+    }
+}
@@ -0,0 +1,70 @@
+use bevy::prelude::*;
+
+use crate::{cleanup::DespawnOnExit, GameState, PlayerName};
+
+use super::text::styled_text;
+
+/// How far above the character's center its name floats.
+const TAG_HEIGHT: f32 = 0.45;
+
+pub struct NameTagPlugin;
+
+impl Plugin for NameTagPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(spawn_name_tags)
+            .add_system(position_name_tags.after(spawn_name_tags));
+    }
+}
+
+#[derive(Component)]
+struct NameTag(Entity);
+
+fn spawn_name_tags(
+    mut commands: Commands,
+    names: Query<(Entity, &PlayerName), Changed<PlayerName>>,
+    tags: Query<(Entity, &NameTag)>,
+) {
+    for (player, name) in &names {
+        for (tag, NameTag(target)) in &tags {
+            if *target == player {
+                commands.entity(tag).despawn_recursive();
+            }
+        }
+        commands
+            .spawn_bundle(styled_text(name.0.clone(), "hud"))
+            .insert_bundle((NameTag(player), DespawnOnExit(GameState::Ready)))
+            .insert(Style {
+                position_type: PositionType::Absolute,
+                ..default()
+            });
+    }
+}
+
+fn position_name_tags(
+    mut commands: Commands,
+    cameras: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    players: Query<&GlobalTransform, With<PlayerName>>,
+    mut tags: Query<(Entity, &NameTag, &mut Style, &Node)>,
+) {
+    let (camera, camera_transform) = if let Some(camera) = cameras.iter().next() {
+        camera
+    } else {
+        return;
+    };
+    for (tag, NameTag(player), mut style, node) in &mut tags {
+        let transform = if let Ok(transform) = players.get(*player) {
+            transform
+        } else {
+            commands.entity(tag).despawn_recursive();
+            continue;
+        };
+        if let Some(position) = camera.world_to_viewport(
+            camera_transform,
+            transform.translation() + Vec3::Z * TAG_HEIGHT,
+        ) {
+            // Centered over the character.
+            style.position.left = Val::Px(position.x - node.size().x / 2.0);
+            style.position.bottom = Val::Px(position.y);
+        }
+    }
+}
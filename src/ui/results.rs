@@ -0,0 +1,133 @@
+use bevy::prelude::*;
+
+use crate::{
+    celebration::ShowResults,
+    cleanup::DespawnOnExit,
+    input::{InputContext, InputLayer},
+    stats::{MatchStats, Telemetry},
+    team::{Team, TeamColors},
+    GameState, PlayerName,
+};
+
+use super::{
+    text::styled_text,
+    widgets::{self, Series, WidgetEvent, WidgetStyle},
+};
+
+const GRAPH_SIZE: Vec2 = Vec2::new(480.0, 120.0);
+/// The teams plotted on the results graphs.
+const TEAMS: [Team; 2] = [Team(0), Team(1)];
+
+pub struct ResultsPlugin;
+
+impl Plugin for ResultsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(show_results)
+            .add_system(results_events.after(show_results));
+    }
+}
+
+#[derive(Component)]
+struct ResultsScreen;
+
+#[derive(Component)]
+struct CloseResults;
+
+fn show_results(
+    mut commands: Commands,
+    mut events: EventReader<ShowResults>,
+    style: Res<WidgetStyle>,
+    team_colors: Res<TeamColors>,
+    telemetry: Res<Telemetry>,
+    stats: Res<MatchStats>,
+    players: Query<(Entity, &PlayerName, &Team)>,
+) {
+    if events.iter().last().is_none() {
+        return;
+    }
+
+    let colors = TEAMS.map(|team| team_colors.get(team));
+    let hits = TEAMS
+        .iter()
+        .zip(colors)
+        .map(|(team, color)| Series {
+            color,
+            values: telemetry
+                .samples
+                .iter()
+                .map(|sample| sample.hits.get(team).copied().unwrap_or_default() as f32)
+                .collect(),
+        })
+        .collect::<Vec<_>>();
+    let most_hits = hits
+        .iter()
+        .flat_map(|series| series.values.iter().copied())
+        .fold(1.0, f32::max);
+    let possession = TEAMS
+        .iter()
+        .zip(colors)
+        .map(|(team, color)| Series {
+            color,
+            values: telemetry
+                .samples
+                .iter()
+                .map(|sample| sample.possession.get(team).copied().unwrap_or_default())
+                .collect(),
+        })
+        .collect::<Vec<_>>();
+    let held = possession
+        .iter()
+        .map(|series| series.values.iter().sum::<f32>())
+        .collect::<Vec<_>>();
+    let total_held = held.iter().sum::<f32>().max(f32::EPSILON);
+    let shares = held
+        .iter()
+        .enumerate()
+        .map(|(index, held)| format!("Team {} {:.0}%", index + 1, held / total_held * 100.0))
+        .collect::<Vec<_>>()
+        .join("  ");
+
+    widgets::panel(&mut commands, &style)
+        .insert_bundle((
+            ResultsScreen,
+            InputLayer(InputContext::Menu),
+            DespawnOnExit(GameState::Ready),
+        ))
+        .with_children(|panel| {
+            panel.spawn_bundle(styled_text("Results", "title"));
+            panel.spawn_bundle(styled_text("Hits over time", "hud"));
+            widgets::graph(panel, &style, GRAPH_SIZE, most_hits, &hits);
+            panel.spawn_bundle(styled_text(format!("Possession  {shares}"), "hud"));
+            widgets::graph(panel, &style, GRAPH_SIZE, 1.0, &possession);
+            let mut players = players.iter().collect::<Vec<_>>();
+            players.sort_by_key(|(entity, _, team)| (team.0, *entity));
+            for (entity, name, _) in players {
+                let player_stats = stats.get(entity);
+                panel.spawn_bundle(styled_text(
+                    format!(
+                        "{}  {} hits  {} catches",
+                        name.0, player_stats.hits, player_stats.catches
+                    ),
+                    "hud",
+                ));
+            }
+            widgets::button(panel, &style, "Continue").insert(CloseResults);
+        });
+}
+
+fn results_events(
+    mut commands: Commands,
+    mut events: EventReader<WidgetEvent>,
+    buttons: Query<(), With<CloseResults>>,
+    screens: Query<Entity, With<ResultsScreen>>,
+) {
+    for event in events.iter() {
+        if let WidgetEvent::Pressed(entity) = event {
+            if buttons.contains(*entity) {
+                for screen in &screens {
+                    commands.entity(screen).despawn_recursive();
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use std::path::PathBuf;
+
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::*;
+
+use crate::{
+    cleanup::DespawnOnExit,
+    input::{InputContext, InputLayer},
+    rules::{MatchMode, Ruleset},
+    training::{read_scenario, saved_scenarios, LoadScenario},
+    GameState,
+};
+
+use super::{
+    text::styled_text,
+    widgets::{self, MenuAction, WidgetEvent, WidgetStyle},
+};
+
+pub struct ScenarioBrowserPlugin;
+
+impl Plugin for ScenarioBrowserPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(toggle_scenario_browser)
+            .add_system(scenario_browser_events.after(toggle_scenario_browser));
+    }
+}
+
+#[derive(Component)]
+struct ScenarioBrowser;
+
+#[derive(Component)]
+enum BrowserButton {
+    Scenario(PathBuf),
+    Close,
+}
+
+fn toggle_scenario_browser(
+    mut commands: Commands,
+    ruleset: Res<Ruleset>,
+    action_state: Res<ActionState<MenuAction>>,
+    style: Res<WidgetStyle>,
+    browsers: Query<Entity, With<ScenarioBrowser>>,
+) {
+    let open = !browsers.is_empty();
+    if !((open && action_state.just_pressed(MenuAction::Back))
+        || action_state.just_pressed(MenuAction::Scenarios))
+    {
+        return;
+    }
+    if open {
+        for browser in &browsers {
+            commands.entity(browser).despawn_recursive();
+        }
+    } else if ruleset.mode == MatchMode::Training {
+        spawn_scenario_browser(&mut commands, &style);
+    }
+}
+
+fn spawn_scenario_browser(commands: &mut Commands, style: &WidgetStyle) {
+    let scenarios = saved_scenarios();
+    widgets::panel(commands, style)
+        .insert_bundle((
+            ScenarioBrowser,
+            InputLayer(InputContext::Menu),
+            DespawnOnExit(GameState::Ready),
+        ))
+        .with_children(|parent| {
+            parent.spawn_bundle(styled_text("Scenarios", "title"));
+            if scenarios.is_empty() {
+                parent.spawn_bundle(styled_text("Nothing saved yet", "widget"));
+            }
+            for path in scenarios {
+                let caption = read_scenario(&path)
+                    .map_or_else(|| path.display().to_string(), |scenario| scenario.name);
+                widgets::button(parent, style, &caption).insert(BrowserButton::Scenario(path));
+            }
+            widgets::button(parent, style, "Back").insert(BrowserButton::Close);
+        });
+}
+
+fn scenario_browser_events(
+    mut commands: Commands,
+    mut events: EventReader<WidgetEvent>,
+    mut loads: EventWriter<LoadScenario>,
+    buttons: Query<&BrowserButton>,
+    browsers: Query<Entity, With<ScenarioBrowser>>,
+) {
+    for event in events.iter() {
+        let button = if let WidgetEvent::Pressed(entity) = event {
+            if let Ok(button) = buttons.get(*entity) {
+                button
+            } else {
+                continue;
+            }
+        } else {
+            continue;
+        };
+        if let BrowserButton::Scenario(path) = button {
+            if let Some(scenario) = read_scenario(path) {
+                loads.send(LoadScenario(scenario));
+            }
+        }
+        for browser in &browsers {
+            commands.entity(browser).despawn_recursive();
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::*;
+
+use crate::{
+    cleanup::DespawnOnExit, devices::PlayerSlot, hit::Health, rules::match_active,
+    stats::MatchStats, team::Team, GameState, Player, PlayerName,
+};
+
+use super::{
+    text::styled_text,
+    widgets::{self, MenuAction, WidgetStyle},
+};
+
+const COLUMN_WIDTH: f32 = 96.0;
+const NAME_COLUMN_WIDTH: f32 = 160.0;
+
+pub struct ScoreboardPlugin;
+
+impl Plugin for ScoreboardPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(hide_scoreboard)
+            .add_system(show_scoreboard.with_run_criteria(match_active));
+    }
+}
+
+#[derive(Component)]
+struct Scoreboard;
+
+/// Shows the scoreboard while its button is held, rebuilding it whenever the stats change.
+fn show_scoreboard(
+    mut commands: Commands,
+    action_state: Res<ActionState<MenuAction>>,
+    style: Res<WidgetStyle>,
+    stats: Res<MatchStats>,
+    players: Query<
+        (
+            Entity,
+            &Team,
+            Option<&PlayerSlot>,
+            Option<&PlayerName>,
+            Option<&Health>,
+        ),
+        With<Player>,
+    >,
+    scoreboards: Query<Entity, With<Scoreboard>>,
+) {
+    if !action_state.pressed(MenuAction::Scoreboard)
+        || !(action_state.just_pressed(MenuAction::Scoreboard) || stats.is_changed())
+    {
+        return;
+    }
+    for scoreboard in &scoreboards {
+        commands.entity(scoreboard).despawn_recursive();
+    }
+
+    let mut rows: Vec<_> = players.iter().collect();
+    rows.sort_by_key(|(entity, team, slot, ..)| (team.0, slot.map(|slot| slot.0), *entity));
+
+    widgets::panel(&mut commands, &style)
+        .insert_bundle((Scoreboard, DespawnOnExit(GameState::Ready)))
+        .with_children(|panel| {
+            spawn_row(panel, ["Player", "Team", "Lives", "Hits", "Catches"]);
+            for (entity, team, _, name, health) in rows {
+                let name = name.map_or("CPU".to_string(), |name| name.0.clone());
+                let lives = health.map_or("-".to_string(), |health| {
+                    format!("{}/{}", health.current, health.max)
+                });
+                let player_stats = stats.get(entity);
+                spawn_row(
+                    panel,
+                    [
+                        name,
+                        (team.0 + 1).to_string(),
+                        lives,
+                        player_stats.hits.to_string(),
+                        player_stats.catches.to_string(),
+                    ],
+                );
+            }
+        });
+}
+
+fn spawn_row(parent: &mut ChildBuilder, cells: [impl Into<String>; 5]) {
+    parent
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                margin: UiRect::all(Val::Px(4.0)),
+                ..default()
+            },
+            color: Color::NONE.into(),
+            ..default()
+        })
+        .with_children(|row| {
+            for (column, cell) in cells.into_iter().enumerate() {
+                let width = if column == 0 {
+                    NAME_COLUMN_WIDTH
+                } else {
+                    COLUMN_WIDTH
+                };
+                row.spawn_bundle(NodeBundle {
+                    style: Style {
+                        size: Size::new(Val::Px(width), Val::Auto),
+                        ..default()
+                    },
+                    color: Color::NONE.into(),
+                    ..default()
+                })
+                .with_children(|cell_node| {
+                    cell_node.spawn_bundle(styled_text(cell, "hud"));
+                });
+            }
+        });
+}
+
+fn hide_scoreboard(
+    mut commands: Commands,
+    action_state: Res<ActionState<MenuAction>>,
+    scoreboards: Query<Entity, With<Scoreboard>>,
+) {
+    if action_state.pressed(MenuAction::Scoreboard) {
+        return;
+    }
+    for scoreboard in &scoreboards {
+        commands.entity(scoreboard).despawn_recursive();
+    }
+}
@@ -0,0 +1,374 @@
+use bevy::{ecs::system::EntityCommands, prelude::*};
+use leafwing_input_manager::prelude::*;
+
+use crate::{
+    input::{InputContext, InputLayer},
+    keyboard_layout::KeyboardLayout,
+    profile::Profile,
+    rules::Ruleset,
+    settings::{Language, Settings, StickResponse, VideoMode},
+    team::{Team, TeamColors, TEAM_PALETTE},
+    tween::{Ease, Tween},
+};
+
+use super::widgets::{self, Carousel, MenuAction, Slider, Toggle, WidgetEvent, WidgetStyle};
+
+/// Teams whose colors can be picked in the menu.
+const TEAM_COUNT: usize = 2;
+/// Local players whose sticks can be tuned in the menu.
+const STICK_PLAYERS: usize = 2;
+/// Stick deadzones on offer.
+const DEADZONES: [f32; 7] = [0.0, 0.05, 0.1, 0.15, 0.2, 0.25, 0.3];
+/// How far the volume slider moves with each press, in percent.
+const VOLUME_STEP: f32 = 10.0;
+/// The most balls a match can be set to play with.
+const MAX_BALL_COUNT: usize = 7;
+/// The sections of the menu, one tab each.
+const TABS: [&str; 4] = ["General", "Teams", "Controls", "Match"];
+const RESPONSES: [(&str, StickResponse); 3] = [
+    ("Linear", StickResponse::Linear),
+    ("Precise", StickResponse::Precise),
+    ("Aggressive", StickResponse::Aggressive),
+];
+
+const SLIDE_SECONDS: f32 = 0.3;
+
+pub struct SettingsMenuPlugin;
+
+impl Plugin for SettingsMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(toggle_settings_menu)
+            .add_system(settings_menu_events.after(toggle_settings_menu))
+            .add_system(sync_team_colors.after(settings_menu_events));
+    }
+}
+
+#[derive(Component)]
+struct SettingsMenu;
+
+#[derive(Component, Clone, Copy)]
+enum SettingsField {
+    Language,
+    VideoMode,
+    Volume,
+    VSync,
+    DebugPhysics,
+    PixelText,
+    PixelPerfect,
+    SpriteAlphaCutoff,
+    Minimap,
+    TeamColor(Team),
+    KeyboardLayout,
+    /// Settings for the stick of the player in this slot.
+    Deadzone(usize),
+    Response(usize),
+    InvertX(usize),
+    InvertY(usize),
+    FriendlyFire,
+    BallCount,
+    Close,
+}
+
+fn toggle_settings_menu(
+    mut commands: Commands,
+    action_state: Res<ActionState<MenuAction>>,
+    style: Res<WidgetStyle>,
+    settings: Res<Settings>,
+    team_colors: Res<TeamColors>,
+    profile: Res<Profile>,
+    ruleset: Res<Ruleset>,
+    menus: Query<Entity, With<SettingsMenu>>,
+) {
+    let open = !menus.is_empty();
+    if (open && action_state.just_pressed(MenuAction::Back))
+        || action_state.just_pressed(MenuAction::Settings)
+    {
+        if open {
+            for menu in &menus {
+                commands.entity(menu).despawn_recursive();
+            }
+            settings.save();
+        } else {
+            spawn_settings_menu(
+                &mut commands,
+                &style,
+                &settings,
+                &team_colors,
+                &profile,
+                &ruleset,
+            );
+        }
+    }
+}
+
+fn spawn_settings_menu(
+    commands: &mut Commands,
+    style: &WidgetStyle,
+    settings: &Settings,
+    team_colors: &TeamColors,
+    profile: &Profile,
+    ruleset: &Ruleset,
+) {
+    widgets::panel(commands, style)
+        .insert_bundle((
+            SettingsMenu,
+            InputLayer(InputContext::Menu),
+            Tween::slide_top(-100.0, 0.0, SLIDE_SECONDS).ease(Ease::CubicOut),
+        ))
+        .with_children(|parent| {
+            let bar = widgets::tab_bar(parent, style, &TABS).id();
+            widgets::tab_pane(parent, bar, 0).with_children(|pane| {
+                widgets::carousel(
+                    pane,
+                    style,
+                    "Language",
+                    Language::ALL
+                        .iter()
+                        .map(|language| language.name().to_string())
+                        .collect(),
+                    Language::ALL
+                        .iter()
+                        .position(|language| *language == settings.language)
+                        .unwrap_or_default(),
+                )
+                .insert(SettingsField::Language);
+                widgets::carousel(
+                    pane,
+                    style,
+                    "Video mode",
+                    VideoMode::ALL
+                        .iter()
+                        .map(|mode| mode.name().to_string())
+                        .collect(),
+                    VideoMode::ALL
+                        .iter()
+                        .position(|mode| *mode == settings.video_mode)
+                        .unwrap_or_default(),
+                )
+                .insert(SettingsField::VideoMode);
+                volume_slider(pane, style, settings.volume).insert(SettingsField::Volume);
+                widgets::toggle(pane, style, "VSync", settings.vsync).insert(SettingsField::VSync);
+                widgets::toggle(pane, style, "Physics debug", settings.debug_physics)
+                    .insert(SettingsField::DebugPhysics);
+                widgets::toggle(pane, style, "Pixel text", settings.pixel_text)
+                    .insert(SettingsField::PixelText);
+                widgets::toggle(pane, style, "Pixel perfect", settings.pixel_perfect)
+                    .insert(SettingsField::PixelPerfect);
+                widgets::toggle(pane, style, "Sharp sprites", settings.sprite_alpha_cutoff)
+                    .insert(SettingsField::SpriteAlphaCutoff);
+                widgets::toggle(pane, style, "Minimap", settings.minimap)
+                    .insert(SettingsField::Minimap);
+            });
+            widgets::tab_pane(parent, bar, 1).with_children(|pane| {
+                for team in (0..TEAM_COUNT).map(Team) {
+                    widgets::carousel(
+                        pane,
+                        style,
+                        &format!("Team {} color", team.0 + 1),
+                        TEAM_PALETTE
+                            .iter()
+                            .map(|(name, _)| (*name).to_string())
+                            .collect(),
+                        palette_index(team_colors.get(team)),
+                    )
+                    .insert(SettingsField::TeamColor(team));
+                }
+            });
+            widgets::tab_pane(parent, bar, 2).with_children(|pane| {
+                let layout = profile.keyboard_layout.unwrap_or_default();
+                widgets::carousel(
+                    pane,
+                    style,
+                    "Keyboard",
+                    KeyboardLayout::ALL
+                        .iter()
+                        .map(|layout| layout.name().to_string())
+                        .collect(),
+                    KeyboardLayout::ALL
+                        .iter()
+                        .position(|candidate| *candidate == layout)
+                        .unwrap_or_default(),
+                )
+                .insert(SettingsField::KeyboardLayout);
+                for slot in 0..STICK_PLAYERS {
+                    let stick = settings.stick(slot);
+                    let player = slot + 1;
+                    widgets::carousel(
+                        pane,
+                        style,
+                        &format!("P{player} deadzone"),
+                        DEADZONES
+                            .iter()
+                            .map(|deadzone| format!("{:.0}%", deadzone * 100.0))
+                            .collect(),
+                        DEADZONES
+                            .iter()
+                            .position(|deadzone| *deadzone >= stick.deadzone)
+                            .unwrap_or_default(),
+                    )
+                    .insert(SettingsField::Deadzone(slot));
+                    widgets::carousel(
+                        pane,
+                        style,
+                        &format!("P{player} stick response"),
+                        RESPONSES
+                            .iter()
+                            .map(|(name, _)| (*name).to_string())
+                            .collect(),
+                        RESPONSES
+                            .iter()
+                            .position(|(_, response)| *response == stick.response)
+                            .unwrap_or_default(),
+                    )
+                    .insert(SettingsField::Response(slot));
+                    widgets::toggle(pane, style, &format!("P{player} invert X"), stick.invert_x)
+                        .insert(SettingsField::InvertX(slot));
+                    widgets::toggle(pane, style, &format!("P{player} invert Y"), stick.invert_y)
+                        .insert(SettingsField::InvertY(slot));
+                }
+            });
+            // Match rules take effect right away, and last until the game is closed. Raising the
+            // ball count drops more balls in, and lowering it stops lost ones being replaced.
+            widgets::tab_pane(parent, bar, 3).with_children(|pane| {
+                widgets::toggle(pane, style, "Friendly fire", ruleset.friendly_fire)
+                    .insert(SettingsField::FriendlyFire);
+                widgets::slider(
+                    pane,
+                    style,
+                    "Ball count",
+                    Slider {
+                        value: ruleset.ball_count as f32,
+                        min: 1.0,
+                        max: MAX_BALL_COUNT as f32,
+                        step: 1.0,
+                    },
+                )
+                .insert(SettingsField::BallCount);
+            });
+            widgets::button(parent, style, "Back").insert(SettingsField::Close);
+        });
+}
+
+fn settings_menu_events(
+    mut commands: Commands,
+    mut events: EventReader<WidgetEvent>,
+    mut settings: ResMut<Settings>,
+    mut team_colors: ResMut<TeamColors>,
+    mut profile: ResMut<Profile>,
+    mut ruleset: ResMut<Ruleset>,
+    fields: Query<(
+        &SettingsField,
+        Option<&Toggle>,
+        Option<&Carousel>,
+        Option<&Slider>,
+    )>,
+    menus: Query<Entity, With<SettingsMenu>>,
+) {
+    for event in events.iter() {
+        match event {
+            WidgetEvent::Changed(entity) => match fields.get(*entity) {
+                Ok((SettingsField::Language, _, Some(carousel), _)) => {
+                    settings.language = Language::ALL[carousel.selected];
+                }
+                Ok((SettingsField::VideoMode, _, Some(carousel), _)) => {
+                    settings.video_mode = VideoMode::ALL[carousel.selected];
+                }
+                Ok((SettingsField::Volume, _, _, Some(slider))) => {
+                    settings.volume = slider.value / 100.0;
+                }
+                Ok((SettingsField::VSync, Some(toggle), _, _)) => settings.vsync = toggle.0,
+                Ok((SettingsField::DebugPhysics, Some(toggle), _, _)) => {
+                    settings.debug_physics = toggle.0;
+                }
+                Ok((SettingsField::PixelText, Some(toggle), _, _)) => {
+                    settings.pixel_text = toggle.0;
+                }
+                Ok((SettingsField::PixelPerfect, Some(toggle), _, _)) => {
+                    settings.pixel_perfect = toggle.0;
+                }
+                Ok((SettingsField::SpriteAlphaCutoff, Some(toggle), _, _)) => {
+                    settings.sprite_alpha_cutoff = toggle.0;
+                }
+                Ok((SettingsField::Minimap, Some(toggle), _, _)) => settings.minimap = toggle.0,
+                Ok((SettingsField::TeamColor(team), _, Some(carousel), _)) => {
+                    team_colors.set(*team, TEAM_PALETTE[carousel.selected].1);
+                }
+                Ok((SettingsField::KeyboardLayout, _, Some(carousel), _)) => {
+                    profile.keyboard_layout = Some(KeyboardLayout::ALL[carousel.selected]);
+                    profile.save();
+                }
+                Ok((SettingsField::Deadzone(slot), _, Some(carousel), _)) => {
+                    settings.stick_mut(*slot).deadzone = DEADZONES[carousel.selected];
+                }
+                Ok((SettingsField::Response(slot), _, Some(carousel), _)) => {
+                    settings.stick_mut(*slot).response = RESPONSES[carousel.selected].1;
+                }
+                Ok((SettingsField::InvertX(slot), Some(toggle), _, _)) => {
+                    settings.stick_mut(*slot).invert_x = toggle.0;
+                }
+                Ok((SettingsField::InvertY(slot), Some(toggle), _, _)) => {
+                    settings.stick_mut(*slot).invert_y = toggle.0;
+                }
+                Ok((SettingsField::FriendlyFire, Some(toggle), _, _)) => {
+                    ruleset.friendly_fire = toggle.0;
+                }
+                Ok((SettingsField::BallCount, _, _, Some(slider))) => {
+                    ruleset.ball_count = slider.value as usize;
+                }
+                _ => {}
+            },
+            WidgetEvent::Pressed(entity) => {
+                if let Ok((SettingsField::Close, ..)) = fields.get(*entity) {
+                    for menu in &menus {
+                        commands.entity(menu).despawn_recursive();
+                    }
+                    settings.save();
+                }
+            }
+        }
+    }
+}
+
+/// Shows colors moved by clash resolution on the other teams' pickers.
+fn sync_team_colors(
+    team_colors: Res<TeamColors>,
+    mut fields: Query<(&SettingsField, &mut Carousel)>,
+) {
+    if !team_colors.is_changed() {
+        return;
+    }
+    for (field, mut carousel) in &mut fields {
+        if let SettingsField::TeamColor(team) = field {
+            let selected = palette_index(team_colors.get(*team));
+            if carousel.selected != selected {
+                carousel.selected = selected;
+            }
+        }
+    }
+}
+
+/// A slider setting the volume in percent, from mute to full.
+pub fn volume_slider<'w, 's, 'a>(
+    parent: &'a mut ChildBuilder<'w, 's, '_>,
+    style: &WidgetStyle,
+    volume: f32,
+) -> EntityCommands<'w, 's, 'a> {
+    widgets::slider(
+        parent,
+        style,
+        "Volume",
+        Slider {
+            value: (volume * 100.0 / VOLUME_STEP).round() * VOLUME_STEP,
+            min: 0.0,
+            max: 100.0,
+            step: VOLUME_STEP,
+        },
+    )
+}
+
+fn palette_index(color: Color) -> usize {
+    TEAM_PALETTE
+        .iter()
+        .position(|(_, candidate)| *candidate == color)
+        .unwrap_or_default()
+}
@@ -0,0 +1,235 @@
+use bevy::prelude::*;
+
+use crate::{
+    game_time::{HoldPhysics, PhysicsHold},
+    input::{InputContext, InputLayer},
+    settings::{ControlPreset, FirstRun, Language, Settings, VideoMode},
+    GameState,
+};
+
+use super::{
+    settings_menu::volume_slider,
+    text::styled_text,
+    widgets::{self, Carousel, Slider, WidgetEvent, WidgetStyle},
+};
+
+pub struct SetupWizardPlugin;
+
+impl Plugin for SetupWizardPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_enter(GameState::Ready).with_system(start_setup))
+            .add_system(setup_events)
+            .add_system(detect_controls);
+    }
+}
+
+/// One page of the setup shown on first launch, in the order they're shown.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum SetupStep {
+    Language,
+    Controls,
+    Video,
+    Volume,
+}
+
+impl SetupStep {
+    fn next(self) -> Option<Self> {
+        match self {
+            Self::Language => Some(Self::Controls),
+            Self::Controls => Some(Self::Video),
+            Self::Video => Some(Self::Volume),
+            Self::Volume => None,
+        }
+    }
+}
+
+#[derive(Component)]
+struct SetupWizard(SetupStep);
+
+#[derive(Component, Clone, Copy)]
+enum SetupField {
+    Language,
+    VideoMode,
+    Volume,
+    Next,
+}
+
+/// Holds the first match still while the player picks their settings, which are only written
+/// once they're done.
+fn start_setup(
+    mut commands: Commands,
+    first_run: Res<FirstRun>,
+    style: Res<WidgetStyle>,
+    settings: Res<Settings>,
+    mut holds: EventWriter<HoldPhysics>,
+) {
+    if !first_run.0 {
+        return;
+    }
+    holds.send(HoldPhysics {
+        hold: PhysicsHold::Setup,
+        held: true,
+    });
+    spawn_step(&mut commands, &style, &settings, SetupStep::Language);
+}
+
+fn spawn_step(commands: &mut Commands, style: &WidgetStyle, settings: &Settings, step: SetupStep) {
+    widgets::panel(commands, style)
+        .insert_bundle((SetupWizard(step), InputLayer(InputContext::Menu)))
+        .with_children(|parent| match step {
+            SetupStep::Language => {
+                parent.spawn_bundle(styled_text("Language", "title"));
+                widgets::carousel(
+                    parent,
+                    style,
+                    "Language",
+                    Language::ALL
+                        .iter()
+                        .map(|language| language.name().to_string())
+                        .collect(),
+                    Language::ALL
+                        .iter()
+                        .position(|language| *language == settings.language)
+                        .unwrap_or_default(),
+                )
+                .insert(SetupField::Language);
+                widgets::button(parent, style, "Next").insert(SetupField::Next);
+            }
+            SetupStep::Controls => {
+                parent.spawn_bundle(styled_text("Controls", "title"));
+                parent.spawn_bundle(styled_text(
+                    "Press a button on the keyboard or controller you'll play with",
+                    "hud",
+                ));
+            }
+            SetupStep::Video => {
+                parent.spawn_bundle(styled_text("Video", "title"));
+                widgets::carousel(
+                    parent,
+                    style,
+                    "Video mode",
+                    VideoMode::ALL
+                        .iter()
+                        .map(|mode| mode.name().to_string())
+                        .collect(),
+                    VideoMode::ALL
+                        .iter()
+                        .position(|mode| *mode == settings.video_mode)
+                        .unwrap_or_default(),
+                )
+                .insert(SetupField::VideoMode);
+                widgets::button(parent, style, "Next").insert(SetupField::Next);
+            }
+            SetupStep::Volume => {
+                parent.spawn_bundle(styled_text("Volume", "title"));
+                volume_slider(parent, style, settings.volume).insert(SetupField::Volume);
+                widgets::button(parent, style, "Done").insert(SetupField::Next);
+            }
+        });
+}
+
+/// Moves on from a step, writing the settings file and letting the match start after the last.
+fn advance(
+    commands: &mut Commands,
+    style: &WidgetStyle,
+    settings: &Settings,
+    first_run: &mut FirstRun,
+    holds: &mut EventWriter<HoldPhysics>,
+    wizard: Entity,
+    step: SetupStep,
+) {
+    commands.entity(wizard).despawn_recursive();
+    if let Some(next) = step.next() {
+        spawn_step(commands, style, settings, next);
+        return;
+    }
+    info!("Setup finished");
+    settings.save();
+    first_run.0 = false;
+    holds.send(HoldPhysics {
+        hold: PhysicsHold::Setup,
+        held: false,
+    });
+}
+
+fn setup_events(
+    mut commands: Commands,
+    mut events: EventReader<WidgetEvent>,
+    style: Res<WidgetStyle>,
+    mut settings: ResMut<Settings>,
+    mut first_run: ResMut<FirstRun>,
+    mut holds: EventWriter<HoldPhysics>,
+    fields: Query<(&SetupField, Option<&Carousel>, Option<&Slider>)>,
+    wizards: Query<(Entity, &SetupWizard)>,
+) {
+    for event in events.iter() {
+        match event {
+            WidgetEvent::Changed(entity) => match fields.get(*entity) {
+                Ok((SetupField::Language, Some(carousel), _)) => {
+                    settings.language = Language::ALL[carousel.selected];
+                }
+                // Applied right away, so the player can see and hear what they're picking.
+                Ok((SetupField::VideoMode, Some(carousel), _)) => {
+                    settings.video_mode = VideoMode::ALL[carousel.selected];
+                }
+                Ok((SetupField::Volume, _, Some(slider))) => {
+                    settings.volume = slider.value / 100.0;
+                }
+                _ => {}
+            },
+            WidgetEvent::Pressed(entity) => {
+                if !matches!(fields.get(*entity), Ok((SetupField::Next, ..))) {
+                    continue;
+                }
+                for (wizard, SetupWizard(step)) in &wizards {
+                    advance(
+                        &mut commands,
+                        &style,
+                        &settings,
+                        &mut first_run,
+                        &mut holds,
+                        wizard,
+                        *step,
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Picks the control preset from whichever device the player presses something on. The press
+/// that opened the step doesn't count.
+fn detect_controls(
+    mut commands: Commands,
+    keys: Res<Input<KeyCode>>,
+    buttons: Res<Input<GamepadButton>>,
+    style: Res<WidgetStyle>,
+    mut settings: ResMut<Settings>,
+    mut first_run: ResMut<FirstRun>,
+    mut holds: EventWriter<HoldPhysics>,
+    wizards: Query<(Entity, &SetupWizard, ChangeTrackers<SetupWizard>)>,
+) {
+    for (wizard, SetupWizard(step), tracker) in &wizards {
+        if *step != SetupStep::Controls || tracker.is_added() {
+            continue;
+        }
+        let controls = if buttons.get_just_pressed().next().is_some() {
+            ControlPreset::Gamepad
+        } else if keys.get_just_pressed().next().is_some() {
+            ControlPreset::Keyboard
+        } else {
+            continue;
+        };
+        info!("Playing with {controls:?} controls");
+        settings.controls = controls;
+        advance(
+            &mut commands,
+            &style,
+            &settings,
+            &mut first_run,
+            &mut holds,
+            wizard,
+            *step,
+        );
+    }
+}
@@ -0,0 +1,97 @@
+use bevy::prelude::*;
+
+use crate::{
+    cleanup::DespawnOnExit, profile::Profile, target_practice::TargetPracticeRun, GameState,
+};
+
+use super::text::styled_text;
+
+const HUD_MARGIN: f32 = 16.0;
+
+pub struct TargetPracticeHudPlugin;
+
+impl Plugin for TargetPracticeHudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(spawn_target_practice_hud)
+            .add_system(update_target_practice_hud.after(spawn_target_practice_hud));
+    }
+}
+
+#[derive(Component)]
+struct TargetPracticeStatus;
+
+#[derive(Component)]
+struct HighScores;
+
+fn spawn_target_practice_hud(mut commands: Commands, run: Option<Res<TargetPracticeRun>>) {
+    if !run.map_or(false, |run| run.is_added()) {
+        return;
+    }
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    top: Val::Px(HUD_MARGIN),
+                    ..default()
+                },
+                size: Size::new(Val::Percent(100.0), Val::Auto),
+                flex_direction: FlexDirection::ColumnReverse,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            color: Color::NONE.into(),
+            ..default()
+        })
+        .insert(DespawnOnExit(GameState::Ready))
+        .with_children(|hud| {
+            hud.spawn_bundle(styled_text("Target Practice", "hud"));
+            hud.spawn_bundle(styled_text("", "title"))
+                .insert(TargetPracticeStatus);
+            hud.spawn_bundle(styled_text("", "hud")).insert(HighScores);
+        });
+}
+
+fn update_target_practice_hud(
+    run: Option<Res<TargetPracticeRun>>,
+    profile: Res<Profile>,
+    mut statuses: Query<&mut Text, (With<TargetPracticeStatus>, Without<HighScores>)>,
+    mut high_scores: Query<&mut Text, (With<HighScores>, Without<TargetPracticeStatus>)>,
+) {
+    let run = if let Some(run) = run {
+        run
+    } else {
+        return;
+    };
+    let (status, scores) = if run.finished {
+        let status = match run.rank {
+            Some(0) => format!("New best! {}", run.score),
+            Some(_) => format!("High score! {}", run.score),
+            None => format!("Time! {}", run.score),
+        };
+        let scores = profile
+            .target_scores
+            .iter()
+            .enumerate()
+            .map(|(index, score)| format!("{}. {score}", index + 1))
+            .collect::<Vec<_>>()
+            .join("\n");
+        (status, scores)
+    } else {
+        let best = profile.target_scores.first().copied().unwrap_or(0);
+        (
+            format!("{}  {:.1}", run.score, run.seconds_left()),
+            format!("Best {best}"),
+        )
+    };
+    for mut text in &mut statuses {
+        if text.sections[0].value != status {
+            text.sections[0].value = status.clone();
+        }
+    }
+    for mut text in &mut high_scores {
+        if text.sections[0].value != scores {
+            text.sections[0].value = scores.clone();
+        }
+    }
+}
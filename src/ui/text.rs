@@ -0,0 +1,99 @@
+use bevy::{prelude::*, transform::TransformSystem, utils::HashMap};
+use bevy_asset_loader::prelude::*;
+
+use crate::settings::Settings;
+
+use super::theme::UiTheme;
+
+pub struct TextStylePlugin;
+
+impl Plugin for TextStylePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TextStyles>()
+            .add_system(apply_text_styles)
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                snap_text.after(TransformSystem::TransformPropagate),
+            );
+    }
+}
+
+/// Fonts used by the UI theme, and the theme itself, loaded up front so the match doesn't start
+/// with text in no font at all.
+#[derive(AssetCollection)]
+pub struct FontAssets {
+    // Only held to keep these loaded; the theme refers to the font by path, and the theme is
+    // applied from its own handle.
+    #[allow(dead_code)]
+    #[asset(path = "fonts/pixel.ttf")]
+    pixel: Handle<Font>,
+    #[allow(dead_code)]
+    #[asset(path = "ui/default.theme.ron")]
+    theme: Handle<UiTheme>,
+}
+
+/// Named text styles shared by the HUD and menus, filled in from the UI theme.
+#[derive(Default)]
+pub struct TextStyles {
+    pub styles: HashMap<String, TextStyle>,
+    /// The size the font was designed for; snapped text is scaled by whole multiples of it.
+    pub pixel_size: f32,
+}
+
+impl TextStyles {
+    pub fn get(&self, name: &str, pixel_snap: bool) -> TextStyle {
+        let mut style = self.styles.get(name).cloned().unwrap_or_default();
+        if pixel_snap && self.pixel_size > 0.0 {
+            style.font_size =
+                (style.font_size / self.pixel_size).round().max(1.0) * self.pixel_size;
+        }
+        style
+    }
+}
+
+/// Styles the sections of an entity's [`Text`] from the [`TextStyles`] entry with this name.
+#[derive(Component)]
+pub struct StyledText(pub &'static str);
+
+pub fn styled_text(value: impl Into<String>, name: &'static str) -> (TextBundle, StyledText) {
+    (
+        TextBundle::from_section(value, TextStyle::default()),
+        StyledText(name),
+    )
+}
+
+fn apply_text_styles(
+    styles: Res<TextStyles>,
+    settings: Res<Settings>,
+    mut texts: Query<(&StyledText, ChangeTrackers<StyledText>, &mut Text)>,
+) {
+    let restyle_all = styles.is_changed() || settings.is_changed();
+    for (styled, tracker, mut text) in &mut texts {
+        if !restyle_all && !tracker.is_added() {
+            continue;
+        }
+        let style = styles.get(styled.0, settings.pixel_text);
+        for section in &mut text.sections {
+            section.style = style.clone();
+        }
+    }
+}
+
+fn snap_text(
+    settings: Res<Settings>,
+    windows: Res<Windows>,
+    mut texts: Query<(&Node, &mut GlobalTransform), With<StyledText>>,
+) {
+    if !settings.pixel_text {
+        return;
+    }
+    let scale = windows.get_primary().map_or(1.0, Window::scale_factor) as f32;
+    for (node, mut global_transform) in &mut texts {
+        let mut transform = global_transform.compute_transform();
+        let half_size = node.size / 2.0;
+        let corner = (transform.translation.truncate() - half_size) * scale;
+        transform.translation =
+            (corner.round() / scale + half_size).extend(transform.translation.z);
+        *global_transform = GlobalTransform::from(transform);
+    }
+}
@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+
+use bevy::{
+    prelude::*,
+    reflect::TypeUuid,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+use serde::Deserialize;
+
+use crate::ron_loader::{RonAsset, RonLoader};
+
+use super::{
+    text::TextStyles,
+    widgets::{PanelImage, WidgetStyle},
+};
+
+pub struct ThemePlugin;
+
+impl Plugin for ThemePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<UiTheme>()
+            .init_asset_loader::<RonLoader<UiTheme>>()
+            .init_resource::<ThemeHandle>()
+            .add_system(apply_theme)
+            .add_system(slice_panel_image.after(apply_theme));
+    }
+}
+
+#[derive(Deserialize, TypeUuid)]
+#[uuid = "5b8a3c4e-7d0f-4a51-9c5e-2f6a1d7b9e30"]
+pub struct UiTheme {
+    pub pixel_size: f32,
+    pub text_styles: HashMap<String, TextStyleTheme>,
+    pub text_color: Color,
+    pub accent_color: Color,
+    pub panel_color: Color,
+    pub normal_color: Color,
+    pub focused_color: Color,
+    pub pressed_color: Color,
+    pub widget_width: f32,
+    pub widget_height: f32,
+    #[serde(default)]
+    pub panel: Option<PanelTheme>,
+}
+
+#[derive(Deserialize)]
+pub struct TextStyleTheme {
+    pub font: String,
+    pub size: f32,
+    pub color: Color,
+}
+
+#[derive(Deserialize)]
+pub struct PanelTheme {
+    pub image: String,
+    pub border: u32,
+}
+
+impl RonAsset for UiTheme {
+    const EXTENSIONS: &'static [&'static str] = &["theme.ron"];
+}
+
+pub struct ThemeHandle(pub Handle<UiTheme>);
+
+impl FromWorld for ThemeHandle {
+    fn from_world(world: &mut World) -> Self {
+        Self(world.resource::<AssetServer>().load("ui/default.theme.ron"))
+    }
+}
+
+fn apply_theme(
+    mut events: EventReader<AssetEvent<UiTheme>>,
+    theme_handle: Res<ThemeHandle>,
+    themes: Res<Assets<UiTheme>>,
+    asset_server: Res<AssetServer>,
+    mut style: ResMut<WidgetStyle>,
+    mut text_styles: ResMut<TextStyles>,
+) {
+    for event in events.iter() {
+        let changed = match event {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => handle,
+            AssetEvent::Removed { .. } => continue,
+        };
+        if *changed != theme_handle.0 {
+            continue;
+        }
+        let theme = if let Some(theme) = themes.get(&theme_handle.0) {
+            theme
+        } else {
+            continue;
+        };
+
+        info!("Applying UI theme");
+        text_styles.pixel_size = theme.pixel_size;
+        text_styles.styles = theme
+            .text_styles
+            .iter()
+            .map(|(name, text_style)| {
+                (
+                    name.clone(),
+                    TextStyle {
+                        font: asset_server.load(&text_style.font),
+                        font_size: text_style.size,
+                        color: text_style.color,
+                    },
+                )
+            })
+            .collect();
+        style.text_color = theme.text_color;
+        style.accent_color = theme.accent_color;
+        style.panel_color = theme.panel_color;
+        style.normal_color = theme.normal_color;
+        style.focused_color = theme.focused_color;
+        style.pressed_color = theme.pressed_color;
+        style.width = theme.widget_width;
+        style.height = theme.widget_height;
+        style.panel = theme.panel.as_ref().map(|panel| PanelImage {
+            image: asset_server.load(&panel.image),
+            border: panel.border,
+            slices: None,
+        });
+    }
+}
+
+fn slice_panel_image(
+    mut events: EventReader<AssetEvent<Image>>,
+    mut style: ResMut<WidgetStyle>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let reloaded = events.iter().any(|event| match (event, &style.panel) {
+        (AssetEvent::Modified { handle }, Some(panel)) => *handle == panel.image,
+        _ => false,
+    });
+    let (image, border) = match &style.panel {
+        Some(panel) if panel.slices.is_none() || reloaded => (panel.image.clone(), panel.border),
+        _ => return,
+    };
+    let slices = match images.get(&image).map(|image| slice_image(image, border)) {
+        Some(Some(slices)) => slices.map(|slice| images.add(slice)),
+        Some(None) => {
+            warn!("Panel image must be RGBA8 and larger than twice its border");
+            style.panel = None;
+            return;
+        }
+        None => return,
+    };
+    if let Some(panel) = &mut style.panel {
+        panel.slices = Some(slices);
+    }
+}
+
+/// Cuts `image` into the nine pieces of a 9-slice panel, in row-major order from the top left.
+fn slice_image(image: &Image, border: u32) -> Option<[Image; 9]> {
+    let format = image.texture_descriptor.format;
+    let Extent3d { width, height, .. } = image.texture_descriptor.size;
+    if format != TextureFormat::Rgba8UnormSrgb || border * 2 >= width.min(height) {
+        return None;
+    }
+
+    let columns = [
+        (0, border),
+        (border, width - border * 2),
+        (width - border, border),
+    ];
+    let rows = [
+        (0, border),
+        (border, height - border * 2),
+        (height - border, border),
+    ];
+    let mut slices = Vec::with_capacity(9);
+    for (y, slice_height) in rows {
+        for (x, slice_width) in columns {
+            let mut data = Vec::with_capacity((slice_width * slice_height * 4) as usize);
+            for row in y..y + slice_height {
+                let start = ((row * width + x) * 4) as usize;
+                data.extend_from_slice(&image.data[start..start + (slice_width * 4) as usize]);
+            }
+            slices.push(Image::new(
+                Extent3d {
+                    width: slice_width,
+                    height: slice_height,
+                    depth_or_array_layers: 1,
+                },
+                TextureDimension::D2,
+                data,
+                format,
+            ));
+        }
+    }
+    slices.try_into().ok()
+}
@@ -0,0 +1,712 @@
+use bevy::{ecs::system::EntityCommands, prelude::*};
+use leafwing_input_manager::prelude::*;
+
+use super::text::{styled_text, StyledText};
+
+const GRAPH_POINT_SIZE: f32 = 4.0;
+
+pub struct WidgetPlugin;
+
+impl Plugin for WidgetPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WidgetStyle>()
+            .init_resource::<Focus>()
+            .add_event::<WidgetEvent>()
+            .add_plugin(InputManagerPlugin::<MenuAction>::default())
+            .init_resource::<ActionState<MenuAction>>()
+            .insert_resource(menu_input_map())
+            .add_system(mouse_focus)
+            .add_system(navigate_focus.after(mouse_focus))
+            .add_system(activate_focused.after(navigate_focus))
+            .add_system(update_widget_text.after(activate_focused))
+            .add_system(update_slider_fill.after(activate_focused))
+            .add_system(update_tabs.after(activate_focused))
+            .add_system(update_widget_colors.after(activate_focused))
+            .add_system(restyle_widgets)
+            .add_system(restyle_panels);
+    }
+}
+
+#[derive(Actionlike, Clone, Copy)]
+pub enum MenuAction {
+    Up,
+    Down,
+    Left,
+    Right,
+    Confirm,
+    Back,
+    Settings,
+    Scoreboard,
+    SaveScenario,
+    ResetScenario,
+    Scenarios,
+}
+
+fn menu_input_map() -> InputMap<MenuAction> {
+    let mut input_map = InputMap::new([
+        (KeyCode::Up, MenuAction::Up),
+        (KeyCode::Down, MenuAction::Down),
+        (KeyCode::Left, MenuAction::Left),
+        (KeyCode::Right, MenuAction::Right),
+        (KeyCode::Return, MenuAction::Confirm),
+        (KeyCode::Back, MenuAction::Back),
+        (KeyCode::F1, MenuAction::Settings),
+        (KeyCode::Tab, MenuAction::Scoreboard),
+        (KeyCode::F5, MenuAction::SaveScenario),
+        (KeyCode::F6, MenuAction::ResetScenario),
+        (KeyCode::F8, MenuAction::Scenarios),
+    ]);
+    input_map.insert_multiple([
+        (GamepadButtonType::DPadUp, MenuAction::Up),
+        (GamepadButtonType::DPadDown, MenuAction::Down),
+        (GamepadButtonType::DPadLeft, MenuAction::Left),
+        (GamepadButtonType::DPadRight, MenuAction::Right),
+        (GamepadButtonType::South, MenuAction::Confirm),
+        (GamepadButtonType::East, MenuAction::Back),
+        (GamepadButtonType::Start, MenuAction::Settings),
+        (GamepadButtonType::Select, MenuAction::Scoreboard),
+        (GamepadButtonType::RightThumb, MenuAction::ResetScenario),
+    ]);
+    input_map
+}
+
+pub struct WidgetStyle {
+    pub text_color: Color,
+    pub accent_color: Color,
+    pub panel_color: Color,
+    pub normal_color: Color,
+    pub focused_color: Color,
+    pub pressed_color: Color,
+    pub width: f32,
+    pub height: f32,
+    pub panel: Option<PanelImage>,
+}
+
+pub struct PanelImage {
+    pub image: Handle<Image>,
+    pub border: u32,
+    pub slices: Option<[Handle<Image>; 9]>,
+}
+
+impl Default for WidgetStyle {
+    fn default() -> Self {
+        Self {
+            text_color: Color::WHITE,
+            accent_color: Color::rgb(0.9, 0.6, 0.1),
+            panel_color: Color::rgba(0.05, 0.05, 0.1, 0.9),
+            normal_color: Color::rgb(0.2, 0.2, 0.3),
+            focused_color: Color::rgb(0.35, 0.3, 0.5),
+            pressed_color: Color::rgb(0.5, 0.4, 0.7),
+            width: 320.0,
+            height: 40.0,
+            panel: None,
+        }
+    }
+}
+
+impl WidgetStyle {
+    fn widget(&self) -> ButtonBundle {
+        ButtonBundle {
+            style: Style {
+                size: Size::new(Val::Px(self.width), Val::Px(self.height)),
+                margin: UiRect::all(Val::Px(4.0)),
+                padding: UiRect::all(Val::Px(4.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            color: self.normal_color.into(),
+            ..default()
+        }
+    }
+}
+
+/// The widget that receives menu input.
+#[derive(Default)]
+pub struct Focus(pub Option<Entity>);
+
+#[derive(Component)]
+pub struct Focusable;
+
+#[derive(Component)]
+pub struct Caption(pub String);
+
+#[derive(Component)]
+struct WidgetText;
+
+#[derive(Component)]
+struct PanelSlice(usize);
+
+#[derive(Component)]
+pub struct Slider {
+    pub value: f32,
+    pub min: f32,
+    pub max: f32,
+    pub step: f32,
+}
+
+#[derive(Component)]
+struct SliderFill;
+
+#[derive(Component)]
+pub struct Toggle(pub bool);
+
+#[derive(Component)]
+pub struct Carousel {
+    pub options: Vec<String>,
+    pub selected: usize,
+}
+
+#[derive(Component)]
+pub struct TabBar {
+    pub selected: usize,
+    pub count: usize,
+}
+
+#[derive(Component)]
+struct Tab {
+    bar: Entity,
+    index: usize,
+}
+
+#[derive(Component)]
+pub struct TabPane {
+    pub bar: Entity,
+    pub index: usize,
+}
+
+pub enum WidgetEvent {
+    Pressed(Entity),
+    Changed(Entity),
+}
+
+pub fn panel<'w, 's, 'a>(
+    commands: &'a mut Commands<'w, 's>,
+    style: &WidgetStyle,
+) -> EntityCommands<'w, 's, 'a> {
+    let mut panel = commands.spawn_bundle(NodeBundle {
+        style: Style {
+            size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+            flex_direction: FlexDirection::ColumnReverse,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        color: Color::NONE.into(),
+        ..default()
+    });
+    panel.with_children(|panel| {
+        panel
+            .spawn_bundle(NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                    flex_direction: FlexDirection::ColumnReverse,
+                    ..default()
+                },
+                color: Color::NONE.into(),
+                ..default()
+            })
+            .with_children(|background| {
+                for row in 0..3 {
+                    background
+                        .spawn_bundle(NodeBundle {
+                            style: Style {
+                                flex_grow: if row == 1 { 1.0 } else { 0.0 },
+                                ..default()
+                            },
+                            color: Color::NONE.into(),
+                            ..default()
+                        })
+                        .with_children(|row_node| {
+                            for column in 0..3 {
+                                let index = row * 3 + column;
+                                row_node
+                                    .spawn_bundle(NodeBundle {
+                                        style: panel_slice_style(style, index),
+                                        color: panel_slice_color(style, index).into(),
+                                        ..default()
+                                    })
+                                    .insert(PanelSlice(index));
+                            }
+                        });
+                }
+            });
+    });
+    panel
+}
+
+fn panel_slice_style(style: &WidgetStyle, index: usize) -> Style {
+    let border = style
+        .panel
+        .as_ref()
+        .map_or(0.0, |panel| panel.border as f32);
+    let (row, column) = (index / 3, index % 3);
+    Style {
+        size: Size::new(
+            if column == 1 {
+                Val::Auto
+            } else {
+                Val::Px(border)
+            },
+            if row == 1 { Val::Auto } else { Val::Px(border) },
+        ),
+        flex_grow: if column == 1 { 1.0 } else { 0.0 },
+        ..default()
+    }
+}
+
+fn panel_slice_color(style: &WidgetStyle, index: usize) -> Color {
+    match &style.panel {
+        Some(PanelImage {
+            slices: Some(_), ..
+        }) => Color::WHITE,
+        _ if index == 4 => style.panel_color,
+        _ => Color::NONE,
+    }
+}
+
+pub fn button<'w, 's, 'a>(
+    parent: &'a mut ChildBuilder<'w, 's, '_>,
+    style: &WidgetStyle,
+    caption: &str,
+) -> EntityCommands<'w, 's, 'a> {
+    let mut button = parent.spawn_bundle(style.widget());
+    button
+        .insert_bundle((Focusable, Caption(caption.to_string())))
+        .with_children(|button| {
+            button
+                .spawn_bundle(styled_text(caption, "widget"))
+                .insert(WidgetText);
+        });
+    button
+}
+
+pub fn toggle<'w, 's, 'a>(
+    parent: &'a mut ChildBuilder<'w, 's, '_>,
+    style: &WidgetStyle,
+    caption: &str,
+    value: bool,
+) -> EntityCommands<'w, 's, 'a> {
+    let mut toggle = parent.spawn_bundle(style.widget());
+    toggle
+        .insert_bundle((Focusable, Caption(caption.to_string()), Toggle(value)))
+        .with_children(|toggle| {
+            toggle
+                .spawn_bundle(styled_text("", "widget"))
+                .insert(WidgetText);
+        });
+    toggle
+}
+
+pub fn slider<'w, 's, 'a>(
+    parent: &'a mut ChildBuilder<'w, 's, '_>,
+    style: &WidgetStyle,
+    caption: &str,
+    slider: Slider,
+) -> EntityCommands<'w, 's, 'a> {
+    let mut entity = parent.spawn_bundle(style.widget());
+    entity
+        .insert_bundle((Focusable, Caption(caption.to_string()), slider))
+        .with_children(|slider| {
+            slider
+                .spawn_bundle(NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        position: UiRect {
+                            left: Val::Px(0.0),
+                            bottom: Val::Px(0.0),
+                            ..default()
+                        },
+                        size: Size::new(Val::Percent(0.0), Val::Px(4.0)),
+                        ..default()
+                    },
+                    color: style.accent_color.into(),
+                    ..default()
+                })
+                .insert(SliderFill);
+            slider
+                .spawn_bundle(styled_text("", "widget"))
+                .insert(WidgetText);
+        });
+    entity
+}
+
+pub fn carousel<'w, 's, 'a>(
+    parent: &'a mut ChildBuilder<'w, 's, '_>,
+    style: &WidgetStyle,
+    caption: &str,
+    options: Vec<String>,
+    selected: usize,
+) -> EntityCommands<'w, 's, 'a> {
+    let mut carousel = parent.spawn_bundle(style.widget());
+    carousel
+        .insert_bundle((
+            Focusable,
+            Caption(caption.to_string()),
+            Carousel { options, selected },
+        ))
+        .with_children(|carousel| {
+            carousel
+                .spawn_bundle(styled_text("", "widget"))
+                .insert(WidgetText);
+        });
+    carousel
+}
+
+pub fn tab_bar<'w, 's, 'a>(
+    parent: &'a mut ChildBuilder<'w, 's, '_>,
+    style: &WidgetStyle,
+    captions: &[&str],
+) -> EntityCommands<'w, 's, 'a> {
+    let mut bar = parent.spawn_bundle(style.widget());
+    let entity = bar.id();
+    bar.insert_bundle((
+        Focusable,
+        TabBar {
+            selected: 0,
+            count: captions.len(),
+        },
+    ))
+    .with_children(|bar| {
+        for (index, caption) in captions.iter().enumerate() {
+            bar.spawn_bundle(
+                TextBundle::from_section(*caption, TextStyle::default()).with_style(Style {
+                    margin: UiRect::new(Val::Px(8.0), Val::Px(8.0), Val::Undefined, Val::Undefined),
+                    ..default()
+                }),
+            )
+            .insert_bundle((StyledText("widget"), Tab { bar: entity, index }));
+        }
+    });
+    bar
+}
+
+pub fn tab_pane<'w, 's, 'a>(
+    parent: &'a mut ChildBuilder<'w, 's, '_>,
+    bar: Entity,
+    index: usize,
+) -> EntityCommands<'w, 's, 'a> {
+    let mut pane = parent.spawn_bundle(NodeBundle {
+        style: Style {
+            flex_direction: FlexDirection::ColumnReverse,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        color: Color::NONE.into(),
+        ..default()
+    });
+    pane.insert(TabPane { bar, index });
+    pane
+}
+
+/// A line of values to plot on a [`graph`].
+pub struct Series {
+    pub color: Color,
+    pub values: Vec<f32>,
+}
+
+/// Plots each series as a row of points, with values from zero at the bottom to `max` at the
+/// top, spread evenly from left to right.
+pub fn graph<'w, 's, 'a>(
+    parent: &'a mut ChildBuilder<'w, 's, '_>,
+    style: &WidgetStyle,
+    size: Vec2,
+    max: f32,
+    series: &[Series],
+) -> EntityCommands<'w, 's, 'a> {
+    let mut graph = parent.spawn_bundle(NodeBundle {
+        style: Style {
+            size: Size::new(Val::Px(size.x), Val::Px(size.y)),
+            margin: UiRect::all(Val::Px(4.0)),
+            ..default()
+        },
+        color: style.normal_color.into(),
+        ..default()
+    });
+    graph.with_children(|graph| {
+        for line in series {
+            let last = line.values.len().saturating_sub(1).max(1) as f32;
+            for (index, value) in line.values.iter().enumerate() {
+                let x = index as f32 / last;
+                let y = if max > 0.0 {
+                    (value / max).clamp(0.0, 1.0)
+                } else {
+                    0.0
+                };
+                graph.spawn_bundle(NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        position: UiRect {
+                            left: Val::Px(x * (size.x - GRAPH_POINT_SIZE)),
+                            bottom: Val::Px(y * (size.y - GRAPH_POINT_SIZE)),
+                            ..default()
+                        },
+                        size: Size::new(Val::Px(GRAPH_POINT_SIZE), Val::Px(GRAPH_POINT_SIZE)),
+                        ..default()
+                    },
+                    color: line.color.into(),
+                    ..default()
+                });
+            }
+        }
+    });
+    graph
+}
+
+fn mouse_focus(
+    mut focus: ResMut<Focus>,
+    interactions: Query<(Entity, &Interaction), (Changed<Interaction>, With<Focusable>)>,
+) {
+    for (entity, interaction) in &interactions {
+        if matches!(interaction, Interaction::Hovered | Interaction::Clicked) {
+            focus.0 = Some(entity);
+        }
+    }
+}
+
+fn navigate_focus(
+    mut focus: ResMut<Focus>,
+    action_state: Res<ActionState<MenuAction>>,
+    roots: Query<Entity, (With<Node>, Without<Parent>)>,
+    children: Query<&Children>,
+    focusables: Query<&ComputedVisibility, With<Focusable>>,
+) {
+    let mut order = Vec::new();
+    let mut stack: Vec<Entity> = roots.iter().collect();
+    stack.sort();
+    stack.reverse();
+    while let Some(entity) = stack.pop() {
+        if let Ok(visibility) = focusables.get(entity) {
+            if visibility.is_visible() {
+                order.push(entity);
+            }
+        }
+        if let Ok(children) = children.get(entity) {
+            stack.extend(children.iter().rev());
+        }
+    }
+    if order.is_empty() {
+        focus.0 = None;
+        return;
+    }
+
+    let current = focus
+        .0
+        .and_then(|entity| order.iter().position(|e| *e == entity));
+    let next = if action_state.just_pressed(MenuAction::Down) {
+        current.map_or(0, |index| (index + 1) % order.len())
+    } else if action_state.just_pressed(MenuAction::Up) {
+        current.map_or(0, |index| (index + order.len() - 1) % order.len())
+    } else {
+        current.unwrap_or(0)
+    };
+    if focus.0 != Some(order[next]) {
+        focus.0 = Some(order[next]);
+    }
+}
+
+fn activate_focused(
+    focus: Res<Focus>,
+    action_state: Res<ActionState<MenuAction>>,
+    mut events: EventWriter<WidgetEvent>,
+    clicked: Query<&Interaction, (Changed<Interaction>, With<Focusable>)>,
+    mut sliders: Query<&mut Slider>,
+    mut toggles: Query<&mut Toggle>,
+    mut carousels: Query<&mut Carousel>,
+    mut tab_bars: Query<&mut TabBar>,
+) {
+    let entity = if let Some(entity) = focus.0 {
+        entity
+    } else {
+        return;
+    };
+    let confirm = action_state.just_pressed(MenuAction::Confirm)
+        || matches!(clicked.get(entity), Ok(Interaction::Clicked));
+    let direction = if action_state.just_pressed(MenuAction::Right) {
+        Some(true)
+    } else if action_state.just_pressed(MenuAction::Left) {
+        Some(false)
+    } else {
+        None
+    };
+    let forward = match (direction, confirm) {
+        (Some(forward), _) => forward,
+        (None, true) => true,
+        (None, false) => return,
+    };
+
+    if let Ok(mut slider) = sliders.get_mut(entity) {
+        if direction.is_some() {
+            let step = if forward { slider.step } else { -slider.step };
+            slider.value = (slider.value + step).clamp(slider.min, slider.max);
+            events.send(WidgetEvent::Changed(entity));
+        }
+    } else if let Ok(mut toggle) = toggles.get_mut(entity) {
+        toggle.0 = !toggle.0;
+        events.send(WidgetEvent::Changed(entity));
+    } else if let Ok(mut carousel) = carousels.get_mut(entity) {
+        carousel.selected = cycle(carousel.selected, forward, carousel.options.len());
+        events.send(WidgetEvent::Changed(entity));
+    } else if let Ok(mut tab_bar) = tab_bars.get_mut(entity) {
+        tab_bar.selected = cycle(tab_bar.selected, forward, tab_bar.count);
+        events.send(WidgetEvent::Changed(entity));
+    } else if confirm {
+        events.send(WidgetEvent::Pressed(entity));
+    }
+}
+
+fn cycle(index: usize, forward: bool, len: usize) -> usize {
+    if len == 0 {
+        0
+    } else if forward {
+        (index + 1) % len
+    } else {
+        (index + len - 1) % len
+    }
+}
+
+fn update_widget_text(
+    widgets: Query<
+        (
+            &Caption,
+            &Children,
+            Option<&Slider>,
+            Option<&Toggle>,
+            Option<&Carousel>,
+        ),
+        Or<(
+            Changed<Slider>,
+            Changed<Toggle>,
+            Changed<Carousel>,
+            Added<Caption>,
+        )>,
+    >,
+    mut texts: Query<&mut Text, With<WidgetText>>,
+) {
+    for (caption, children, slider, toggle, carousel) in &widgets {
+        let value = if let Some(slider) = slider {
+            format!("{}: {:.0}", caption.0, slider.value)
+        } else if let Some(toggle) = toggle {
+            format!("{}: {}", caption.0, if toggle.0 { "On" } else { "Off" })
+        } else if let Some(carousel) = carousel {
+            let option = carousel
+                .options
+                .get(carousel.selected)
+                .map_or("", String::as_str);
+            format!("{}: < {option} >", caption.0)
+        } else {
+            caption.0.clone()
+        };
+        for child in children {
+            if let Ok(mut text) = texts.get_mut(*child) {
+                text.sections[0].value = value.clone();
+            }
+        }
+    }
+}
+
+fn update_slider_fill(
+    sliders: Query<(&Slider, &Children), Changed<Slider>>,
+    mut fills: Query<&mut Style, With<SliderFill>>,
+) {
+    for (slider, children) in &sliders {
+        let fraction = if slider.max > slider.min {
+            (slider.value - slider.min) / (slider.max - slider.min)
+        } else {
+            0.0
+        };
+        for child in children {
+            if let Ok(mut style) = fills.get_mut(*child) {
+                style.size.width = Val::Percent(fraction * 100.0);
+            }
+        }
+    }
+}
+
+fn update_tabs(
+    style: Res<WidgetStyle>,
+    tab_bars: Query<&TabBar>,
+    mut tabs: Query<(&Tab, &mut Text)>,
+    mut panes: Query<(&TabPane, &mut Style, &mut Visibility)>,
+) {
+    for (tab, mut text) in &mut tabs {
+        if let Ok(bar) = tab_bars.get(tab.bar) {
+            text.sections[0].style.color = if bar.selected == tab.index {
+                style.accent_color
+            } else {
+                style.text_color
+            };
+        }
+    }
+    for (pane, mut pane_style, mut visibility) in &mut panes {
+        if let Ok(bar) = tab_bars.get(pane.bar) {
+            let selected = bar.selected == pane.index;
+            let display = if selected {
+                Display::Flex
+            } else {
+                Display::None
+            };
+            if pane_style.display != display {
+                pane_style.display = display;
+            }
+            // Hidden too, so focus skips the widgets of the other tabs.
+            if visibility.is_visible != selected {
+                visibility.is_visible = selected;
+            }
+        }
+    }
+}
+
+fn update_widget_colors(
+    focus: Res<Focus>,
+    style: Res<WidgetStyle>,
+    mut widgets: Query<(Entity, &Interaction, &mut UiColor), With<Focusable>>,
+) {
+    for (entity, interaction, mut color) in &mut widgets {
+        let target = match (interaction, focus.0 == Some(entity)) {
+            (Interaction::Clicked, _) => style.pressed_color,
+            (_, true) => style.focused_color,
+            _ => style.normal_color,
+        };
+        if color.0 != target {
+            color.0 = target;
+        }
+    }
+}
+
+fn restyle_widgets(
+    style: Res<WidgetStyle>,
+    mut widgets: Query<&mut Style, With<Focusable>>,
+    mut fills: Query<&mut UiColor, With<SliderFill>>,
+) {
+    if !style.is_changed() {
+        return;
+    }
+    for mut widget_style in &mut widgets {
+        widget_style.size = Size::new(Val::Px(style.width), Val::Px(style.height));
+    }
+    for mut color in &mut fills {
+        color.0 = style.accent_color;
+    }
+}
+
+fn restyle_panels(
+    style: Res<WidgetStyle>,
+    mut slices: Query<(&PanelSlice, &mut Style, &mut UiColor, &mut UiImage)>,
+) {
+    if !style.is_changed() {
+        return;
+    }
+    for (slice, mut slice_style, mut color, mut image) in &mut slices {
+        *slice_style = panel_slice_style(&style, slice.0);
+        color.0 = panel_slice_color(&style, slice.0);
+        *image = match &style.panel {
+            Some(PanelImage {
+                slices: Some(slices),
+                ..
+            }) => UiImage(slices[slice.0].clone()),
+            _ => UiImage::default(),
+        };
+    }
+}